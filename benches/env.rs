@@ -0,0 +1,111 @@
+//! Compares variable lookup in the arena environment with the heap-allocated
+//! `Rc<RefCell<Env>>` design it replaced. Run with `cargo bench --bench env`.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::rc::Rc;
+use std::time::Instant;
+use tyson::MemoryBlock as Block;
+use tyson::env::Env;
+use tyson::read::Atom;
+
+const DEPTH: usize = 8;
+const WIDTH: usize = 4;
+const ROUNDS: usize = 100_000;
+
+// The previous environment, kept here as the baseline.
+#[derive(Default)]
+struct HeapEnv<'a> {
+    parent: Option<Rc<RefCell<HeapEnv<'a>>>>,
+    vars: HashMap<&'a str, Atom<'a>>,
+}
+
+impl<'a> HeapEnv<'a> {
+    fn get(&self, name: &str) -> Option<Atom<'a>> {
+        match self.vars.get(name) {
+            Some(value) => Some(*value),
+            None => self.parent.as_ref().and_then(|p| p.borrow().get(name)),
+        }
+    }
+}
+
+fn time(label: &str, lookups: usize, mut run: impl FnMut()) {
+    let start = Instant::now();
+    run();
+    let elapsed = start.elapsed();
+    let each = elapsed.as_nanos() as f64 / lookups as f64;
+    println!("{label:<24} {:>10.2?} {each:>8.2} ns/lookup", elapsed);
+}
+
+fn main() {
+    let names: Vec<String> = (0..DEPTH * WIDTH).map(|i| format!("v{i}")).collect();
+    let lookups = ROUNDS * names.len();
+
+    let mut heap = Rc::new(RefCell::new(HeapEnv::default()));
+    for (i, name) in names.iter().enumerate() {
+        if i > 0 && i % WIDTH == 0 {
+            heap = Rc::new(RefCell::new(HeapEnv {
+                parent: Some(heap),
+                vars: HashMap::new(),
+            }));
+        }
+        heap.borrow_mut()
+            .vars
+            .insert(name.as_str(), Atom::Int { inner: i as i64 });
+    }
+
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(256 * 1024).unwrap();
+    let mut env = Env::new(&arena).unwrap();
+    for (i, name) in names.iter().enumerate() {
+        if i > 0 && i % WIDTH == 0 {
+            env = Env::extend(&env, WIDTH).unwrap();
+        }
+        env.define(name, Atom::Int { inner: i as i64 });
+    }
+
+    let symbols: Vec<_> = names
+        .iter()
+        .map(|name| env.symbols().get(name).unwrap())
+        .collect();
+    let addresses: Vec<_> = symbols
+        .iter()
+        .map(|symbol| env.resolve(*symbol).unwrap())
+        .collect();
+
+    println!("{lookups} lookups over {DEPTH} frames of {WIDTH}");
+
+    let heap = heap.borrow();
+    time("heap env by name", lookups, || {
+        for _ in 0..ROUNDS {
+            for name in &names {
+                black_box(heap.get(black_box(name)));
+            }
+        }
+    });
+
+    time("arena env by name", lookups, || {
+        for _ in 0..ROUNDS {
+            for name in &names {
+                black_box(env.get(black_box(name)));
+            }
+        }
+    });
+
+    time("arena env by symbol", lookups, || {
+        for _ in 0..ROUNDS {
+            for symbol in &symbols {
+                black_box(env.lookup(black_box(*symbol)));
+            }
+        }
+    });
+
+    time("arena env by address", lookups, || {
+        for _ in 0..ROUNDS {
+            for address in &addresses {
+                black_box(env.get_at(black_box(*address)));
+            }
+        }
+    });
+}
@@ -4,7 +4,7 @@ use core::fmt::Write;
 use core::marker::PhantomData;
 use core::ops::{Deref, DerefMut};
 use core::ptr::NonNull;
-use libc::{MAP_ANONYMOUS, MAP_PRIVATE, PROT_READ, PROT_WRITE, mmap, munmap};
+use libc::{MAP_ANONYMOUS, MAP_FAILED, MAP_PRIVATE, PROT_READ, PROT_WRITE, mmap, munmap};
 
 type Byte = u8;
 
@@ -13,6 +13,9 @@ pub struct MemoryBlock {
     buffer: *mut Byte,
     capacity: usize,
     len: Cell<usize>,
+    // Whether the block's memory may have been written other than through
+    // its arenas, which clear what they used when dropped.
+    dirty: Cell<bool>,
 }
 
 #[derive(Debug)]
@@ -30,7 +33,14 @@ pub struct StrSink<'a> {
 }
 
 impl MemoryBlock {
+    /// Maps a block of `capacity` bytes, panicking if the mapping fails.
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::try_with_capacity(capacity).expect("Unable to map memory")
+    }
+
+    /// Like `with_capacity`, but returns `None` if the memory cannot be
+    /// mapped.
+    pub fn try_with_capacity(capacity: usize) -> Option<Self> {
         let buffer = unsafe {
             mmap(
                 core::ptr::null_mut(),
@@ -39,14 +49,19 @@ impl MemoryBlock {
                 MAP_ANONYMOUS | MAP_PRIVATE,
                 0,
                 0,
-            ) as *mut Byte
+            )
         };
 
-        MemoryBlock {
-            buffer,
+        if buffer == MAP_FAILED {
+            return None;
+        }
+
+        Some(MemoryBlock {
+            buffer: buffer as *mut Byte,
             capacity,
             len: Cell::new(0),
-        }
+            dirty: Cell::new(false),
+        })
     }
 
     pub fn buffer(&self) -> *mut Byte {
@@ -57,11 +72,15 @@ impl MemoryBlock {
         self.len.get()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub fn capacity(&self) -> usize {
         self.capacity
     }
 
-    pub fn arena<'a>(&self, size: usize) -> Option<Arena<'a>> {
+    pub fn arena<'a>(&'a self, size: usize) -> Option<Arena<'a>> {
         let current = self.len();
         let end = current + size;
 
@@ -71,7 +90,25 @@ impl MemoryBlock {
 
         self.len.set(end);
         let slice = &self[current..end];
-        Some(unsafe { Arena::from_raw_parts_mut(slice.as_ptr() as *mut Byte, slice.len()) })
+        let ptr = slice.as_ptr() as *mut Byte;
+
+        // A fresh mapping reads as zeroes and each part of the block is only
+        // handed out once, so unless the block was written directly there is
+        // nothing to clear. Skipping it leaves the pages an arena never
+        // touches uncommitted.
+        if self.dirty.get() {
+            Some(unsafe { Arena::from_raw_parts_mut(ptr, slice.len()) })
+        } else {
+            Some(Arena::from_zeroed_parts(ptr, slice.len()))
+        }
+    }
+
+    pub fn arenas<'a>(&'a self, count: usize, size: usize) -> Option<Vec<Arena<'a>>> {
+        if self.len().checked_add(count.checked_mul(size)?)? > self.capacity {
+            return None;
+        }
+
+        (0..count).map(|_| self.arena(size)).collect()
     }
 
     pub fn split(&self) -> (&[Byte], &[Byte]) {
@@ -82,7 +119,13 @@ impl MemoryBlock {
         self.split().0
     }
 
+    /// # Safety
+    ///
+    /// The returned slice aliases the unallocated tail of the buffer; callers
+    /// must not hold it across further allocations.
+    #[allow(clippy::mut_from_ref)]
     pub unsafe fn remaining(&self) -> &mut [Byte] {
+        self.dirty.set(true);
         let remaining = self.split().1;
 
         unsafe { core::slice::from_raw_parts_mut(remaining.as_ptr() as *mut Byte, remaining.len()) }
@@ -90,16 +133,25 @@ impl MemoryBlock {
 }
 
 impl Arena<'_> {
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reads and writes of `capacity` bytes for the
+    /// lifetime of the arena.
     pub unsafe fn from_raw_parts_mut(ptr: *mut Byte, capacity: usize) -> Self {
         unsafe {
             core::ptr::write_bytes(ptr, 0, capacity);
         }
 
+        Self::from_zeroed_parts(ptr, capacity)
+    }
+
+    // For memory known to be zeroed and valid for the arena's lifetime.
+    fn from_zeroed_parts(ptr: *mut Byte, capacity: usize) -> Self {
         Arena {
             buffer: ptr,
             capacity,
             len: Cell::new(0),
-            phantom: PhantomData::default(),
+            phantom: PhantomData,
         }
     }
 
@@ -107,6 +159,10 @@ impl Arena<'_> {
         self.len.get()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub fn capacity(&self) -> usize {
         self.capacity
     }
@@ -146,6 +202,11 @@ impl Arena<'_> {
         self.split().0
     }
 
+    /// # Safety
+    ///
+    /// The returned slice aliases the unallocated tail of the buffer; callers
+    /// must not hold it across further allocations.
+    #[allow(clippy::mut_from_ref)]
     pub unsafe fn remaining(&self) -> &mut [Byte] {
         let remaining = self.split().1;
 
@@ -153,6 +214,15 @@ impl Arena<'_> {
     }
 }
 
+// An arena owns a disjoint region of its block and borrows the block for
+// its lifetime, so it can be handed to another thread: the borrow keeps the
+// block mapped until the thread is done with it.
+unsafe impl Send for Arena<'_> {}
+
+// A block owns its mapping outright, so it can move to the thread that
+// will carve arenas out of it.
+unsafe impl Send for MemoryBlock {}
+
 impl Drop for MemoryBlock {
     fn drop(&mut self) {
         unsafe {
@@ -171,11 +241,12 @@ impl Deref for MemoryBlock {
 
 impl DerefMut for MemoryBlock {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        self.dirty.set(true);
         unsafe { core::slice::from_raw_parts_mut(self.buffer, self.capacity) }
     }
 }
 
-impl<'a> Drop for Arena<'_> {
+impl Drop for Arena<'_> {
     fn drop(&mut self) {
         let len = self.len.get();
         unsafe {
@@ -184,7 +255,7 @@ impl<'a> Drop for Arena<'_> {
     }
 }
 
-impl<'a> Deref for Arena<'_> {
+impl Deref for Arena<'_> {
     type Target = [Byte];
 
     fn deref(&self) -> &Self::Target {
@@ -192,7 +263,7 @@ impl<'a> Deref for Arena<'_> {
     }
 }
 
-impl<'a> DerefMut for Arena<'_> {
+impl DerefMut for Arena<'_> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         unsafe { core::slice::from_raw_parts_mut(self.buffer, self.capacity) }
     }
@@ -0,0 +1,166 @@
+use crate::pair;
+use crate::read::{Atom, Expression, Span};
+use crate::{Arena, Array, make};
+
+type CharResult<'arena> = Result<Atom<'arena>, &'static str>;
+
+const NAMES: &[(&str, char)] = &[
+    ("alarm", '\u{7}'),
+    ("backspace", '\u{8}'),
+    ("delete", '\u{7f}'),
+    ("escape", '\u{1b}'),
+    ("newline", '\n'),
+    ("null", '\0'),
+    ("return", '\r'),
+    ("space", ' '),
+    ("tab", '\t'),
+];
+
+// The single-letter escapes allowed in string literals, as in R7RS.
+const ESCAPES: &[(char, char)] = &[
+    ('a', '\u{7}'),
+    ('b', '\u{8}'),
+    ('t', '\t'),
+    ('n', '\n'),
+    ('r', '\r'),
+    ('"', '"'),
+    ('\\', '\\'),
+    ('|', '|'),
+];
+
+// Parses the text following `#\`: a single character, a name such as
+// `space`, or a hex scalar value such as `x41`.
+pub fn parse_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    let first = chars.next()?;
+
+    if chars.next().is_none() {
+        return Some(first);
+    }
+
+    if let Some((_, c)) = NAMES.iter().find(|(name, _)| *name == text) {
+        return Some(*c);
+    }
+
+    let hex = text.strip_prefix('x')?;
+    char::from_u32(u32::from_str_radix(hex, 16).ok()?)
+}
+
+pub fn char_name(c: char) -> Option<&'static str> {
+    NAMES
+        .iter()
+        .find(|(_, named)| *named == c)
+        .map(|(name, _)| *name)
+}
+
+/// The character written as `\` followed by `letter` in a string.
+pub fn unescape_char(letter: char) -> Option<char> {
+    ESCAPES
+        .iter()
+        .find(|(escape, _)| *escape == letter)
+        .map(|(_, c)| *c)
+}
+
+/// The letter that follows `\` when `c` is written in a string, if it has
+/// one. `|` is left alone since it reads fine unescaped.
+pub fn escape_char(c: char) -> Option<char> {
+    ESCAPES
+        .iter()
+        .find(|(escape, unescaped)| *unescaped == c && *escape != '|')
+        .map(|(escape, _)| *escape)
+}
+
+pub fn char_to_integer<'arena>(c: &Atom) -> CharResult<'arena> {
+    match c {
+        Atom::Char { inner } => Ok(Atom::Int {
+            inner: *inner as i64,
+        }),
+        _ => Err("Not a character"),
+    }
+}
+
+pub fn integer_to_char<'arena>(n: &Atom) -> CharResult<'arena> {
+    let Atom::Int { inner } = n else {
+        return Err("Not an integer");
+    };
+
+    u32::try_from(*inner)
+        .ok()
+        .and_then(char::from_u32)
+        .map(|inner| Atom::Char { inner })
+        .ok_or("Not a Unicode scalar value")
+}
+
+pub fn char_alphabetic<'arena>(c: &Atom) -> CharResult<'arena> {
+    match c {
+        Atom::Char { inner } if inner.is_alphabetic() => Ok(Atom::True),
+        Atom::Char { .. } => Ok(Atom::False),
+        _ => Err("Not a character"),
+    }
+}
+
+pub fn string_ref<'arena>(s: &Atom, k: &Atom) -> CharResult<'arena> {
+    let (Atom::String { inner }, Atom::Int { inner: index }) = (s, k) else {
+        return Err("Expected a string and an index");
+    };
+
+    usize::try_from(*index)
+        .ok()
+        .and_then(|index| inner.chars().nth(index))
+        .map(|inner| Atom::Char { inner })
+        .ok_or("String index out of range")
+}
+
+pub fn string_to_list<'arena>(arena: &'arena Arena<'arena>, s: &Atom) -> CharResult<'arena> {
+    let Atom::String { inner } = s else {
+        return Err("Not a string");
+    };
+
+    let count = inner.chars().count();
+    if count == 0 {
+        return Ok(Atom::Void);
+    }
+
+    let mut body = make!(arena, Expression, count)
+        .map(Array::new)
+        .ok_or("Failed to allocate list")?;
+
+    for inner in inner.chars() {
+        body.push(&Expression {
+            depth: 1,
+            payload: Atom::Char { inner },
+            span: Span::default(),
+        });
+    }
+
+    Ok(Atom::List { body })
+}
+
+pub fn list_to_string<'arena>(
+    arena: &'arena Arena<'arena>,
+    list: &Atom<'arena>,
+) -> CharResult<'arena> {
+    let mut items = pair::items(*list);
+    let mut len = 0;
+    for item in items.by_ref() {
+        match item {
+            Atom::Char { inner } => len += inner.len_utf8(),
+            _ => return Err("List contains a non-character"),
+        }
+    }
+    if !pair::is_null(&items.rest()) {
+        return Err("Not a list");
+    }
+
+    let buffer = make!(arena, u8, len).ok_or("Failed to allocate string")?;
+    let mut used = 0;
+    for item in pair::items(*list) {
+        if let Atom::Char { inner } = item {
+            used += inner.encode_utf8(&mut buffer[used..]).len();
+        }
+    }
+
+    core::str::from_utf8(buffer)
+        .map(|inner| Atom::String { inner })
+        .map_err(|_| "Invalid UTF-8")
+}
@@ -0,0 +1,680 @@
+use crate::expand::{expand, is_colon};
+use crate::read::{Atom, Expression};
+use crate::types::Type;
+use crate::{Arena, List};
+use core::fmt::{Display, Formatter};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exact(usize),
+    AtLeast(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiagnosticKind<'arena> {
+    Unbound {
+        name: &'arena str,
+    },
+    ArityMismatch {
+        name: &'arena str,
+        expected: Arity,
+        found: usize,
+    },
+    DuplicateDefinition {
+        name: &'arena str,
+    },
+    Malformed {
+        form: &'arena str,
+    },
+    TypeMismatch {
+        expected: Type<'arena>,
+        found: Type<'arena>,
+    },
+    UnknownType {
+        name: &'arena str,
+    },
+    Forbidden {
+        name: &'arena str,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Diagnostic<'arena> {
+    pub kind: DiagnosticKind<'arena>,
+    pub expr: Expression<'arena>,
+}
+
+#[derive(Debug, Default)]
+pub struct Checker<'arena> {
+    globals: HashMap<&'arena str, Option<Arity>>,
+    forbidden: HashSet<&'arena str>,
+}
+
+/// A lexical scope, mapping each name bound in it to what a pass knows
+/// about the name: its arity here, its type in `types`.
+pub(crate) struct Scope<'s, 'arena, T> {
+    parent: Option<&'s Scope<'s, 'arena, T>>,
+    pub(crate) bindings: HashMap<&'arena str, T>,
+}
+
+/// One walk over some code by `checker`, collecting its diagnostics.
+pub(crate) struct Pass<'c, 'arena, C> {
+    pub(crate) arena: &'arena Arena<'arena>,
+    pub(crate) checker: &'c C,
+    pub(crate) diagnostics: List<'arena, Diagnostic<'arena>>,
+}
+
+impl Arity {
+    pub fn accepts(&self, count: usize) -> bool {
+        match *self {
+            Arity::Exact(n) => count == n,
+            Arity::AtLeast(n) => count >= n,
+        }
+    }
+}
+
+impl<'arena> Checker<'arena> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn declare(&mut self, name: &'arena str, arity: Option<Arity>) {
+        self.globals.insert(name, arity);
+    }
+
+    /// Reports references to `name` as forbidden rather than unbound, for
+    /// primitives a sandbox leaves out. File literals are forbidden along
+    /// with `load`.
+    pub fn forbid(&mut self, name: &'arena str) {
+        self.forbidden.insert(name);
+    }
+
+    /// Resolves every symbol in `root` against the scopes introduced by
+    /// `define`, `lambda` and the `let` family, returning the problems found.
+    /// Derived forms such as `match` and `define-record-type` bind names of
+    /// their own, so `root` is expanded first; a form that does not expand
+    /// is reported as malformed and left out.
+    pub fn check(
+        &self,
+        arena: &'arena Arena<'arena>,
+        root: &[Expression<'arena>],
+    ) -> List<'arena, Diagnostic<'arena>> {
+        let mut pass = Pass::new(arena, self);
+        let mut expanded = Vec::with_capacity(root.len());
+        for expr in root {
+            match expand(arena, core::slice::from_ref(expr)) {
+                Ok(exprs) => expanded.extend_from_slice(&exprs[..exprs.len()]),
+                Err(_) => pass.report(DiagnosticKind::Malformed { form: head(expr) }, expr),
+            }
+        }
+
+        let mut scope = Scope::default();
+        pass.body(&mut scope, &expanded);
+        pass.diagnostics
+    }
+}
+
+impl<'s, 'arena, T: Copy> Scope<'s, 'arena, T> {
+    pub(crate) fn extend(parent: &'s Scope<'s, 'arena, T>) -> Self {
+        Scope {
+            parent: Some(parent),
+            bindings: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn lookup(&self, name: &str) -> Option<T> {
+        match self.bindings.get(name) {
+            Some(value) => Some(*value),
+            None => self.parent.and_then(|p| p.lookup(name)),
+        }
+    }
+}
+
+impl<T> Default for Scope<'_, '_, T> {
+    fn default() -> Self {
+        Scope {
+            parent: None,
+            bindings: HashMap::new(),
+        }
+    }
+}
+
+impl<'c, 'arena, C> Pass<'c, 'arena, C> {
+    pub(crate) fn new(arena: &'arena Arena<'arena>, checker: &'c C) -> Self {
+        Pass {
+            arena,
+            checker,
+            diagnostics: List::new(arena),
+        }
+    }
+
+    pub(crate) fn report(&mut self, kind: DiagnosticKind<'arena>, expr: &Expression<'arena>) {
+        self.diagnostics
+            .push_back(&Diagnostic { kind, expr: *expr });
+    }
+}
+
+type Arities<'s, 'arena> = Scope<'s, 'arena, Option<Arity>>;
+
+impl<'c, 'arena> Pass<'c, 'arena, Checker<'arena>> {
+    fn unbound(&mut self, name: &'arena str, expr: &Expression<'arena>) {
+        let kind = if self.checker.forbidden.contains(name) {
+            DiagnosticKind::Forbidden { name }
+        } else {
+            DiagnosticKind::Unbound { name }
+        };
+        self.report(kind, expr);
+    }
+
+    fn resolve(&self, scope: &Arities<'_, 'arena>, name: &str) -> Option<Option<Arity>> {
+        if is_syntax(name) {
+            return Some(None);
+        }
+
+        scope
+            .lookup(name)
+            .or_else(|| self.checker.globals.get(name).copied())
+    }
+
+    // Definitions in a body are visible to every expression in it, so they
+    // are collected before any reference is resolved.
+    fn body(&mut self, scope: &mut Arities<'_, 'arena>, exprs: &[Expression<'arena>]) {
+        for expr in exprs {
+            self.hoist(scope, expr);
+        }
+
+        for expr in exprs {
+            self.expression(scope, expr);
+        }
+    }
+
+    // A `begin` in a body splices its forms into it, so its definitions
+    // are hoisted along with the rest.
+    fn hoist(&mut self, scope: &mut Arities<'_, 'arena>, expr: &Expression<'arena>) {
+        if let Some(forms) = begin(expr) {
+            for form in forms {
+                self.hoist(scope, form);
+            }
+        } else if let Some((name, arity, target)) = definition(expr) {
+            if scope.bindings.contains_key(name) {
+                self.report(DiagnosticKind::DuplicateDefinition { name }, target);
+            }
+            scope.bindings.insert(name, arity);
+        }
+    }
+
+    fn expression(&mut self, scope: &Arities<'_, 'arena>, expr: &Expression<'arena>) {
+        match expr.payload {
+            Atom::Symbol { name } if self.resolve(scope, name).is_none() => {
+                self.unbound(name, expr);
+            }
+            Atom::File { .. } if self.checker.forbidden.contains("load") => {
+                self.report(DiagnosticKind::Forbidden { name: "load" }, expr);
+            }
+            Atom::List { ref body } => self.form(scope, expr, &body[..body.len()]),
+            Atom::Vector { ref body } => {
+                for item in &body[..body.len()] {
+                    self.expression(scope, item);
+                }
+            }
+            Atom::Map { map } => {
+                for value in map.values() {
+                    self.expression(scope, value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn form(
+        &mut self,
+        scope: &Arities<'_, 'arena>,
+        expr: &Expression<'arena>,
+        body: &[Expression<'arena>],
+    ) {
+        let Some(head) = body.first() else {
+            return;
+        };
+        let args = &body[1..];
+
+        match head.payload {
+            Atom::Define => self.define(scope, expr, args),
+            Atom::Symbol { name } => match name {
+                "quote" | "quasiquote" => {}
+                "lambda" => self.lambda(scope, expr, args),
+                "let" | "let*" | "letrec" | "letrec*" => self.let_form(scope, expr, name, args),
+                "let1" => self.let1(scope, expr, args),
+                "receive" => self.receive(scope, expr, args),
+                "set!" => {
+                    for arg in args {
+                        self.expression(scope, arg);
+                    }
+                }
+                "cond" => {
+                    for clause in args {
+                        match clause.payload {
+                            Atom::List { ref body } => {
+                                for part in &body[..body.len()] {
+                                    self.expression(scope, part);
+                                }
+                            }
+                            _ => self.report(DiagnosticKind::Malformed { form: "cond" }, clause),
+                        }
+                    }
+                }
+                _ => {
+                    match self.resolve(scope, name) {
+                        None => self.unbound(name, head),
+                        Some(Some(expected)) if !expected.accepts(args.len()) => self.report(
+                            DiagnosticKind::ArityMismatch {
+                                name,
+                                expected,
+                                found: args.len(),
+                            },
+                            expr,
+                        ),
+                        Some(_) => {}
+                    }
+
+                    for arg in args {
+                        self.expression(scope, arg);
+                    }
+                }
+            },
+            atom => {
+                if let Some((name, expected)) = builtin(&atom)
+                    && !expected.accepts(args.len())
+                {
+                    self.report(
+                        DiagnosticKind::ArityMismatch {
+                            name,
+                            expected,
+                            found: args.len(),
+                        },
+                        expr,
+                    );
+                }
+
+                for part in body {
+                    self.expression(scope, part);
+                }
+            }
+        }
+    }
+
+    fn define(
+        &mut self,
+        scope: &Arities<'_, 'arena>,
+        expr: &Expression<'arena>,
+        args: &[Expression<'arena>],
+    ) {
+        let (target, rest) = match args.split_first() {
+            Some((target, rest)) => (target.payload, unannotated(rest)),
+            None => return self.report(DiagnosticKind::Malformed { form: "define" }, expr),
+        };
+
+        match target {
+            Atom::Symbol { .. } if rest.len() == 1 => self.expression(scope, &rest[0]),
+            Atom::List { body: signature } if !rest.is_empty() => {
+                let signature = &signature[..signature.len()];
+                let mut inner = Scope::extend(scope);
+                self.formals(&mut inner, &signature[1..]);
+                self.body(&mut inner, rest);
+            }
+            _ => self.report(DiagnosticKind::Malformed { form: "define" }, expr),
+        }
+    }
+
+    fn lambda(
+        &mut self,
+        scope: &Arities<'_, 'arena>,
+        expr: &Expression<'arena>,
+        args: &[Expression<'arena>],
+    ) {
+        let body = unannotated(args.get(1..).unwrap_or_default());
+        if body.is_empty() {
+            return self.report(DiagnosticKind::Malformed { form: "lambda" }, expr);
+        }
+
+        let mut inner = Scope::extend(scope);
+        match args[0].payload {
+            Atom::List { ref body } => self.formals(&mut inner, &body[..body.len()]),
+            Atom::Symbol { name } => {
+                inner.bindings.insert(name, None);
+            }
+            Atom::Void => {}
+            _ => return self.report(DiagnosticKind::Malformed { form: "lambda" }, expr),
+        }
+        self.body(&mut inner, body);
+    }
+
+    fn let_form(
+        &mut self,
+        scope: &Arities<'_, 'arena>,
+        expr: &Expression<'arena>,
+        form: &'arena str,
+        args: &[Expression<'arena>],
+    ) {
+        // Named let binds the loop procedure around the body.
+        let (label, args) = match args.first().map(|e| e.payload) {
+            Some(Atom::Symbol { name }) => (Some(name), &args[1..]),
+            _ => (None, args),
+        };
+
+        let bindings = match args.first().map(|e| e.payload) {
+            Some(Atom::List { body }) if args.len() >= 2 => body,
+            Some(Atom::Void) if args.len() >= 2 => {
+                let mut inner = Scope::extend(scope);
+                if let Some(label) = label {
+                    inner.bindings.insert(label, Some(Arity::Exact(0)));
+                }
+                return self.body(&mut inner, &args[1..]);
+            }
+            _ => return self.report(DiagnosticKind::Malformed { form }, expr),
+        };
+        let bindings = &bindings[..bindings.len()];
+
+        let mut outer = Scope::extend(scope);
+        if let Some(label) = label {
+            outer
+                .bindings
+                .insert(label, Some(Arity::Exact(bindings.len())));
+        }
+
+        let sequential = form != "let";
+        let recursive = form.starts_with("letrec");
+        let mut inner = Scope::extend(&outer);
+
+        if recursive {
+            for binding in bindings {
+                if let Some((name, value)) = let_binding(binding) {
+                    inner
+                        .bindings
+                        .insert(name, value.and_then(|v| procedure_arity(&v)));
+                }
+            }
+        }
+
+        for binding in bindings {
+            let Some((name, value)) = let_binding(binding) else {
+                self.report(DiagnosticKind::Malformed { form }, binding);
+                continue;
+            };
+
+            if let Some(value) = value {
+                if sequential {
+                    self.expression(&inner, &value);
+                } else {
+                    self.expression(scope, &value);
+                }
+            }
+
+            if !recursive && inner.bindings.contains_key(name) && !sequential {
+                self.report(DiagnosticKind::DuplicateDefinition { name }, binding);
+            }
+            inner
+                .bindings
+                .insert(name, value.and_then(|v| procedure_arity(&v)));
+        }
+
+        self.body(&mut inner, &args[1..]);
+    }
+
+    fn let1(
+        &mut self,
+        scope: &Arities<'_, 'arena>,
+        expr: &Expression<'arena>,
+        args: &[Expression<'arena>],
+    ) {
+        match args.first().map(|e| e.payload) {
+            Some(Atom::Symbol { name }) if args.len() >= 3 => {
+                self.expression(scope, &args[1]);
+                let mut inner = Scope::extend(scope);
+                inner.bindings.insert(name, procedure_arity(&args[1]));
+                self.body(&mut inner, &args[2..]);
+            }
+            _ => self.report(DiagnosticKind::Malformed { form: "let1" }, expr),
+        }
+    }
+
+    fn receive(
+        &mut self,
+        scope: &Arities<'_, 'arena>,
+        expr: &Expression<'arena>,
+        args: &[Expression<'arena>],
+    ) {
+        if args.len() < 3 {
+            return self.report(DiagnosticKind::Malformed { form: "receive" }, expr);
+        }
+
+        self.expression(scope, &args[1]);
+        let mut inner = Scope::extend(scope);
+        match args[0].payload {
+            Atom::List { ref body } => self.formals(&mut inner, &body[..body.len()]),
+            Atom::Symbol { name } => {
+                inner.bindings.insert(name, None);
+            }
+            _ => return self.report(DiagnosticKind::Malformed { form: "receive" }, expr),
+        }
+        self.body(&mut inner, &args[2..]);
+    }
+
+    // A parameter may carry a type annotation, `(name : Type)`, which is
+    // left for the type checker.
+    fn formals(&mut self, scope: &mut Arities<'_, 'arena>, params: &[Expression<'arena>]) {
+        for param in params {
+            let name = match param.payload {
+                Atom::Symbol { name: "." } => continue,
+                Atom::Symbol { name } => name,
+                Atom::List { ref body } => match &body[..body.len()] {
+                    [name, colon, _] if is_colon(colon) => match name.payload {
+                        Atom::Symbol { name } => name,
+                        _ => {
+                            self.report(DiagnosticKind::Malformed { form: "parameter" }, param);
+                            continue;
+                        }
+                    },
+                    _ => {
+                        self.report(DiagnosticKind::Malformed { form: "parameter" }, param);
+                        continue;
+                    }
+                },
+                _ => {
+                    self.report(DiagnosticKind::Malformed { form: "parameter" }, param);
+                    continue;
+                }
+            };
+
+            if scope.bindings.insert(name, None).is_some() {
+                self.report(DiagnosticKind::DuplicateDefinition { name }, param);
+            }
+        }
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Arity::Exact(n) => write!(f, "{n}"),
+            Arity::AtLeast(n) => write!(f, "at least {n}"),
+        }
+    }
+}
+
+impl Display for DiagnosticKind<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DiagnosticKind::Unbound { name } => write!(f, "unbound symbol `{name}`"),
+            DiagnosticKind::ArityMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{name}` expects {expected} argument(s) but was given {found}"
+            ),
+            DiagnosticKind::DuplicateDefinition { name } => {
+                write!(f, "`{name}` is defined more than once in the same scope")
+            }
+            DiagnosticKind::Malformed { form } => write!(f, "malformed `{form}` form"),
+            DiagnosticKind::TypeMismatch { expected, found } => {
+                write!(f, "expected a value of type {expected} but found {found}")
+            }
+            DiagnosticKind::UnknownType { name } => write!(f, "unknown type `{name}`"),
+            DiagnosticKind::Forbidden { name } => {
+                write!(f, "`{name}` is not available in this sandbox")
+            }
+        }
+    }
+}
+
+impl Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+// The name a form is known by, for reporting it.
+fn head<'arena>(expr: &Expression<'arena>) -> &'arena str {
+    let Atom::List { ref body } = expr.payload else {
+        return "expression";
+    };
+
+    match body.first().map(|head| head.payload) {
+        Some(Atom::Symbol { name }) => name,
+        Some(Atom::Define) => "define",
+        Some(Atom::ArrowRight) => "->",
+        Some(Atom::ArrowLeft) => "<-",
+        _ => "expression",
+    }
+}
+
+fn is_syntax(name: &str) -> bool {
+    matches!(
+        name,
+        "lambda"
+            | "let"
+            | "let*"
+            | "letrec"
+            | "letrec*"
+            | "let1"
+            | "receive"
+            | "quote"
+            | "quasiquote"
+            | "if"
+            | "begin"
+            | "set!"
+            | "cond"
+            | "else"
+            | "and"
+            | "or"
+            | "when"
+            | "unless"
+    )
+}
+
+fn builtin(atom: &Atom) -> Option<(&'static str, Arity)> {
+    match atom {
+        Atom::Cons => Some(("cons", Arity::Exact(2))),
+        Atom::Head => Some(("head", Arity::Exact(1))),
+        Atom::Tail => Some(("tail", Arity::Exact(1))),
+        Atom::Negate => Some(("!", Arity::Exact(1))),
+        Atom::Neq => Some(("!=", Arity::Exact(2))),
+        Atom::Exp => Some(("^", Arity::Exact(2))),
+        Atom::Mod => Some(("%", Arity::Exact(2))),
+        Atom::Remainder => Some(("//", Arity::Exact(2))),
+        _ => None,
+    }
+}
+
+fn formals_arity(params: &[Expression]) -> Arity {
+    match params
+        .iter()
+        .position(|p| p.payload == Atom::Symbol { name: "." })
+    {
+        Some(required) => Arity::AtLeast(required),
+        None => Arity::Exact(params.len()),
+    }
+}
+
+fn procedure_arity(expr: &Expression) -> Option<Arity> {
+    let Atom::List { ref body } = expr.payload else {
+        return None;
+    };
+    let body = &body[..body.len()];
+
+    match (body.first()?.payload, body.get(1)?.payload) {
+        (Atom::Symbol { name: "lambda" }, Atom::List { body: params }) => {
+            Some(formals_arity(&params[..params.len()]))
+        }
+        (Atom::Symbol { name: "lambda" }, Atom::Void) => Some(Arity::Exact(0)),
+        (Atom::Symbol { name: "lambda" }, Atom::Symbol { .. }) => Some(Arity::AtLeast(0)),
+        _ => None,
+    }
+}
+
+fn definition<'e, 'arena>(
+    expr: &'e Expression<'arena>,
+) -> Option<(&'arena str, Option<Arity>, &'e Expression<'arena>)> {
+    let Atom::List { ref body } = expr.payload else {
+        return None;
+    };
+    let body = &body[..body.len()];
+
+    if body.first()?.payload != Atom::Define {
+        return None;
+    }
+
+    let target = body.get(1)?;
+    match target.payload {
+        Atom::Symbol { name } => {
+            let value = unannotated(&body[2..]).first();
+            Some((name, value.and_then(procedure_arity), target))
+        }
+        Atom::List { body: signature } => {
+            let signature = &signature[..signature.len()];
+            match signature.first()?.payload {
+                Atom::Symbol { name } => Some((name, Some(formals_arity(&signature[1..])), target)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// Skips the `: Type` that may follow a defined name, a signature or the
+// formals of a lambda.
+fn unannotated<'e, 'arena>(rest: &'e [Expression<'arena>]) -> &'e [Expression<'arena>] {
+    match rest {
+        [colon, _, rest @ ..] if is_colon(colon) => rest,
+        _ => rest,
+    }
+}
+
+fn begin<'e, 'arena>(expr: &'e Expression<'arena>) -> Option<&'e [Expression<'arena>]> {
+    let Atom::List { ref body } = expr.payload else {
+        return None;
+    };
+    let body = &body[..body.len()];
+
+    match body.first()?.payload {
+        Atom::Symbol { name: "begin" } => Some(&body[1..]),
+        _ => None,
+    }
+}
+
+fn let_binding<'arena>(
+    binding: &Expression<'arena>,
+) -> Option<(&'arena str, Option<Expression<'arena>>)> {
+    let Atom::List { ref body } = binding.payload else {
+        return None;
+    };
+    let body = &body[..body.len()];
+
+    match (body.first()?.payload, body.len()) {
+        (Atom::Symbol { name }, 1) => Some((name, None)),
+        (Atom::Symbol { name }, 2) => Some((name, Some(body[1]))),
+        _ => None,
+    }
+}
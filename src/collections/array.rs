@@ -94,7 +94,7 @@ impl<T> Array<T> {
     {
         let len = self.len();
         if index < len {
-            let value = *&self[index];
+            let value = self[index];
             for i in index..len - 1 {
                 self[i] = self[i + 1];
             }
@@ -105,6 +105,18 @@ impl<T> Array<T> {
         }
     }
 
+    /// The elements from `start` on, sharing this array's buffer. The view
+    /// has no spare capacity, so pushing to it never overwrites the rest of
+    /// the original.
+    pub fn tail(&self, start: usize) -> Self {
+        let start = start.min(self.len);
+        Array {
+            buffer: unsafe { self.buffer.add(start) },
+            capacity: self.len - start,
+            len: self.len - start,
+        }
+    }
+
     pub fn clear(&mut self) {
         self.len = 0;
     }
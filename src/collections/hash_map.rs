@@ -1,5 +1,6 @@
 use super::array::Array;
 use crate::{Arena, make};
+use core::borrow::Borrow;
 use core::fmt::Debug;
 use core::hash::Hash;
 use core::ptr::NonNull;
@@ -66,18 +67,23 @@ impl<'a, K, V> HashMap<'a, K, V> {
         K: Copy,
         V: Copy,
     {
-        let buckets = make!(arena, Option<Bucket<K, V>>, capacity)
-            .map(|b| {
-                b.fill(None);
-                b
-            })
-            .expect("Failed to allocate buckets");
-
-        Self {
+        Self::try_new(arena, capacity).expect("Failed to allocate buckets")
+    }
+
+    /// Like `new`, but returns `None` if the arena is out of memory.
+    pub fn try_new(arena: &'a Arena, capacity: usize) -> Option<Self>
+    where
+        K: Copy,
+        V: Copy,
+    {
+        let buckets = make!(arena, Option<Bucket<K, V>>, capacity)?;
+        buckets.fill(None);
+
+        Some(Self {
             arena,
             size: 0,
             buckets: Array::new(buckets),
-        }
+        })
     }
 
     pub fn len(&self) -> usize {
@@ -142,16 +148,17 @@ impl<'a, K, V> HashMap<'a, K, V> {
         unreachable!() // Should never reach here
     }
 
-    pub fn find(&self, key: &K) -> Option<&V>
+    pub fn find<Q>(&self, key: &Q) -> Option<&V>
     where
-        K: Hash + PartialEq,
+        K: Borrow<Q>,
+        Q: Hash + PartialEq + ?Sized,
     {
         let hash = hash64(key) as usize;
         let index = hash % self.buckets.capacity();
         let mut current = self.buckets[index].as_ref();
 
         while let Some(bucket) = current {
-            if bucket.key == *key {
+            if bucket.key.borrow() == key {
                 return Some(&bucket.value);
             }
             current = unsafe { bucket.next.map(|n| n.as_ref()) };
@@ -167,7 +174,7 @@ impl<'a, K, V> HashMap<'a, K, V> {
     {
         let hash = hash64(key) as usize;
         let index = hash % self.buckets.capacity();
-        let mut current = &self.buckets[index].as_ref().map(|b| NonNull::from(b));
+        let mut current = &self.buckets[index].as_ref().map(NonNull::from);
         let mut prev: Option<NonNull<Bucket<K, V>>> = None;
 
         while let &Some(mut bucket) = current {
@@ -191,9 +198,10 @@ impl<'a, K, V> HashMap<'a, K, V> {
         None
     }
 
-    pub fn contains(&self, key: &K) -> bool
+    pub fn contains<Q>(&self, key: &Q) -> bool
     where
-        K: Hash + PartialEq,
+        K: Borrow<Q>,
+        Q: Hash + PartialEq + ?Sized,
     {
         self.find(key).is_some()
     }
@@ -16,13 +16,18 @@ pub struct Table<K, V> {
 
 impl<K, V> Table<K, V> {
     pub fn new(arena: &Arena, capacity: usize) -> Self {
-        let keys = make!(arena, Key<K>, capacity).expect("Failed to allocate memory for keys");
-        let values = make!(arena, V, capacity).expect("Failed to allocate memory for values");
+        Self::try_new(arena, capacity).expect("Failed to allocate memory for table")
+    }
+
+    /// Like `new`, but returns `None` if the arena is out of memory.
+    pub fn try_new(arena: &Arena, capacity: usize) -> Option<Self> {
+        let keys = make!(arena, Key<K>, capacity)?;
+        let values = make!(arena, V, capacity)?;
 
-        Table {
+        Some(Table {
             keys: Array::new(keys),
             values: Array::new(values),
-        }
+        })
     }
 
     pub fn len(&self) -> usize {
@@ -76,6 +81,14 @@ impl<K, V> Table<K, V> {
         self.index(key).and_then(|i| self.values.get(i))
     }
 
+    pub fn at(&self, index: usize) -> Option<(&K, &V)> {
+        if index < self.keys.len() {
+            Some((&self.keys[index].value, &self.values[index]))
+        } else {
+            None
+        }
+    }
+
     pub fn contains(&self, key: &K) -> bool
     where
         K: Hash + PartialEq<K>,
@@ -83,6 +96,16 @@ impl<K, V> Table<K, V> {
         self.index(key).is_some()
     }
 
+    pub fn remove(&mut self, key: &K) -> Option<V>
+    where
+        K: Copy + Hash + PartialEq<K>,
+        V: Copy,
+    {
+        let index = self.index(key)?;
+        self.keys.remove(index);
+        self.values.remove(index)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
         self.keys
             .iter()
@@ -0,0 +1,263 @@
+use crate::read::{self, Expression, ParseError, ParseErrorKind, Span, Token, tokenize};
+use crate::symbol::SymbolTable;
+use crate::{Arena, Array, make};
+
+/// Source text that means nothing to the evaluator but should survive a
+/// rewrite of the code around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    LineComment,
+    BlockComment,
+    /// A `#;` together with the form it removes.
+    DatumComment,
+}
+
+/// One piece of trivia. Whitespace is split after every line break, so a
+/// piece never spans more than one line ending.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trivia<'arena> {
+    pub kind: TriviaKind,
+    pub text: &'arena str,
+    pub span: Span,
+    trailing: bool,
+}
+
+/// A lossless parse of `source`: the expressions `read::parse` returns, and
+/// every piece of trivia between them in source order. Together they cover
+/// the source, so a tool can rewrite one form and copy the rest verbatim.
+#[derive(Debug, Clone, Copy)]
+pub struct Syntax<'arena> {
+    pub source: &'arena str,
+    pub root: Array<Expression<'arena>>,
+    trivia: Array<Trivia<'arena>>,
+}
+
+pub fn parse<'arena>(
+    arena: &'arena Arena<'arena>,
+    code: &'arena str,
+) -> Result<Syntax<'arena>, ParseError> {
+    let mut symbols = SymbolTable::with_capacity(arena, 16);
+    parse_with(arena, code, &mut symbols)
+}
+
+pub fn parse_with<'arena>(
+    arena: &'arena Arena<'arena>,
+    code: &'arena str,
+    symbols: &mut SymbolTable<'arena>,
+) -> Result<Syntax<'arena>, ParseError> {
+    let root = read::parse_with(arena, code, symbols)?;
+    let collected = Collector::new(code).collect()?;
+
+    let buffer = make!(arena, Trivia, collected.len()).ok_or(ParseError::new(
+        ParseErrorKind::OutOfMemory,
+        Span::default(),
+    ))?;
+    let mut trivia = Array::new(buffer);
+    for piece in &collected {
+        trivia.push(piece);
+    }
+
+    Ok(Syntax {
+        source: code,
+        root,
+        trivia,
+    })
+}
+
+impl<'arena> Syntax<'arena> {
+    pub fn trivia(&self) -> &[Trivia<'arena>] {
+        &self.trivia[..self.trivia.len()]
+    }
+
+    /// The trivia just before `expr` that belongs to it, such as the
+    /// comment lines above a definition.
+    pub fn leading(&self, expr: &Expression) -> &[Trivia<'arena>] {
+        let trivia = self.trivia();
+        let Ok(last) = trivia.binary_search_by_key(&expr.span.start, |t| t.span.end) else {
+            return &[];
+        };
+        if trivia[last].trailing {
+            return &[];
+        }
+
+        let mut first = last;
+        while first > 0
+            && touches(&trivia[first - 1], &trivia[first])
+            && !trivia[first - 1].trailing
+        {
+            first -= 1;
+        }
+        &trivia[first..=last]
+    }
+
+    /// The trivia after `expr` up to the end of its line, such as a comment
+    /// following it on the same line.
+    pub fn trailing(&self, expr: &Expression) -> &[Trivia<'arena>] {
+        let trivia = self.trivia();
+        let Ok(first) = trivia.binary_search_by_key(&expr.span.end, |t| t.span.start) else {
+            return &[];
+        };
+
+        let mut last = first;
+        while last < trivia.len() && trivia[last].trailing {
+            if last > first && !touches(&trivia[last - 1], &trivia[last]) {
+                break;
+            }
+            last += 1;
+        }
+        &trivia[first..last]
+    }
+
+    /// The source text `expr` was read from.
+    pub fn text(&self, expr: &Expression) -> &'arena str {
+        &self.source[expr.span.start as usize..expr.span.end as usize]
+    }
+}
+
+fn touches(before: &Trivia, after: &Trivia) -> bool {
+    before.span.end == after.span.start
+}
+
+// Walks the tokens of code that is already known to parse, picking out the
+// trivia and deciding which form each piece belongs to.
+struct Collector<'code> {
+    code: &'code str,
+    trivia: Vec<Trivia<'code>>,
+    // Where the trivia since the last significant token starts.
+    run: usize,
+    // Whether that token ended a form, as opposed to opening one.
+    after_form: bool,
+    offset: usize,
+    line: u32,
+    column: u32,
+}
+
+impl<'code> Collector<'code> {
+    fn new(code: &'code str) -> Self {
+        Collector {
+            code,
+            trivia: Vec::new(),
+            run: 0,
+            after_form: false,
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn collect(mut self) -> Result<Vec<Trivia<'code>>, ParseError> {
+        let mut end = 0;
+        let mut depth = 0usize;
+        // An open `#;`: where it starts, the depth of the forms it removes
+        // and how many of them are still to come.
+        let mut skip: Option<(usize, usize, usize)> = None;
+
+        for token in tokenize(self.code) {
+            let (token, span) = token?;
+            let (start, stop) = (span.start as usize, span.end as usize);
+
+            match token {
+                Token::LParen | Token::LBracket | Token::LBrace => depth += 1,
+                Token::RParen | Token::RBracket | Token::RBrace => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+
+            if let Some((from, base, count)) = skip.as_mut() {
+                match token {
+                    Token::DatumComment if depth == *base => *count += 1,
+                    Token::Quote | Token::Quasiquote | Token::Comment(_) | Token::DatumComment => {}
+                    _ if depth == *base => *count -= 1,
+                    _ => {}
+                }
+
+                if *count == 0 {
+                    let from = *from;
+                    self.push(TriviaKind::DatumComment, from, stop);
+                    skip = None;
+                }
+                end = stop;
+                continue;
+            }
+
+            self.whitespace(end, start);
+            end = stop;
+
+            match token {
+                Token::Comment(text) if text.starts_with("#|") => {
+                    self.push(TriviaKind::BlockComment, start, stop)
+                }
+                Token::Comment(_) => self.push(TriviaKind::LineComment, start, stop),
+                Token::DatumComment => skip = Some((start, depth, 1)),
+                _ => {
+                    let closing = matches!(token, Token::RParen | Token::RBracket | Token::RBrace);
+                    self.attach(!closing);
+                    self.after_form = !matches!(
+                        token,
+                        Token::Quote
+                            | Token::Quasiquote
+                            | Token::LParen
+                            | Token::LBracket
+                            | Token::LBrace
+                    );
+                }
+            }
+        }
+
+        self.whitespace(end, self.code.len());
+        self.attach(false);
+        Ok(self.trivia)
+    }
+
+    fn whitespace(&mut self, start: usize, stop: usize) {
+        let mut from = start;
+        for piece in self.code[start..stop].split_inclusive('\n') {
+            self.push(TriviaKind::Whitespace, from, from + piece.len());
+            from += piece.len();
+        }
+    }
+
+    fn push(&mut self, kind: TriviaKind, start: usize, stop: usize) {
+        for c in self.code[self.offset..start].chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.offset = start;
+
+        self.trivia.push(Trivia {
+            kind,
+            text: &self.code[start..stop],
+            span: Span {
+                start: start as u32,
+                end: stop as u32,
+                line: self.line,
+                column: self.column,
+            },
+            trailing: false,
+        });
+    }
+
+    // Splits the current run between the forms around it: up to the end of
+    // the line trails the form before, the rest leads the form after. With
+    // no form after, all of it trails the form before.
+    fn attach(&mut self, before_form: bool) {
+        let run = &mut self.trivia[self.run..];
+        if self.after_form {
+            let split = match before_form {
+                true => run
+                    .iter()
+                    .position(|t| t.text.ends_with('\n'))
+                    .map_or(run.len(), |i| i + 1),
+                false => run.len(),
+            };
+            for piece in &mut run[..split] {
+                piece.trailing = true;
+            }
+        }
+        self.run = self.trivia.len();
+    }
+}
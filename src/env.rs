@@ -1,43 +1,274 @@
 use crate::read::Atom;
+use crate::symbol::{Symbol, SymbolTable};
+use crate::{Arena, HashMap, Table, make};
+use core::cell::{RefCell, RefMut};
 
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::rc::Rc;
+const GLOBAL_BUCKETS: usize = 64;
+const SYMBOL_BUCKETS: usize = 64;
 
-#[derive(Debug, PartialEq, Default)]
+/// A handle to one frame of an environment. Frames live in the arena, so
+/// handles are cheap to copy and share their frame and parents. Every copy
+/// sees the same bindings, which are only changed through a `RefCell`.
+#[derive(Debug, Clone, Copy)]
 pub struct Env<'arena> {
-    parent: Option<Rc<RefCell<Env<'arena>>>>,
-    vars: HashMap<&'arena str, Atom<'arena>>,
+    frame: &'arena Frame<'arena>,
+}
+
+/// Where a variable lives, as resolved ahead of time: `depth` frames up the
+/// parent chain at slot `index`, or in the global frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Address<'arena> {
+    Local { depth: usize, index: usize },
+    Global(Symbol<'arena>),
+}
+
+#[derive(Debug)]
+struct Frame<'arena> {
+    arena: &'arena Arena<'arena>,
+    parent: Option<Env<'arena>>,
+    symbols: &'arena RefCell<SymbolTable<'arena>>,
+    vars: RefCell<Vars<'arena>>,
+}
+
+// The global frame grows without bound, so it is hashed. Local frames are
+// sized by their binding form and searched linearly, which beats hashing
+// for a handful of names. A local frame that fills up, say with internal
+// defines, moves to a larger table with its slots in the same order, so
+// resolved addresses stay valid.
+#[derive(Debug)]
+enum Vars<'arena> {
+    Global(HashMap<'arena, Symbol<'arena>, Atom<'arena>>),
+    Local(Table<Symbol<'arena>, Atom<'arena>>),
 }
 
 impl<'arena> Env<'arena> {
-    pub fn new() -> Self {
-        Default::default()
+    /// A root environment, or `None` if the arena is out of memory.
+    pub fn new(arena: &'arena Arena<'arena>) -> Option<Self> {
+        Self::with_capacity(arena, GLOBAL_BUCKETS)
     }
 
-    pub fn update(&mut self, data: Rc<RefCell<Self>>) {
-        self.vars
-            .extend(data.borrow().vars.iter().map(|(k, v)| (k, v)));
+    pub fn with_capacity(arena: &'arena Arena<'arena>, buckets: usize) -> Option<Self> {
+        Self::global(arena, buckets, symbol_table(arena)?)
     }
 
-    pub fn extend(parent: Rc<RefCell<Self>>) -> Self {
-        Env {
-            vars: HashMap::new(),
-            parent: Some(parent),
-        }
+    /// A root environment interning its names in `symbols`, which code
+    /// decoded before the environment existed was already read into.
+    pub(crate) fn with_symbols(
+        arena: &'arena Arena<'arena>,
+        symbols: &'arena RefCell<SymbolTable<'arena>>,
+    ) -> Option<Self> {
+        Self::global(arena, GLOBAL_BUCKETS, symbols)
+    }
+
+    fn global(
+        arena: &'arena Arena<'arena>,
+        buckets: usize,
+        symbols: &'arena RefCell<SymbolTable<'arena>>,
+    ) -> Option<Self> {
+        let vars = Vars::Global(HashMap::try_new(arena, buckets.max(1))?);
+        Self::frame(arena, None, symbols, vars)
+    }
+
+    /// A local frame below `parent` with room for `capacity` bindings
+    /// before it has to grow, or `None` if the arena is out of memory.
+    pub fn extend(parent: &Env<'arena>, capacity: usize) -> Option<Self> {
+        let arena = parent.frame.arena;
+        let vars = Vars::Local(Table::try_new(arena, capacity)?);
+        Self::frame(arena, Some(*parent), parent.frame.symbols, vars)
+    }
+
+    fn frame(
+        arena: &'arena Arena<'arena>,
+        parent: Option<Env<'arena>>,
+        symbols: &'arena RefCell<SymbolTable<'arena>>,
+        vars: Vars<'arena>,
+    ) -> Option<Self> {
+        let frame = make!(arena, Frame)?;
+        *frame = Frame {
+            arena,
+            parent,
+            symbols,
+            vars: RefCell::new(vars),
+        };
+
+        Some(Env { frame })
+    }
+
+    pub fn parent(&self) -> Option<Env<'arena>> {
+        self.frame.parent
+    }
+
+    /// The address of this handle's frame, which every copy shares.
+    pub fn id(&self) -> usize {
+        self.frame as *const Frame as usize
+    }
+
+    /// The symbol table shared by every frame of this environment. Code read
+    /// with it can be resolved without hashing names.
+    pub fn symbols(&self) -> RefMut<'arena, SymbolTable<'arena>> {
+        self.frame.symbols.borrow_mut()
     }
 
     pub fn get(&self, name: &str) -> Option<Atom<'arena>> {
-        match self.vars.get(name) {
-            Some(value) => Some(value.clone()),
-            None => self
-                .parent
-                .as_ref()
-                .and_then(|o| o.borrow().get(name).clone()),
+        let symbol = self.frame.symbols.borrow().get(name)?;
+        self.lookup(symbol)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Binds `name` in this frame, shadowing any outer binding. Returns
+    /// `None` if the arena is out of memory.
+    pub fn define(&mut self, name: &str, val: Atom<'arena>) -> Option<Atom<'arena>> {
+        let symbol = self.symbols().intern(name)?;
+        self.bind(symbol, val)
+    }
+
+    /// Updates the nearest visible binding of `name`, as `set!` does.
+    pub fn assign(&mut self, name: &str, val: Atom<'arena>) -> Result<(), &'static str> {
+        let symbol = self
+            .frame
+            .symbols
+            .borrow()
+            .get(name)
+            .ok_or("Unbound variable")?;
+        let depth = match self.resolve(symbol).ok_or("Unbound variable")? {
+            Address::Local { depth, .. } => depth,
+            Address::Global(_) => self.frames().count() - 1,
+        };
+
+        self.up(depth)
+            .and_then(|mut env| env.bind(symbol, val))
+            .map(|_| ())
+            .ok_or("Unbound variable")
+    }
+
+    /// Removes `name` from this frame only. Addresses resolved into this
+    /// frame beforehand may no longer be valid.
+    pub fn remove(&mut self, name: &str) -> Option<Atom<'arena>> {
+        let symbol = self.frame.symbols.borrow().get(name)?;
+        match &mut *self.frame.vars.borrow_mut() {
+            Vars::Global(vars) => vars.remove(&symbol),
+            Vars::Local(vars) => vars.remove(&symbol),
+        }
+    }
+
+    /// Every binding visible from this frame, innermost first, skipping
+    /// those shadowed by a closer frame.
+    pub fn bindings(&self) -> impl Iterator<Item = (Symbol<'arena>, Atom<'arena>)> + '_ {
+        self.frames().enumerate().flat_map(move |(depth, env)| {
+            env.vars()
+                .filter(move |(symbol, _)| self.depth_of(*symbol) == Some(depth))
+        })
+    }
+
+    pub fn lookup(&self, symbol: Symbol<'arena>) -> Option<Atom<'arena>> {
+        self.resolve(symbol)
+            .and_then(|address| self.get_at(address))
+    }
+
+    /// Binds `symbol` in this frame, returning `None` if the arena is out
+    /// of memory.
+    pub fn bind(&mut self, symbol: Symbol<'arena>, val: Atom<'arena>) -> Option<Atom<'arena>> {
+        match &mut *self.frame.vars.borrow_mut() {
+            Vars::Global(vars) => vars.insert(&symbol, &val).copied(),
+            Vars::Local(vars) => {
+                if vars.len() == vars.capacity() && !vars.contains(&symbol) {
+                    *vars = grow(self.frame.arena, vars)?;
+                }
+                vars.insert(&symbol, &val).copied()
+            }
+        }
+    }
+
+    pub fn resolve(&self, symbol: Symbol<'arena>) -> Option<Address<'arena>> {
+        let mut env = *self;
+        let mut depth = 0;
+
+        loop {
+            match &*env.frame.vars.borrow() {
+                Vars::Local(vars) => {
+                    if let Some(index) = vars.index(&symbol) {
+                        return Some(Address::Local { depth, index });
+                    }
+                }
+                Vars::Global(vars) => {
+                    return vars.contains(&symbol).then_some(Address::Global(symbol));
+                }
+            }
+
+            env = env.parent()?;
+            depth += 1;
+        }
+    }
+
+    pub fn get_at(&self, address: Address<'arena>) -> Option<Atom<'arena>> {
+        match address {
+            Address::Local { depth, index } => match &*self.up(depth)?.frame.vars.borrow() {
+                Vars::Local(vars) => vars.at(index).map(|(_, value)| *value),
+                Vars::Global(_) => None,
+            },
+            Address::Global(symbol) => match &*self.root().frame.vars.borrow() {
+                Vars::Global(vars) => vars.find(&symbol).copied(),
+                Vars::Local(_) => None,
+            },
+        }
+    }
+
+    fn depth_of(&self, symbol: Symbol<'arena>) -> Option<usize> {
+        match self.resolve(symbol)? {
+            Address::Local { depth, .. } => Some(depth),
+            Address::Global(_) => Some(self.frames().count() - 1),
         }
     }
 
-    pub fn set(&mut self, name: &'arena str, val: Atom<'arena>) {
-        self.vars.insert(name, val);
+    fn frames(&self) -> impl Iterator<Item = Env<'arena>> + use<'arena> {
+        core::iter::successors(Some(*self), Env::parent)
+    }
+
+    // Copied out so the frame is not left borrowed while the caller looks
+    // at other frames.
+    fn vars(self) -> impl Iterator<Item = (Symbol<'arena>, Atom<'arena>)> {
+        let vars: Vec<_> = match &*self.frame.vars.borrow() {
+            Vars::Global(vars) => vars.iter().map(|(s, v)| (*s, *v)).collect(),
+            Vars::Local(vars) => vars.iter().map(|(s, v)| (*s, *v)).collect(),
+        };
+        vars.into_iter()
+    }
+
+    fn up(&self, depth: usize) -> Option<Env<'arena>> {
+        self.frames().nth(depth)
+    }
+
+    fn root(&self) -> Env<'arena> {
+        self.frames().last().unwrap_or(*self)
+    }
+
+    pub(crate) fn flatten(&self) -> std::collections::HashMap<&'arena str, Atom<'arena>> {
+        self.bindings()
+            .map(|(symbol, value)| (symbol.name(), value))
+            .collect()
+    }
+}
+
+/// A symbol table for an environment yet to be made, or `None` if the
+/// arena is out of memory.
+pub(crate) fn symbol_table<'arena>(
+    arena: &'arena Arena<'arena>,
+) -> Option<&'arena RefCell<SymbolTable<'arena>>> {
+    let symbols = make!(arena, RefCell<SymbolTable>)?;
+    *symbols = RefCell::new(SymbolTable::try_with_capacity(arena, SYMBOL_BUCKETS)?);
+    Some(symbols)
+}
+
+fn grow<'arena>(
+    arena: &'arena Arena<'arena>,
+    vars: &Table<Symbol<'arena>, Atom<'arena>>,
+) -> Option<Table<Symbol<'arena>, Atom<'arena>>> {
+    let mut grown = Table::try_new(arena, (vars.capacity() * 2).max(4))?;
+    for (symbol, value) in vars.iter() {
+        grown.insert(symbol, value)?;
     }
+    Some(grown)
 }
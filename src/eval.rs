@@ -1,65 +1,1065 @@
-use crate::{make, read::{Atom, Expression}, env::Env, Arena};
-use core::ptr::NonNull;
+use crate::check::Arity;
+use crate::env::Env;
+use crate::expand::expand;
+use crate::lazy::{self, Promise, State};
+use crate::map::Map;
+use crate::pair;
+use crate::primitive;
+use crate::read::{Atom, Expression, Span, parse_file_with, parse_with};
+use crate::sandbox;
+use crate::{Arena, Array, make};
+use core::cell::RefCell;
+use core::fmt::{Debug, Formatter};
 
 type EvalResult<'arena> = Result<Atom<'arena>, &'static str>;
 
-fn eval_cons<'arena>(arena: &Arena, env: &Env, exprs: &[Expression]) -> EvalResult<'arena> {
-    if exprs.len() != 3 {
-	return Err("Invalid number of arguments for define");
+// Frames are only pushed for work left over once a subexpression returns,
+// so this bounds non-tail recursion rather than loops.
+const MAX_FRAMES: usize = 100_000;
+
+/// A procedure made by `lambda`: its formals, the forms of its body and
+/// the environment it closes over.
+pub struct Closure<'arena> {
+    params: &'arena [&'arena str],
+    rest: Option<&'arena str>,
+    body: Array<Expression<'arena>>,
+    env: Env<'arena>,
+}
+
+/// A procedure call that can suspend itself with `yield` and be continued
+/// with `resume`, made by `make-coroutine`. A suspended coroutine keeps the
+/// part of the stack it had built up.
+pub struct Coroutine<'arena> {
+    state: RefCell<Run<'arena>>,
+}
+
+#[derive(Debug)]
+enum Run<'arena> {
+    Fresh {
+        procedure: Atom<'arena>,
+    },
+    // Frames and values above the coroutine's `Resume` frame, with value
+    // positions relative to where its values started.
+    Suspended {
+        frames: Vec<Frame<'arena>>,
+        values: Vec<Atom<'arena>>,
+    },
+    Running,
+    Done,
+}
+
+/// Generator procedures written in tyson: `make-generator`,
+/// `generator-for-each` and `generator->list`. They are evaluated like any
+/// other code, and the prelude's `for-each` hands generators to them.
+pub const GENERATORS: &str = include_str!("generators.tyson");
+
+/// Evaluates expanded code. Evaluation runs on an explicit stack of frames
+/// rather than the Rust stack, so deep recursion in the evaluated code is
+/// reported as an error and calls in tail position do not grow the stack.
+/// Each call still allocates a frame of the environment in the arena,
+/// which is only reclaimed along with the arena.
+pub struct Interpreter<'arena> {
+    arena: &'arena Arena<'arena>,
+    env: Env<'arena>,
+    frames: Vec<Frame<'arena>>,
+    values: Vec<Atom<'arena>>,
+}
+
+// What the machine does next: evaluate an expression, call the procedure
+// at `values[base]` with the values above it, or hand a value to the
+// frame on top of the stack.
+#[derive(Debug, Clone)]
+enum Control<'arena> {
+    Eval(Atom<'arena>, Env<'arena>),
+    Apply(usize),
+    Return(Atom<'arena>),
+}
+
+// The work left once the expression being evaluated returns. `rest` is
+// always a view of the forms not evaluated yet.
+#[derive(Debug, Clone)]
+enum Frame<'arena> {
+    Sequence {
+        rest: Array<Expression<'arena>>,
+        env: Env<'arena>,
+    },
+    Branch {
+        then: Atom<'arena>,
+        otherwise: Atom<'arena>,
+        env: Env<'arena>,
+    },
+    And {
+        rest: Array<Expression<'arena>>,
+        env: Env<'arena>,
+    },
+    Or {
+        rest: Array<Expression<'arena>>,
+        env: Env<'arena>,
+    },
+    Define {
+        name: &'arena str,
+        env: Env<'arena>,
+    },
+    Assign {
+        name: &'arena str,
+        env: Env<'arena>,
+    },
+    // Evaluates `rest` one by one onto `values`, starting at `base`.
+    Collect {
+        rest: Array<Expression<'arena>>,
+        base: usize,
+        env: Env<'arena>,
+        then: Then<'arena>,
+    },
+    // Binds `name` in `env`, then evaluates the `rest` of the bindings of
+    // a `let*`, `letrec` or `let1` in it and finally the body.
+    Bind {
+        name: &'arena str,
+        rest: Array<Expression<'arena>>,
+        body: Array<Expression<'arena>>,
+        env: Env<'arena>,
+    },
+    // Stores the value of a promise's thunk.
+    Force {
+        promise: &'arena Promise<'arena>,
+    },
+    // Marks where a coroutine's stack starts: `yield` suspends the frames
+    // above it and the values from `values` up.
+    Resume {
+        coroutine: &'arena Coroutine<'arena>,
+        values: usize,
+    },
+}
+
+// What to build from the values a `Collect` frame gathered.
+#[derive(Debug, Clone, Copy)]
+enum Then<'arena> {
+    Apply,
+    Vector,
+    // The values of a map literal, to pair back up with its keys.
+    Map {
+        map: &'arena Map<'arena>,
+    },
+    Let {
+        bindings: Array<Expression<'arena>>,
+        body: Array<Expression<'arena>>,
+        name: Option<&'arena str>,
+    },
+}
+
+impl<'arena> Frame<'arena> {
+    // The frame with the value positions it holds moved by `offset`.
+    fn rebase(self, offset: impl Fn(usize) -> usize) -> Self {
+        match self {
+            Frame::Collect {
+                rest,
+                base,
+                env,
+                then,
+            } => Frame::Collect {
+                rest,
+                base: offset(base),
+                env,
+                then,
+            },
+            Frame::Resume { coroutine, values } => Frame::Resume {
+                coroutine,
+                values: offset(values),
+            },
+            frame => frame,
+        }
+    }
+}
+
+impl<'arena> Closure<'arena> {
+    pub fn params(&self) -> &'arena [&'arena str] {
+        self.params
+    }
+
+    /// The name bound to the arguments left over after `params`, if any.
+    pub fn rest(&self) -> Option<&'arena str> {
+        self.rest
+    }
+
+    pub fn body(&self) -> &[Expression<'arena>] {
+        &self.body[..self.body.len()]
+    }
+
+    pub fn env(&self) -> Env<'arena> {
+        self.env
+    }
+
+    pub fn arity(&self) -> Arity {
+        match self.rest {
+            Some(_) => Arity::AtLeast(self.params.len()),
+            None => Arity::Exact(self.params.len()),
+        }
+    }
+}
+
+// The environment refers back to the closure, so it is left out.
+impl Debug for Closure<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Closure")
+            .field("params", &self.params)
+            .field("rest", &self.rest)
+            .field("body", &self.body)
+            .finish_non_exhaustive()
+    }
+}
+
+// Procedures are the same only if they are the same object, as for `eq?`.
+impl PartialEq for Closure<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self, other)
+    }
+}
+
+pub(crate) fn closure<'arena>(
+    arena: &'arena Arena<'arena>,
+    params: &[&'arena str],
+    rest: Option<&'arena str>,
+    body: Array<Expression<'arena>>,
+    env: Env<'arena>,
+) -> EvalResult<'arena> {
+    let copy = make!(arena, &str, params.len()).ok_or("Failed to allocate closure")?;
+    copy.copy_from_slice(params);
+
+    let closure = make!(arena, Closure).ok_or("Failed to allocate closure")?;
+    *closure = Closure {
+        params: copy,
+        rest,
+        body,
+        env,
+    };
+    Ok(Atom::Closure { closure })
+}
+
+impl Coroutine<'_> {
+    /// Whether the coroutine has returned, or failed, and cannot be resumed.
+    pub fn is_done(&self) -> bool {
+        matches!(*self.state.borrow(), Run::Done)
     }
+}
 
-    let head = eval_expression(arena, env, &exprs[1])?;
-    let tail = eval_expression(arena, env, &exprs[2])?;
+impl Debug for Coroutine<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Coroutine")
+            .field("done", &self.is_done())
+            .finish_non_exhaustive()
+    }
+}
 
-    Ok(tail)
+impl PartialEq for Coroutine<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self, other)
+    }
 }
 
-fn eval_define<'arena>(arena: &Arena, env: &Env, exprs: &[Expression]) -> EvalResult<'arena> {
-    if exprs.len() != 3 {
-	return Err("Invalid number of arguments for define");
+/// A coroutine that calls `procedure` without arguments when it is first
+/// resumed.
+pub(crate) fn coroutine<'arena>(
+    arena: &'arena Arena<'arena>,
+    procedure: Atom<'arena>,
+) -> EvalResult<'arena> {
+    let coroutine = make!(arena, Coroutine).ok_or("Failed to allocate coroutine")?;
+    // The zeroed memory is not a valid state, so it must not be dropped.
+    unsafe {
+        core::ptr::write(
+            coroutine,
+            Coroutine {
+                state: RefCell::new(Run::Fresh { procedure }),
+            },
+        );
     }
+    Ok(Atom::Coroutine { coroutine })
+}
 
-    let args = &exprs[1..];
-    let sym = &args[0].payload;
-    let value_expr = &args[1];
+/// Whether `atom` can be called: a closure, a primitive or one of the
+/// operators the reader turns into atoms.
+pub fn is_procedure(atom: &Atom) -> bool {
+    matches!(atom, Atom::Closure { .. }) || primitive::name(atom).is_some()
+}
+
+fn truthy(atom: &Atom) -> bool {
+    !matches!(atom, Atom::False | Atom::Nil)
+}
 
-    if let Atom::Symbol{ name } = sym {
-	
-    } else {
-	return Err("Malformed function defintion");
+// What a quoted form stands for once it is data.
+fn datum(atom: Atom) -> Atom {
+    match atom {
+        Atom::Quoted { name } => Atom::Symbol { name },
+        Atom::Code { body } => Atom::List { body },
+        atom => atom,
     }
+}
 
-    let value = eval_expression(arena, env, value_expr);
+fn symbol<'arena>(
+    expr: &Expression<'arena>,
+    form: &'static str,
+) -> Result<&'arena str, &'static str> {
+    match expr.payload {
+        Atom::Symbol { name } => Ok(name),
+        _ => Err(form),
+    }
+}
+
+// (name init), where a missing init binds nil
+fn binding<'arena>(expr: &Expression<'arena>) -> Result<(&'arena str, Atom<'arena>), &'static str> {
+    let Atom::List { ref body } = expr.payload else {
+        return Err("Malformed let binding");
+    };
 
-    value
+    match &body[..body.len()] {
+        [name] => Ok((symbol(name, "Malformed let binding")?, Atom::Nil)),
+        [name, init] => Ok((symbol(name, "Malformed let binding")?, init.payload)),
+        _ => Err("Malformed let binding"),
+    }
 }
 
-fn eval_expression<'arena>(arena: &Arena, env: &Env, expr: &Expression) -> EvalResult<'arena> {
-    let mut current_expr = make!(arena, NonNull<Expression>)
-	.map(|e| {
-	    *e = NonNull::from_ref(expr);
-	    e
-	});
+// The bindings of a let form, which may be written `()`.
+fn bindings<'arena>(
+    expr: &Expression<'arena>,
+    empty: Array<Expression<'arena>>,
+) -> Result<Array<Expression<'arena>>, &'static str> {
+    match expr.payload {
+        Atom::List { body } => Ok(body),
+        Atom::Void => Ok(empty),
+        _ => Err("Malformed let bindings"),
+    }
+}
+
+// Splits formals into the required names and the rest name after `.`.
+fn formals<'arena>(
+    params: &[Expression<'arena>],
+) -> Result<(Vec<&'arena str>, Option<&'arena str>), &'static str> {
+    let mut names = Vec::with_capacity(params.len());
+    let mut rest = None;
+
+    let mut iter = params.iter();
+    while let Some(param) = iter.next() {
+        match symbol(param, "Malformed parameter list")? {
+            "." => {
+                let name = iter.next().ok_or("Malformed parameter list")?;
+                rest = Some(symbol(name, "Malformed parameter list")?);
+                if iter.next().is_some() {
+                    return Err("Malformed parameter list");
+                }
+            }
+            name => names.push(name),
+        }
+    }
+
+    Ok((names, rest))
+}
+
+// A new, empty frame below `env` with room for `capacity` bindings.
+fn extend<'arena>(env: &Env<'arena>, capacity: usize) -> Result<Env<'arena>, &'static str> {
+    Env::extend(env, capacity).ok_or("Failed to allocate frame")
+}
+
+// Binds `name` in `env` itself, shadowing any outer binding.
+fn define_name<'arena>(
+    env: &mut Env<'arena>,
+    name: &str,
+    value: Atom<'arena>,
+) -> Result<(), &'static str> {
+    env.define(name, value)
+        .map(|_| ())
+        .ok_or("Failed to allocate frame")
+}
+
+impl<'arena> Interpreter<'arena> {
+    pub fn new(arena: &'arena Arena<'arena>, env: Env<'arena>) -> Self {
+        Interpreter {
+            arena,
+            env,
+            frames: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// The environment top-level forms are evaluated in.
+    pub fn env(&self) -> Env<'arena> {
+        self.env
+    }
+
+    pub fn eval(&mut self, expr: &Expression<'arena>) -> EvalResult<'arena> {
+        self.execute(Control::Eval(expr.payload, self.env))
+    }
+
+    /// Evaluates `exprs` in order, returning the value of the last one, or
+    /// nil if there are none.
+    pub fn eval_all(&mut self, exprs: &[Expression<'arena>]) -> EvalResult<'arena> {
+        let mut value = Atom::Nil;
+        for expr in exprs {
+            value = self.eval(expr)?;
+        }
+        Ok(value)
+    }
+
+    pub fn apply(&mut self, procedure: Atom<'arena>, args: &[Atom<'arena>]) -> EvalResult<'arena> {
+        let base = self.values.len();
+        self.values.push(procedure);
+        self.values.extend_from_slice(args);
+
+        let value = self.execute(Control::Apply(base));
+        self.values.truncate(base);
+        value
+    }
+
+    /// Reads, expands and evaluates `code`, interning its symbols in the
+    /// environment's table.
+    pub fn run(&mut self, code: &'arena str) -> EvalResult<'arena> {
+        let arena = self.arena;
+        let code =
+            parse_with(arena, code, &mut self.env.symbols()).map_err(|_| "Unable to read code")?;
+        let code = self.expand(code)?;
+        self.eval_all(&code[..code.len()])
+    }
+
+    // Runs the machine until the frames pushed from here on are used up.
+    // An error unwinds them, so the interpreter can be used again.
+    fn execute(&mut self, control: Control<'arena>) -> EvalResult<'arena> {
+        let (frames, values) = (self.frames.len(), self.values.len());
+        let value = self.drive(frames, control);
+        if value.is_err() {
+            // A coroutine that failed cannot be resumed where it failed.
+            for frame in &self.frames[frames..] {
+                if let Frame::Resume { coroutine, .. } = frame {
+                    *coroutine.state.borrow_mut() = Run::Done;
+                }
+            }
+            self.frames.truncate(frames);
+            self.values.truncate(values);
+        }
+        value
+    }
+
+    fn drive(&mut self, base: usize, mut control: Control<'arena>) -> EvalResult<'arena> {
+        loop {
+            control = match control {
+                Control::Eval(atom, env) => self.step(atom, env)?,
+                Control::Apply(base) => self.call(base)?,
+                Control::Return(value) if self.frames.len() == base => return Ok(value),
+                Control::Return(value) => match self.frames.pop() {
+                    Some(frame) => self.resume(frame, value)?,
+                    None => return Ok(value),
+                },
+            };
+        }
+    }
+
+    fn push(&mut self, frame: Frame<'arena>) -> Result<(), &'static str> {
+        if self.frames.len() == MAX_FRAMES {
+            return Err("Stack overflow");
+        }
+
+        self.frames.push(frame);
+        Ok(())
+    }
+
+    fn step(
+        &mut self,
+        atom: Atom<'arena>,
+        env: Env<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        let value = match atom {
+            Atom::Symbol { name } => match env.get(name) {
+                Some(value) => value,
+                None if sandbox::capability(name).is_some() => return Err("Forbidden primitive"),
+                None => return Err("Unbound variable"),
+            },
+            Atom::Quoted { .. } | Atom::Code { .. } => datum(atom),
+            Atom::Vector { body } if !body.is_empty() => {
+                return self.collect(body, env, Then::Vector);
+            }
+            // Keys are data, so only the values are evaluated.
+            Atom::Map { map } if !map.is_empty() => {
+                let arena = self.arena;
+                let mut values = make!(arena, Expression, map.len())
+                    .map(Array::new)
+                    .ok_or("Failed to allocate map")?;
+                for value in map.values() {
+                    values.push(value);
+                }
+                return self.collect(values, env, Then::Map { map });
+            }
+            Atom::File { path, lazy: true } => {
+                self.allow("lazyload")?;
+                lazy::delay(self.arena, Atom::File { path, lazy: false })
+                    .ok_or("Failed to allocate promise")?
+            }
+            Atom::File { path, lazy: false } => {
+                self.allow("load")?;
+                return self.load(path);
+            }
+            Atom::Define => return Err("Misplaced define"),
+            Atom::List { body } => return self.form(body, env),
+            atom => atom,
+        };
+
+        Ok(Control::Return(value))
+    }
+
+    fn form(
+        &mut self,
+        body: Array<Expression<'arena>>,
+        env: Env<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        let forms = &body[..body.len()];
+
+        let keyword = match forms[0].payload {
+            Atom::Define => "define",
+            Atom::Symbol { name } => name,
+            _ => "",
+        };
+
+        match (keyword, forms) {
+            ("quote" | "quasiquote", [_, quoted]) => Ok(Control::Return(datum(quoted.payload))),
+            ("quote" | "quasiquote", _) => Err("Malformed quote"),
+            ("define", [_, target, rest @ ..]) => self.define(target, rest, body.tail(2), env),
+            ("define", _) => Err("Malformed define"),
+            ("lambda", [_, params, _, ..]) => {
+                let (names, rest) = match params.payload {
+                    Atom::Void => (Vec::new(), None),
+                    Atom::Symbol { name } => (Vec::new(), Some(name)),
+                    Atom::List { ref body } => formals(&body[..body.len()])?,
+                    _ => return Err("Malformed lambda"),
+                };
+                closure(self.arena, &names, rest, body.tail(2), env).map(Control::Return)
+            }
+            ("lambda", _) => Err("Malformed lambda"),
+            ("if", [_, test, then]) => self.branch(test, then.payload, Atom::Nil, env),
+            ("if", [_, test, then, otherwise]) => {
+                self.branch(test, then.payload, otherwise.payload, env)
+            }
+            ("if", _) => Err("Malformed if"),
+            ("begin", _) => self.body(body.tail(1), env),
+            ("set!", [_, name, value]) => {
+                let name = symbol(name, "Malformed set!")?;
+                self.push(Frame::Assign { name, env })?;
+                Ok(Control::Eval(value.payload, env))
+            }
+            ("set!", _) => Err("Malformed set!"),
+            ("and", [_]) => Ok(Control::Return(Atom::True)),
+            ("or", [_]) => Ok(Control::Return(Atom::False)),
+            ("and", _) => self.and(body.tail(1), env),
+            ("or", _) => self.or(body.tail(1), env),
+            ("let", [_, name, bindings, _, ..]) if matches!(name.payload, Atom::Symbol { .. }) => {
+                let name = symbol(name, "Malformed let")?;
+                let bindings = self::bindings(bindings, body.tail(body.len()))?;
+                let then = Then::Let {
+                    bindings,
+                    body: body.tail(3),
+                    name: Some(name),
+                };
+                self.collect(bindings, env, then)
+            }
+            ("let", [_, bindings, _, ..]) => {
+                let bindings = self::bindings(bindings, body.tail(body.len()))?;
+                let then = Then::Let {
+                    bindings,
+                    body: body.tail(2),
+                    name: None,
+                };
+                self.collect(bindings, env, then)
+            }
+            ("let*" | "letrec" | "letrec*", [_, bindings, _, ..]) => {
+                let bindings = self::bindings(bindings, body.tail(body.len()))?;
+                let mut frame = extend(&env, bindings.len())?;
+                if keyword.starts_with("letrec") {
+                    for expr in bindings.iter() {
+                        let (name, _) = binding(expr)?;
+                        define_name(&mut frame, name, Atom::Nil)?;
+                    }
+                }
+                self.bind(bindings, body.tail(2), frame)
+            }
+            ("let" | "let*" | "letrec" | "letrec*", _) => Err("Malformed let"),
+            ("let1", [_, name, init, _, ..]) => {
+                let name = symbol(name, "Malformed let1")?;
+                self.push(Frame::Bind {
+                    name,
+                    rest: body.tail(body.len()),
+                    body: body.tail(3),
+                    env: extend(&env, 1)?,
+                })?;
+                Ok(Control::Eval(init.payload, env))
+            }
+            ("let1", _) => Err("Malformed let1"),
+            _ => self.collect(body, env, Then::Apply),
+        }
+    }
+
+    // (define name value), or (define (name params...) body...) for
+    // (define name (lambda (params...) body...))
+    fn define(
+        &mut self,
+        target: &Expression<'arena>,
+        rest: &[Expression<'arena>],
+        body: Array<Expression<'arena>>,
+        env: Env<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        match (target.payload, rest) {
+            (Atom::Symbol { name }, []) => self.bind_value(env, name, Atom::Nil),
+            (Atom::Symbol { name }, [value]) => {
+                self.push(Frame::Define { name, env })?;
+                Ok(Control::Eval(value.payload, env))
+            }
+            (Atom::List { body: signature }, [_, ..]) => {
+                let name = symbol(&signature[0], "Malformed define")?;
+                let (names, rest) = formals(&signature[1..signature.len()])?;
+                let value = closure(self.arena, &names, rest, body, env)?;
+                self.bind_value(env, name, value)
+            }
+            _ => Err("Malformed define"),
+        }
+    }
+
+    fn bind_value(
+        &mut self,
+        mut env: Env<'arena>,
+        name: &'arena str,
+        value: Atom<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        define_name(&mut env, name, value)?;
+        Ok(Control::Return(Atom::Nil))
+    }
+
+    fn branch(
+        &mut self,
+        test: &Expression<'arena>,
+        then: Atom<'arena>,
+        otherwise: Atom<'arena>,
+        env: Env<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        self.push(Frame::Branch {
+            then,
+            otherwise,
+            env,
+        })?;
+        Ok(Control::Eval(test.payload, env))
+    }
+
+    // The forms of a body in order, the last one in tail position.
+    fn body(
+        &mut self,
+        forms: Array<Expression<'arena>>,
+        env: Env<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        match forms.len() {
+            0 => Ok(Control::Return(Atom::Nil)),
+            1 => Ok(Control::Eval(forms[0].payload, env)),
+            _ => {
+                self.push(Frame::Sequence {
+                    rest: forms.tail(1),
+                    env,
+                })?;
+                Ok(Control::Eval(forms[0].payload, env))
+            }
+        }
+    }
+
+    fn and(
+        &mut self,
+        forms: Array<Expression<'arena>>,
+        env: Env<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        if forms.len() > 1 {
+            self.push(Frame::And {
+                rest: forms.tail(1),
+                env,
+            })?;
+        }
+        Ok(Control::Eval(forms[0].payload, env))
+    }
+
+    fn or(
+        &mut self,
+        forms: Array<Expression<'arena>>,
+        env: Env<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        if forms.len() > 1 {
+            self.push(Frame::Or {
+                rest: forms.tail(1),
+                env,
+            })?;
+        }
+        Ok(Control::Eval(forms[0].payload, env))
+    }
+
+    // Evaluates the bindings of a let* or letrec one at a time in `frame`,
+    // then the body.
+    fn bind(
+        &mut self,
+        bindings: Array<Expression<'arena>>,
+        body: Array<Expression<'arena>>,
+        frame: Env<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        if bindings.is_empty() {
+            return self.body(body, frame);
+        }
+
+        let (name, init) = binding(&bindings[0])?;
+        self.push(Frame::Bind {
+            name,
+            rest: bindings.tail(1),
+            body,
+            env: frame,
+        })?;
+        Ok(Control::Eval(init, frame))
+    }
+
+    fn collect(
+        &mut self,
+        forms: Array<Expression<'arena>>,
+        env: Env<'arena>,
+        then: Then<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        let base = self.values.len();
+        if forms.is_empty() {
+            return self.finish(base, env, then);
+        }
+
+        self.push(Frame::Collect {
+            rest: forms.tail(1),
+            base,
+            env,
+            then,
+        })?;
+        Ok(Control::Eval(operand(&forms[0], then)?, env))
+    }
+
+    fn finish(
+        &mut self,
+        base: usize,
+        env: Env<'arena>,
+        then: Then<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        let arena = self.arena;
+        match then {
+            Then::Apply => Ok(Control::Apply(base)),
+            Then::Vector => {
+                let mut body = make!(arena, Expression, self.values.len() - base)
+                    .map(Array::new)
+                    .ok_or("Failed to allocate vector")?;
+                for payload in self.values.drain(base..) {
+                    body.push(&Expression {
+                        depth: 1,
+                        payload,
+                        span: Span::default(),
+                    });
+                }
+                Ok(Control::Return(Atom::Vector { body }))
+            }
+            Then::Map { map } => {
+                let values: Vec<_> = map
+                    .values()
+                    .zip(self.values.drain(base..))
+                    .map(|(expr, payload)| Expression { payload, ..*expr })
+                    .collect();
+                Ok(Control::Return(Atom::Map {
+                    map: map.with_values(arena, &values)?,
+                }))
+            }
+            Then::Let {
+                bindings,
+                body,
+                name: None,
+            } => {
+                let mut frame = extend(&env, bindings.len())?;
+                for (expr, value) in bindings.iter().zip(self.values.drain(base..)) {
+                    let (name, _) = binding(expr)?;
+                    define_name(&mut frame, name, value)?;
+                }
+                self.body(body, frame)
+            }
+            // A named let binds its name, in a frame of its own, to a
+            // procedure taking the bindings, and calls it.
+            Then::Let {
+                bindings,
+                body,
+                name: Some(name),
+            } => {
+                let mut frame = extend(&env, 1)?;
+                let mut names = Vec::with_capacity(bindings.len());
+                for expr in bindings.iter() {
+                    names.push(binding(expr)?.0);
+                }
+                let procedure = closure(arena, &names, None, body, frame)?;
+                define_name(&mut frame, name, procedure)?;
+                self.values.insert(base, procedure);
+                Ok(Control::Apply(base))
+            }
+        }
+    }
+
+    fn resume(
+        &mut self,
+        frame: Frame<'arena>,
+        value: Atom<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        match frame {
+            Frame::Sequence { rest, env } => self.body(rest, env),
+            Frame::Branch {
+                then,
+                otherwise,
+                env,
+            } => match truthy(&value) {
+                true => Ok(Control::Eval(then, env)),
+                false => Ok(Control::Eval(otherwise, env)),
+            },
+            Frame::And { .. } if !truthy(&value) => Ok(Control::Return(value)),
+            Frame::And { rest, env } => self.and(rest, env),
+            Frame::Or { .. } if truthy(&value) => Ok(Control::Return(value)),
+            Frame::Or { rest, env } => self.or(rest, env),
+            Frame::Define { name, env } => self.bind_value(env, name, value),
+            Frame::Assign { name, mut env } => {
+                env.assign(name, value)?;
+                Ok(Control::Return(Atom::Nil))
+            }
+            Frame::Collect {
+                rest,
+                base,
+                env,
+                then,
+            } => {
+                self.values.push(value);
+                if rest.is_empty() {
+                    return self.finish(base, env, then);
+                }
+
+                self.push(Frame::Collect {
+                    rest: rest.tail(1),
+                    base,
+                    env,
+                    then,
+                })?;
+                Ok(Control::Eval(operand(&rest[0], then)?, env))
+            }
+            Frame::Bind {
+                name,
+                rest,
+                body,
+                mut env,
+            } => {
+                define_name(&mut env, name, value)?;
+                self.bind(rest, body, env)
+            }
+            // If forcing the promise forced it again, the first value stored
+            // wins.
+            Frame::Force { promise } => match promise.state() {
+                State::Forced { value } => Ok(Control::Return(value)),
+                State::Pending { .. } => {
+                    promise.set_state(State::Forced { value });
+                    Ok(Control::Return(value))
+                }
+            },
+            // Either the coroutine yielded `value`, or it returned it.
+            Frame::Resume { coroutine, .. } => {
+                let mut state = coroutine.state.borrow_mut();
+                if let Run::Running = *state {
+                    *state = Run::Done;
+                }
+                Ok(Control::Return(value))
+            }
+        }
+    }
+
+    // Calls the procedure at `values[base]` with the values above it.
+    fn call(&mut self, base: usize) -> Result<Control<'arena>, &'static str> {
+        let procedure = self.values[base];
+        let args = base + 1..self.values.len();
+
+        if let Atom::Closure { closure } = procedure {
+            if !closure.arity().accepts(args.len()) {
+                return Err("Wrong number of arguments");
+            }
+
+            let capacity = closure.params.len() + usize::from(closure.rest.is_some());
+            let mut frame = extend(&closure.env, capacity)?;
+            let (required, extra) = self.values[args].split_at(closure.params.len());
+            for (name, value) in closure.params.iter().zip(required) {
+                define_name(&mut frame, name, *value)?;
+            }
+            if let Some(name) = closure.rest {
+                let list = pair::list(self.arena, extra)?;
+                define_name(&mut frame, name, list)?;
+            }
+
+            self.values.truncate(base);
+            return self.body(closure.body, frame);
+        }
+
+        let name = primitive::name(&procedure).ok_or("Not a procedure")?;
+        if let ("force" | "load" | "lazyload", &[value]) = (name, &self.values[args.clone()]) {
+            self.values.truncate(base);
+            return match (name, value) {
+                ("force", value) => self.force(value),
+                ("load", Atom::String { inner }) => self.load(inner),
+                ("lazyload", Atom::String { inner }) => lazy::delay(
+                    self.arena,
+                    Atom::File {
+                        path: inner,
+                        lazy: false,
+                    },
+                )
+                .map(Control::Return)
+                .ok_or("Failed to allocate promise"),
+                _ => Err("Expected a path"),
+            };
+        }
+
+        // Switching coroutines moves frames on and off the stack, so it
+        // happens here rather than in `primitive`.
+        let switch = match (name, &self.values[args.clone()]) {
+            ("yield", &[value]) => Some((None, value)),
+            ("resume", &[Atom::Coroutine { coroutine }]) => Some((Some(coroutine), Atom::Nil)),
+            ("resume", &[Atom::Coroutine { coroutine }, value]) => Some((Some(coroutine), value)),
+            _ => None,
+        };
+        if let Some((coroutine, value)) = switch {
+            self.values.truncate(base);
+            return match coroutine {
+                Some(coroutine) => self.enter(coroutine, value),
+                None => self.suspend(value),
+            };
+        }
+
+        let value = primitive::call(self.arena, &self.env, name, &self.values[args])?;
+        self.values.truncate(base);
+        Ok(Control::Return(value))
+    }
+
+    // A pending promise runs its thunk if that is a procedure, and otherwise
+    // evaluates it at the top level.
+    fn force(&mut self, value: Atom<'arena>) -> Result<Control<'arena>, &'static str> {
+        let Atom::Promise { promise } = value else {
+            return Ok(Control::Return(value));
+        };
+
+        let thunk = match promise.state() {
+            State::Forced { value } => return Ok(Control::Return(value)),
+            State::Pending { thunk } => thunk,
+        };
+
+        self.push(Frame::Force { promise })?;
+        if !is_procedure(&thunk) {
+            return Ok(Control::Eval(thunk, self.env));
+        }
+
+        let base = self.values.len();
+        self.values.push(thunk);
+        Ok(Control::Apply(base))
+    }
+
+    // File literals bypass the name lookup that keeps a sandbox's code away
+    // from primitives it leaves out, so they check the binding themselves.
+    fn allow(&self, name: &'static str) -> Result<(), &'static str> {
+        match self.env.get(name) {
+            Some(Atom::Primitive { name: bound }) if bound == name => Ok(()),
+            _ => Err("Forbidden primitive"),
+        }
+    }
+
+    // Continues `coroutine` where it left off, or starts it, with `value`
+    // as the result of the `yield` it was suspended in.
+    fn enter(
+        &mut self,
+        coroutine: &'arena Coroutine<'arena>,
+        value: Atom<'arena>,
+    ) -> Result<Control<'arena>, &'static str> {
+        let base = self.values.len();
+        let state = coroutine.state.replace(Run::Running);
+        match state {
+            Run::Fresh { procedure } => {
+                self.push(Frame::Resume {
+                    coroutine,
+                    values: base,
+                })?;
+                self.values.push(procedure);
+                Ok(Control::Apply(base))
+            }
+            Run::Suspended { frames, values } => {
+                if self.frames.len() + frames.len() >= MAX_FRAMES {
+                    *coroutine.state.borrow_mut() = Run::Suspended { frames, values };
+                    return Err("Stack overflow");
+                }
+
+                self.frames.push(Frame::Resume {
+                    coroutine,
+                    values: base,
+                });
+                self.frames
+                    .extend(frames.into_iter().map(|frame| frame.rebase(|at| at + base)));
+                self.values.extend(values);
+                Ok(Control::Return(value))
+            }
+            Run::Running => Err("Coroutine is already running"),
+            Run::Done => {
+                *coroutine.state.borrow_mut() = Run::Done;
+                Err("Coroutine is finished")
+            }
+        }
+    }
+
+    // Suspends the innermost running coroutine, handing `value` to whoever
+    // resumed it.
+    fn suspend(&mut self, value: Atom<'arena>) -> Result<Control<'arena>, &'static str> {
+        let index = self
+            .frames
+            .iter()
+            .rposition(|frame| matches!(frame, Frame::Resume { .. }))
+            .ok_or("Yield outside a coroutine")?;
+        let Frame::Resume {
+            coroutine,
+            values: start,
+        } = self.frames[index]
+        else {
+            unreachable!("rposition found a resume frame");
+        };
+
+        let frames = self
+            .frames
+            .drain(index + 1..)
+            .map(|frame| frame.rebase(|at| at - start))
+            .collect();
+        let values = self.values.drain(start..).collect();
+        *coroutine.state.borrow_mut() = Run::Suspended { frames, values };
+        Ok(Control::Return(value))
+    }
+
+    // Reads, expands and evaluates a file in the top-level environment,
+    // the last form in tail position.
+    fn load(&mut self, path: &str) -> Result<Control<'arena>, &'static str> {
+        let arena = self.arena;
+        let code = parse_file_with(arena, path, &mut self.env.symbols())
+            .map_err(|_| "Unable to load file")?;
+        let code = self.expand(code)?;
+        self.body(code, self.env)
+    }
+
+    // Expands code read with the environment's table, then interns the
+    // names the expander made up, so every symbol in the result is found
+    // by address.
+    fn expand(
+        &self,
+        code: Array<Expression<'arena>>,
+    ) -> Result<Array<Expression<'arena>>, &'static str> {
+        let mut code = expand(self.arena, &code[..code.len()])?;
+        self.env
+            .symbols()
+            .resolve(&mut code)
+            .ok_or("Failed to intern symbol")?;
+        Ok(code)
+    }
+}
 
-    loop {
-	if let Some(ref atom) = current_expr {
-	    match unsafe { atom.as_ref().payload } {
-		Atom::List { body } => {
-		    let head = &body[0].payload;
-		    match head {
-			Atom::Define => {
-			    return eval_define(arena, env, &body); 
-			}
-			Atom::Cons => {
-			    return eval_cons(arena, env, &body);
-			}
-			_ => {
-			}
-		    }
-		}
-		Atom::True => return Ok(Atom::True),
-		_ => {}
-	    }
-	}
+// The expression a `Collect` frame evaluates for `form`.
+fn operand<'arena>(form: &Expression<'arena>, then: Then<'arena>) -> EvalResult<'arena> {
+    match then {
+        Then::Let { .. } => binding(form).map(|(_, init)| init),
+        _ => Ok(form.payload),
     }
 }
@@ -0,0 +1,947 @@
+use crate::read::{Atom, Expression, Span};
+use crate::{Arena, Array, make, strmake};
+
+type ExpandResult<'arena> = Result<Expression<'arena>, &'static str>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Thread {
+    First,
+    Last,
+}
+
+// A compiled pattern is a straight line of tests and bindings; the first
+// failing test jumps to the next clause, so nothing is ever retried.
+#[derive(Debug, Clone, Copy)]
+enum Step<'arena> {
+    Test(Expression<'arena>),
+    Bind(Expression<'arena>, Expression<'arena>),
+}
+
+pub struct Expander<'arena> {
+    arena: &'arena Arena<'arena>,
+    gensyms: usize,
+}
+
+/// Rewrites the threading forms `->`, `<-`, `as->`, `some->` and `some<-`
+/// in `root` into plain nested applications, `delay`/`stream-cons` into
+/// calls that build promises, `generator` into one that builds a
+/// coroutine, `define-record-type` into the definitions of its
+/// constructor, predicate, accessors and modifiers, `match` into nested
+/// tests and bindings, and `when`, `unless`, `cond` and `case` into `if`.
+/// Type annotations are erased. Quoted data is left as written, and in a
+/// quasiquote template only unquoted forms are expanded. `some->` and
+/// `some<-` stop at `#nil` only, not at the empty list.
+pub fn expand<'arena>(
+    arena: &'arena Arena<'arena>,
+    root: &[Expression<'arena>],
+) -> Result<Array<Expression<'arena>>, &'static str> {
+    Expander::new(arena).expand(root)
+}
+
+pub(crate) fn is_colon(expr: &Expression) -> bool {
+    expr.payload == Atom::Symbol { name: ":" }
+}
+
+// (name : Type)
+fn is_annotation(body: &Array<Expression>) -> bool {
+    body.len() == 3 && matches!(body[0].payload, Atom::Symbol { .. }) && is_colon(&body[1])
+}
+
+pub(crate) fn annotated(body: &[Expression]) -> bool {
+    let params = match body.get(1) {
+        Some(Expression {
+            payload: Atom::List { body: params },
+            ..
+        }) => &params[..params.len()],
+        _ => &[],
+    };
+
+    body.get(2).is_some_and(is_colon)
+        || params.iter().any(|param| match param.payload {
+            Atom::List { ref body } => is_annotation(body),
+            _ => false,
+        })
+}
+
+// Splits `p ...` off the end of a sequence pattern.
+fn ellipsis<'p, 'arena>(
+    items: &'p [Expression<'arena>],
+) -> Result<(&'p [Expression<'arena>], Option<Expression<'arena>>), &'static str> {
+    match items {
+        [
+            fixed @ ..,
+            rest,
+            Expression {
+                payload: Atom::Symbol { name: "..." },
+                ..
+            },
+        ] => match rest.payload {
+            Atom::Symbol { name } if name != "..." => Ok((fixed, Some(*rest))),
+            _ => Err("Ellipsis must follow a variable"),
+        },
+        _ => Ok((items, None)),
+    }
+}
+
+impl<'arena> Expander<'arena> {
+    pub fn new(arena: &'arena Arena<'arena>) -> Self {
+        Expander { arena, gensyms: 0 }
+    }
+
+    pub fn expand(
+        &mut self,
+        root: &[Expression<'arena>],
+    ) -> Result<Array<Expression<'arena>>, &'static str> {
+        let mut exprs = self.array(root.len())?;
+        for expr in root {
+            exprs.push(&self.expression(expr)?);
+        }
+        Ok(exprs)
+    }
+
+    fn expression(&mut self, expr: &Expression<'arena>) -> ExpandResult<'arena> {
+        let body = match expr.payload {
+            Atom::List { ref body } => body,
+            Atom::Vector { ref body } => {
+                let body = self.expand(&body[..body.len()])?;
+                return Ok(Expression {
+                    payload: Atom::Vector { body },
+                    ..*expr
+                });
+            }
+            Atom::Map { map } => {
+                let values: Vec<_> = map.values().copied().collect();
+                let values = self.expand(&values)?;
+                return Ok(Expression {
+                    payload: Atom::Map {
+                        map: map.with_values(self.arena, &values[..values.len()])?,
+                    },
+                    ..*expr
+                });
+            }
+            _ => return Ok(*expr),
+        };
+        let body = &body[..body.len()];
+
+        let rewritten = match body[0].payload {
+            // Quoted data is not code, so none of it is rewritten.
+            Atom::Symbol { name: "quote" } => return Ok(*expr),
+            Atom::Symbol { name: "quasiquote" } => return self.template(expr),
+            Atom::ArrowRight => self.thread(expr.depth, &body[1..], Thread::First)?,
+            Atom::ArrowLeft => self.thread(expr.depth, &body[1..], Thread::Last)?,
+            Atom::Symbol { name: "as->" } => self.thread_as(expr.depth, &body[1..])?,
+            Atom::Symbol { name: "some->" } => {
+                self.thread_some(expr.depth, &body[1..], Thread::First)?
+            }
+            Atom::Symbol { name: "some<-" } => {
+                self.thread_some(expr.depth, &body[1..], Thread::Last)?
+            }
+            Atom::Symbol { name: "delay" } => self.delay(expr.depth, &body[1..])?,
+            Atom::Symbol {
+                name: "stream-cons",
+            } => self.stream_cons(expr.depth, &body[1..])?,
+            Atom::Symbol { name: "generator" } => self.generator(expr.depth, &body[1..])?,
+            Atom::Symbol {
+                name: "define-record-type",
+            } => self.record_type(expr.depth, &body[1..])?,
+            Atom::Symbol { name: "match" } => self.match_form(expr.depth, &body[1..])?,
+            Atom::Symbol { name: "when" } => self.when(expr.depth, &body[1..], true)?,
+            Atom::Symbol { name: "unless" } => self.when(expr.depth, &body[1..], false)?,
+            Atom::Symbol { name: "cond" } => self.cond(expr.depth, &body[1..])?,
+            Atom::Symbol { name: "case" } => self.case(expr.depth, &body[1..])?,
+            Atom::Define | Atom::Symbol { name: "lambda" } if annotated(body) => {
+                self.erase(expr.depth, body)?
+            }
+            _ => {
+                let mut exprs = self.array(body.len())?;
+                for child in body {
+                    exprs.push(&self.expression(child)?);
+                }
+                return Ok(Expression {
+                    payload: Atom::List { body: exprs },
+                    ..*expr
+                });
+            }
+        };
+
+        // The rewritten form stands in for the original, so it keeps the
+        // original's place in the source.
+        let expanded = self.expression(&rewritten)?;
+        Ok(Expression {
+            span: expr.span,
+            ..expanded
+        })
+    }
+
+    // In a quasiquote template only what is unquoted is code.
+    fn template(&mut self, expr: &Expression<'arena>) -> ExpandResult<'arena> {
+        let (body, vector) = match expr.payload {
+            Atom::List { ref body } => (body, false),
+            Atom::Vector { ref body } => (body, true),
+            _ => return Ok(*expr),
+        };
+        let body = &body[..body.len()];
+
+        if let Some(Atom::Symbol {
+            name: "unquote" | "unquote-splicing",
+        }) = body.first().map(|head| head.payload)
+        {
+            return self.expression(expr);
+        }
+
+        let mut exprs = self.array(body.len())?;
+        for child in body {
+            exprs.push(&self.template(child)?);
+        }
+        let payload = match vector {
+            true => Atom::Vector { body: exprs },
+            false => Atom::List { body: exprs },
+        };
+        Ok(Expression { payload, ..*expr })
+    }
+
+    // (-> x (f a) g) => (g (f x a)), (<- x (f a) g) => (g (f a x))
+    fn thread(
+        &self,
+        depth: usize,
+        args: &[Expression<'arena>],
+        direction: Thread,
+    ) -> ExpandResult<'arena> {
+        let (initial, steps) = args.split_first().ok_or("Nothing to thread")?;
+        let mut current = *initial;
+
+        for step in steps {
+            current = self.step(depth, &current, step, direction)?;
+        }
+
+        self.relocate(&current, depth)
+    }
+
+    // (as-> x v (f v 1) (g 2 v)) => (let* ((v x) (v (f v 1)) (v (g 2 v))) v)
+    fn thread_as(&self, depth: usize, args: &[Expression<'arena>]) -> ExpandResult<'arena> {
+        if args.len() < 2 {
+            return Err("as-> requires an initial value and a name");
+        }
+
+        let name = args[1];
+        if !matches!(name.payload, Atom::Symbol { .. }) {
+            return Err("as-> binding must be a symbol");
+        }
+
+        let mut bindings = self.array(args.len() - 1)?;
+        bindings.push(&self.list(depth + 2, &[name, args[0]])?);
+        for step in &args[2..] {
+            bindings.push(&self.list(depth + 2, &[name, *step])?);
+        }
+
+        let keyword = self.symbol("let*");
+        let bindings = self.list(depth + 1, &bindings[..bindings.len()])?;
+        self.list(depth, &[keyword, bindings, name])
+    }
+
+    // (some-> x f g) => (let ((t x)) (if (= t nil) nil (some-> (f t) g)))
+    // Only #nil stops the chain; the empty list is a value like any other.
+    fn thread_some(
+        &mut self,
+        depth: usize,
+        args: &[Expression<'arena>],
+        direction: Thread,
+    ) -> ExpandResult<'arena> {
+        let (initial, steps) = args.split_first().ok_or("Nothing to thread")?;
+        let Some((step, rest)) = steps.split_first() else {
+            return self.relocate(initial, depth);
+        };
+
+        let temp = self.gensym("some")?;
+        let binding = self.list(depth + 2, &[temp, *initial])?;
+        let bindings = self.list(depth + 1, &[binding])?;
+
+        let nil = self.atom(Atom::Nil);
+        let test = self.list(depth + 2, &[self.atom(Atom::Eq), temp, nil])?;
+
+        let mut threaded = self.array(rest.len() + 2)?;
+        let head = match direction {
+            Thread::First => "some->",
+            Thread::Last => "some<-",
+        };
+        threaded.push(&self.symbol(head));
+        threaded.push(&self.step(depth + 2, &temp, step, direction)?);
+        threaded.concat(rest);
+        let threaded = self.list(depth + 2, &threaded[..threaded.len()])?;
+
+        let branch = self.list(depth + 1, &[self.symbol("if"), test, nil, threaded])?;
+        self.list(depth, &[self.symbol("let"), bindings, branch])
+    }
+
+    // (delay e) => (%delay (lambda () e))
+    fn delay(&self, depth: usize, args: &[Expression<'arena>]) -> ExpandResult<'arena> {
+        let [value] = args else {
+            return Err("delay takes exactly one expression");
+        };
+
+        let thunk = self.list(
+            depth + 1,
+            &[self.symbol("lambda"), self.atom(Atom::Void), *value],
+        )?;
+        self.list(depth, &[self.symbol("%delay"), thunk])
+    }
+
+    // (stream-cons a b) => (cons a (delay b))
+    fn stream_cons(&self, depth: usize, args: &[Expression<'arena>]) -> ExpandResult<'arena> {
+        let [head, tail] = args else {
+            return Err("stream-cons takes exactly two expressions");
+        };
+
+        let tail = self.list(depth + 1, &[self.symbol("delay"), *tail])?;
+        self.list(depth, &[self.atom(Atom::Cons), *head, tail])
+    }
+
+    // (define-record-type point (make-point x y) point? (x point-x set-point-x!) (y point-y))
+    // => (begin (define point (%record-type 'point 'x 'y))
+    //           (define (make-point x y) (%record point x y))
+    //           (define (point? %object) (%record? point %object))
+    //           (define (point-x %object) (%record-ref point %object 0))
+    //           (define (set-point-x! %object %value) (%record-set! point %object 0 %value))
+    //           (define (point-y %object) (%record-ref point %object 1)))
+    fn record_type(&self, depth: usize, args: &[Expression<'arena>]) -> ExpandResult<'arena> {
+        let [kind, constructor, predicate, specs @ ..] = args else {
+            return Err("define-record-type needs a name, constructor and predicate");
+        };
+
+        let Atom::Symbol { name } = kind.payload else {
+            return Err("Record type name must be a symbol");
+        };
+        let display = name
+            .strip_prefix('<')
+            .and_then(|name| name.strip_suffix('>'))
+            .unwrap_or(name);
+
+        let mut fields = self.array(specs.len())?;
+        for spec in specs {
+            match spec.payload {
+                Atom::List { ref body } => match body[0].payload {
+                    Atom::Symbol { .. } if body.len() <= 3 => {
+                        fields.push(&body[0]);
+                    }
+                    _ => return Err("Malformed record field"),
+                },
+                Atom::Symbol { .. } => {
+                    fields.push(spec);
+                }
+                _ => return Err("Malformed record field"),
+            }
+        }
+        let fields = &fields[..fields.len()];
+
+        let object = self.symbol("%object");
+        let value = self.symbol("%value");
+        let mut forms = self.array(2 * specs.len() + 4)?;
+        forms.push(&self.symbol("begin"));
+
+        // The type itself
+        let mut call = self.array(fields.len() + 2)?;
+        call.push(&self.symbol("%record-type"));
+        call.push(&self.atom(Atom::Quoted { name: display }));
+        for field in fields {
+            if let Atom::Symbol { name } = field.payload {
+                call.push(&self.atom(Atom::Quoted { name }));
+            }
+        }
+        let call = self.list(depth + 2, &call[..call.len()])?;
+        forms.push(&self.list(depth + 1, &[self.atom(Atom::Define), *kind, call])?);
+
+        // The constructor takes its arguments in its own order and leaves
+        // the remaining fields nil.
+        let params = match constructor.payload {
+            Atom::Symbol { .. } => fields,
+            Atom::List { ref body } => &body[1..body.len()],
+            _ => return Err("Malformed record constructor"),
+        };
+        let name = match constructor.payload {
+            Atom::List { ref body } => body[0],
+            _ => *constructor,
+        };
+
+        let mut call = self.array(fields.len() + 2)?;
+        call.push(&self.symbol("%record"));
+        call.push(kind);
+        for field in fields {
+            if !params.iter().any(|p| p.payload == field.payload) {
+                call.push(&self.atom(Atom::Nil));
+            } else {
+                call.push(field);
+            }
+        }
+        for param in params {
+            if !fields.iter().any(|f| f.payload == param.payload) {
+                return Err("Record constructor argument is not a field");
+            }
+        }
+
+        let mut signature = self.array(params.len() + 1)?;
+        signature.push(&name);
+        signature.concat(params);
+        forms.push(&self.define(
+            depth + 1,
+            &signature[..signature.len()],
+            &call[..call.len()],
+        )?);
+
+        forms.push(&self.define(
+            depth + 1,
+            &[*predicate, object],
+            &[self.symbol("%record?"), *kind, object],
+        )?);
+
+        // Accessors and modifiers
+        for (index, spec) in specs.iter().enumerate() {
+            let Atom::List { ref body } = spec.payload else {
+                continue;
+            };
+            let index = self.atom(Atom::Int {
+                inner: index as i64,
+            });
+
+            if let Some(accessor) = body.get(1) {
+                forms.push(&self.define(
+                    depth + 1,
+                    &[*accessor, object],
+                    &[self.symbol("%record-ref"), *kind, object, index],
+                )?);
+            }
+
+            if let Some(modifier) = body.get(2) {
+                forms.push(&self.define(
+                    depth + 1,
+                    &[*modifier, object, value],
+                    &[self.symbol("%record-set!"), *kind, object, index, value],
+                )?);
+            }
+        }
+
+        self.list(depth, &forms[..forms.len()])
+    }
+
+    // (match e (p1 b1...) (p2 b2...))
+    // => (let ((%match-0 e))
+    //      (let ((%fail-1 (lambda () (let ((%fail-2 (lambda () (%match-error %match-0))))
+    //                                  <p2 against %match-0, else (%fail-2)>))))
+    //        <p1 against %match-0, else (%fail-1)>))
+    fn match_form(&mut self, depth: usize, args: &[Expression<'arena>]) -> ExpandResult<'arena> {
+        let (value, clauses) = args.split_first().ok_or("match needs an expression")?;
+        let subject = self.gensym("match")?;
+
+        let mut code = self.list(0, &[self.symbol("%match-error"), subject])?;
+        for clause in clauses.iter().rev() {
+            let Atom::List { ref body } = clause.payload else {
+                return Err("match clause must be a list");
+            };
+            let body = &body[..body.len()];
+            if body.len() < 2 {
+                return Err("match clause needs a pattern and a body");
+            }
+
+            let fail = self.gensym("fail")?;
+            let thunk = self.list(0, &[self.symbol("lambda"), self.atom(Atom::Void), code])?;
+            let call = self.list(0, &[fail])?;
+
+            let mut steps = Vec::new();
+            self.pattern(subject, &body[0], &mut steps)?;
+            let success = self.sequence(&body[1..])?;
+
+            let clause = self.steps(&steps, success, call)?;
+            code = self.bind(fail, thunk, clause)?;
+        }
+
+        let code = self.bind(subject, *value, code)?;
+        self.relocate(&code, depth)
+    }
+
+    // (when c e...) => (if c (begin e...) nil), unless swaps the branches
+    fn when(&self, depth: usize, args: &[Expression<'arena>], when: bool) -> ExpandResult<'arena> {
+        let (test, body) = args.split_first().ok_or("when and unless need a test")?;
+        if body.is_empty() {
+            return Err("when and unless need a body");
+        }
+
+        let body = self.sequence(body)?;
+        let nil = self.atom(Atom::Nil);
+        let (then, otherwise) = if when { (body, nil) } else { (nil, body) };
+        self.list(depth, &[self.symbol("if"), *test, then, otherwise])
+    }
+
+    // (cond (a x) (b) (c => f) (else y))
+    // => (if a x (or b (let ((%cond-0 c)) (if %cond-0 (f %cond-0) y))))
+    fn cond(&mut self, depth: usize, clauses: &[Expression<'arena>]) -> ExpandResult<'arena> {
+        let mut code = self.atom(Atom::Nil);
+        for (i, clause) in clauses.iter().enumerate().rev() {
+            let Atom::List { ref body } = clause.payload else {
+                return Err("cond clause must be a list");
+            };
+
+            code = match &body[..body.len()] {
+                [
+                    Expression {
+                        payload: Atom::Symbol { name: "else" },
+                        ..
+                    },
+                    rest @ ..,
+                ] => {
+                    if i + 1 != clauses.len() {
+                        return Err("else must be the last cond clause");
+                    }
+                    self.sequence(rest)?
+                }
+                [test] => self.list(0, &[self.symbol("or"), *test, code])?,
+                [
+                    test,
+                    Expression {
+                        payload: Atom::Symbol { name: "=>" },
+                        ..
+                    },
+                    receiver,
+                ] => {
+                    let temp = self.gensym("cond")?;
+                    let call = self.list(0, &[*receiver, temp])?;
+                    let branch = self.list(0, &[self.symbol("if"), temp, call, code])?;
+                    self.bind(temp, *test, branch)?
+                }
+                [test, rest @ ..] => {
+                    let then = self.sequence(rest)?;
+                    self.list(0, &[self.symbol("if"), *test, then, code])?
+                }
+                [] => return Err("cond clause must not be empty"),
+            };
+        }
+
+        self.relocate(&code, depth)
+    }
+
+    // (case k ((a 1) x) (else y))
+    // => (let ((%case-0 k)) (cond ((or (eqv? %case-0 'a) (eqv? %case-0 1)) x) (else y)))
+    fn case(&mut self, depth: usize, args: &[Expression<'arena>]) -> ExpandResult<'arena> {
+        let (key, clauses) = args.split_first().ok_or("case needs a key")?;
+        let temp = self.gensym("case")?;
+
+        let mut rewritten = self.array(clauses.len() + 1)?;
+        rewritten.push(&self.symbol("cond"));
+        for clause in clauses {
+            let Atom::List { ref body } = clause.payload else {
+                return Err("case clause must be a list");
+            };
+            let body = &body[..body.len()];
+            let Some((data, rest)) = body.split_first() else {
+                return Err("case clause must not be empty");
+            };
+
+            let test = match data.payload {
+                Atom::Symbol { name: "else" } => *data,
+                Atom::Void => self.atom(Atom::False),
+                Atom::List { body: ref data } => {
+                    let mut tests = self.array(data.len() + 1)?;
+                    tests.push(&self.symbol("or"));
+                    for datum in data.iter() {
+                        let datum = match datum.payload {
+                            Atom::Symbol { name } => self.atom(Atom::Quoted { name }),
+                            _ => *datum,
+                        };
+                        tests.push(&self.call("eqv?", &[temp, datum])?);
+                    }
+                    self.list(0, &tests[..tests.len()])?
+                }
+                _ => return Err("case data must be a list"),
+            };
+
+            let mut forms = self.array(body.len())?;
+            forms.push(&test);
+            forms.concat(rest);
+            rewritten.push(&self.list(0, &forms[..forms.len()])?);
+        }
+
+        let cond = self.list(0, &rewritten[..rewritten.len()])?;
+        let code = self.bind(temp, *key, cond)?;
+        self.relocate(&code, depth)
+    }
+
+    // A body of several forms becomes (begin forms...)
+    fn sequence(&self, body: &[Expression<'arena>]) -> ExpandResult<'arena> {
+        match body {
+            [] => Ok(self.atom(Atom::Nil)),
+            [single] => Ok(*single),
+            _ => {
+                let mut forms = self.array(body.len() + 1)?;
+                forms.push(&self.symbol("begin"));
+                forms.concat(body);
+                self.list(0, &forms[..forms.len()])
+            }
+        }
+    }
+
+    fn pattern(
+        &mut self,
+        subject: Expression<'arena>,
+        pattern: &Expression<'arena>,
+        steps: &mut Vec<Step<'arena>>,
+    ) -> Result<(), &'static str> {
+        match pattern.payload {
+            Atom::Symbol { name: "_" } => {}
+            Atom::Symbol { name: "..." } => return Err("Misplaced ellipsis in pattern"),
+            Atom::Symbol { .. } => steps.push(Step::Bind(*pattern, subject)),
+            Atom::Void => steps.push(Step::Test(self.call("null?", &[subject])?)),
+            Atom::True
+            | Atom::False
+            | Atom::Nil
+            | Atom::Int { .. }
+            | Atom::BigInt { .. }
+            | Atom::Rational { .. }
+            | Atom::Number { .. }
+            | Atom::Char { .. }
+            | Atom::String { .. }
+            | Atom::Quoted { .. }
+            | Atom::Code { .. } => {
+                steps.push(Step::Test(self.call("equal?", &[subject, *pattern])?));
+            }
+            Atom::List { ref body } => {
+                let body = &body[..body.len()];
+                match body[0].payload {
+                    // (? pred p...) tests the subject and then matches each p against it.
+                    Atom::Symbol { name: "?" } => {
+                        let pred = body.get(1).ok_or("? pattern needs a predicate")?;
+                        steps.push(Step::Test(self.list(0, &[*pred, subject])?));
+                        for inner in &body[2..] {
+                            self.pattern(subject, inner, steps)?;
+                        }
+                    }
+                    // ($ type p...) matches record fields by position.
+                    Atom::Symbol { name: "$" } => {
+                        let kind = body.get(1).ok_or("$ pattern needs a record type")?;
+                        steps.push(Step::Test(self.call("%record?", &[*kind, subject])?));
+                        for (index, inner) in body[2..].iter().enumerate() {
+                            let index = self.atom(Atom::Int {
+                                inner: index as i64,
+                            });
+                            let field = self.call("%record-ref", &[*kind, subject, index])?;
+                            self.part(field, inner, steps)?;
+                        }
+                    }
+                    _ => self.list_pattern(subject, body, steps)?,
+                }
+            }
+            Atom::Vector { ref body } => {
+                self.vector_pattern(subject, &body[..body.len()], steps)?
+            }
+            _ => return Err("Unsupported pattern"),
+        }
+
+        Ok(())
+    }
+
+    fn list_pattern(
+        &mut self,
+        subject: Expression<'arena>,
+        items: &[Expression<'arena>],
+        steps: &mut Vec<Step<'arena>>,
+    ) -> Result<(), &'static str> {
+        let (items, rest) = ellipsis(items)?;
+
+        let mut current = subject;
+        for item in items {
+            steps.push(Step::Test(self.call("pair?", &[current])?));
+            let head = self.list(0, &[self.atom(Atom::Head), current])?;
+            self.part(head, item, steps)?;
+
+            let tail = self.gensym("tail")?;
+            steps.push(Step::Bind(
+                tail,
+                self.list(0, &[self.atom(Atom::Tail), current])?,
+            ));
+            current = tail;
+        }
+
+        match rest {
+            Some(rest) => self.pattern(current, &rest, steps),
+            None => {
+                steps.push(Step::Test(self.call("null?", &[current])?));
+                Ok(())
+            }
+        }
+    }
+
+    fn vector_pattern(
+        &mut self,
+        subject: Expression<'arena>,
+        items: &[Expression<'arena>],
+        steps: &mut Vec<Step<'arena>>,
+    ) -> Result<(), &'static str> {
+        let (items, rest) = ellipsis(items)?;
+
+        let count = self.atom(Atom::Int {
+            inner: items.len() as i64,
+        });
+        let length = self.call("vector-length", &[subject])?;
+        let compare = match rest {
+            Some(_) => Atom::GTE,
+            None => Atom::Eq,
+        };
+        steps.push(Step::Test(self.call("vector?", &[subject])?));
+        steps.push(Step::Test(
+            self.list(0, &[self.atom(compare), length, count])?,
+        ));
+
+        for (index, item) in items.iter().enumerate() {
+            let index = self.atom(Atom::Int {
+                inner: index as i64,
+            });
+            let element = self.call("vector-ref", &[subject, index])?;
+            self.part(element, item, steps)?;
+        }
+
+        if let Some(rest) = rest {
+            let tail = self.call("%vector-tail", &[subject, count])?;
+            self.part(tail, &rest, steps)?;
+        }
+
+        Ok(())
+    }
+
+    // Matches a sub-value, binding it to a temporary first unless the
+    // pattern would ignore or just name it.
+    fn part(
+        &mut self,
+        value: Expression<'arena>,
+        pattern: &Expression<'arena>,
+        steps: &mut Vec<Step<'arena>>,
+    ) -> Result<(), &'static str> {
+        match pattern.payload {
+            Atom::Symbol { name: "_" } => Ok(()),
+            Atom::Symbol { name } if name != "..." => {
+                steps.push(Step::Bind(*pattern, value));
+                Ok(())
+            }
+            _ => {
+                let temp = self.gensym("part")?;
+                steps.push(Step::Bind(temp, value));
+                self.pattern(temp, pattern, steps)
+            }
+        }
+    }
+
+    fn steps(
+        &self,
+        steps: &[Step<'arena>],
+        success: Expression<'arena>,
+        fail: Expression<'arena>,
+    ) -> ExpandResult<'arena> {
+        let mut code = success;
+        for step in steps.iter().rev() {
+            code = match *step {
+                Step::Test(test) => self.list(0, &[self.symbol("if"), test, code, fail])?,
+                Step::Bind(name, value) => self.bind(name, value, code)?,
+            };
+        }
+        Ok(code)
+    }
+
+    // (let ((name value)) body)
+    fn bind(
+        &self,
+        name: Expression<'arena>,
+        value: Expression<'arena>,
+        body: Expression<'arena>,
+    ) -> ExpandResult<'arena> {
+        let binding = self.list(0, &[name, value])?;
+        let bindings = self.list(0, &[binding])?;
+        self.list(0, &[self.symbol("let"), bindings, body])
+    }
+
+    fn call(&self, name: &'arena str, args: &[Expression<'arena>]) -> ExpandResult<'arena> {
+        let mut exprs = self.array(args.len() + 1)?;
+        exprs.push(&self.symbol(name));
+        exprs.concat(args);
+        self.list(0, &exprs[..exprs.len()])
+    }
+
+    // (define (f (x : Int) y) : Int body) => (define (f x y) body)
+    // (define x : Int 1) => (define x 1)
+    // (lambda ((x : Int)) : Int body) => (lambda (x) body)
+    fn erase(&self, depth: usize, body: &[Expression<'arena>]) -> ExpandResult<'arena> {
+        let [head, target, rest @ ..] = body else {
+            return Err("Malformed definition");
+        };
+
+        let target = match target.payload {
+            Atom::List { body: ref params } => {
+                let mut erased = self.array(params.len())?;
+                for param in &params[..params.len()] {
+                    match param.payload {
+                        Atom::List {
+                            body: ref annotated,
+                        } if is_annotation(annotated) => {
+                            erased.push(&annotated[0]);
+                        }
+                        _ => {
+                            erased.push(param);
+                        }
+                    }
+                }
+                self.list(depth + 1, &erased[..erased.len()])?
+            }
+            _ => *target,
+        };
+
+        let rest = match rest {
+            [colon, _, rest @ ..] if is_colon(colon) => rest,
+            _ => rest,
+        };
+
+        let mut exprs = self.array(rest.len() + 2)?;
+        exprs.push(head);
+        exprs.push(&target);
+        exprs.concat(rest);
+        self.list(depth, &exprs[..exprs.len()])
+    }
+
+    // (define (signature...) (body...))
+    fn define(
+        &self,
+        depth: usize,
+        signature: &[Expression<'arena>],
+        body: &[Expression<'arena>],
+    ) -> ExpandResult<'arena> {
+        let signature = self.list(depth + 1, signature)?;
+        let body = self.list(depth + 1, body)?;
+        self.list(depth, &[self.atom(Atom::Define), signature, body])
+    }
+
+    fn step(
+        &self,
+        depth: usize,
+        value: &Expression<'arena>,
+        step: &Expression<'arena>,
+        direction: Thread,
+    ) -> ExpandResult<'arena> {
+        match step.payload {
+            Atom::List { ref body } => {
+                let body = &body[..body.len()];
+                let mut exprs = self.array(body.len() + 1)?;
+                exprs.push(&body[0]);
+                if direction == Thread::First {
+                    exprs.push(value);
+                    exprs.concat(&body[1..]);
+                } else {
+                    exprs.concat(&body[1..]);
+                    exprs.push(value);
+                }
+                self.list(depth, &exprs[..exprs.len()])
+            }
+            Atom::Void | Atom::Code { .. } => Err("Unable to thread through this form"),
+            _ => self.list(depth, &[*step, *value]),
+        }
+    }
+
+    // (generator a b) => (make-coroutine (lambda () (begin a b)))
+    fn generator(&self, depth: usize, body: &[Expression<'arena>]) -> ExpandResult<'arena> {
+        if body.is_empty() {
+            return Err("generator needs a body");
+        }
+
+        let thunk = self.list(
+            depth + 1,
+            &[
+                self.symbol("lambda"),
+                self.atom(Atom::Void),
+                self.sequence(body)?,
+            ],
+        )?;
+        self.list(depth, &[self.symbol("make-coroutine"), thunk])
+    }
+
+    fn gensym(&mut self, prefix: &str) -> ExpandResult<'arena> {
+        let arena = self.arena;
+        let id = self.gensyms;
+        self.gensyms += 1;
+
+        strmake!(arena, "%{prefix}-{id}")
+            .map(|name| self.atom(Atom::Symbol { name }))
+            .ok_or("Failed to allocate symbol name")
+    }
+
+    fn symbol(&self, name: &'arena str) -> Expression<'arena> {
+        self.atom(Atom::Symbol { name })
+    }
+
+    fn atom(&self, payload: Atom<'arena>) -> Expression<'arena> {
+        Expression {
+            depth: 0,
+            payload,
+            span: Span::default(),
+        }
+    }
+
+    fn array(&self, len: usize) -> Result<Array<Expression<'arena>>, &'static str> {
+        let arena = self.arena;
+        make!(arena, Expression, len)
+            .map(Array::new)
+            .ok_or("Failed to allocate expressions")
+    }
+
+    // Builds a list at `depth`, moving `items` to the depth of its body.
+    fn list(&self, depth: usize, items: &[Expression<'arena>]) -> ExpandResult<'arena> {
+        let mut body = self.array(items.len())?;
+        for item in items {
+            body.push(&self.relocate(item, depth + 1)?);
+        }
+
+        Ok(Expression {
+            depth,
+            payload: Atom::List { body },
+            span: Span::default(),
+        })
+    }
+
+    fn relocate(&self, expr: &Expression<'arena>, depth: usize) -> ExpandResult<'arena> {
+        if expr.depth == depth {
+            return Ok(*expr);
+        }
+
+        let payload = match expr.payload {
+            Atom::List { ref body } => Atom::List {
+                body: self.relocate_body(body, depth + 1)?,
+            },
+            Atom::Code { ref body } => Atom::Code {
+                body: self.relocate_body(body, depth + 1)?,
+            },
+            Atom::Vector { ref body } => Atom::Vector {
+                body: self.relocate_body(body, depth + 1)?,
+            },
+            Atom::Map { map } => {
+                let mut values = Vec::with_capacity(map.len());
+                for value in map.values() {
+                    values.push(self.relocate(value, depth + 1)?);
+                }
+                Atom::Map {
+                    map: map.with_values(self.arena, &values)?,
+                }
+            }
+            atom => atom,
+        };
+
+        Ok(Expression {
+            depth,
+            payload,
+            span: expr.span,
+        })
+    }
+
+    fn relocate_body(
+        &self,
+        body: &Array<Expression<'arena>>,
+        depth: usize,
+    ) -> Result<Array<Expression<'arena>>, &'static str> {
+        let mut exprs = self.array(body.len())?;
+        for item in body.iter() {
+            exprs.push(&self.relocate(item, depth)?);
+        }
+        Ok(exprs)
+    }
+}
@@ -0,0 +1,847 @@
+use crate::env::{self, Env};
+use crate::eval;
+use crate::lazy::{self, State};
+use crate::map::Map;
+use crate::numeric::{BigInt, Ratio};
+use crate::pair;
+use crate::read::{Atom, Expression, Span};
+use crate::record;
+use crate::symbol::SymbolTable;
+use crate::{Arena, Array, make};
+use core::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"TYSN";
+const VERSION: u32 = 2;
+
+// Encoding and decoding recurse once per level of nesting, so deep data,
+// or a crafted image, could otherwise exhaust the stack. The encoder holds
+// to the same bound so that it never writes an image it cannot read.
+const MAX_DEPTH: usize = 512;
+
+// Images are self-contained: every pointer in the arena (string slices and
+// expression arrays) is written out by value and rebuilt on restore, so an
+// image does not depend on the address its arena was mapped at. Objects
+// whose identity matters (promises, record types, records and closures) are
+// the exception: each is numbered the first time it is written and referred
+// to by that number afterwards, so sharing and cycles survive a round trip.
+//
+// An image holds one flat environment, so only closures over the frames it
+// was taken from can be written; they close over the restored environment.
+// Tasks and coroutines cannot be written at all.
+mod tag {
+    pub const TRUE: u8 = 0;
+    pub const FALSE: u8 = 1;
+    pub const VOID: u8 = 2;
+    pub const NIL: u8 = 3;
+    pub const INT: u8 = 4;
+    pub const NUMBER: u8 = 5;
+    pub const STRING: u8 = 6;
+    pub const BUFFER: u8 = 7;
+    pub const FILE: u8 = 8;
+    pub const DEFINE: u8 = 9;
+    pub const CONS: u8 = 10;
+    pub const HEAD: u8 = 11;
+    pub const TAIL: u8 = 12;
+    pub const SYMBOL: u8 = 13;
+    pub const QUOTED: u8 = 14;
+    pub const LIST: u8 = 15;
+    pub const CODE: u8 = 16;
+    pub const ADD: u8 = 17;
+    pub const SUBTRACT: u8 = 18;
+    pub const MULTIPLY: u8 = 19;
+    pub const DIVIDE: u8 = 20;
+    pub const EQ: u8 = 21;
+    pub const NEQ: u8 = 22;
+    pub const LT: u8 = 23;
+    pub const GT: u8 = 24;
+    pub const LTE: u8 = 25;
+    pub const GTE: u8 = 26;
+    pub const ARROW_LEFT: u8 = 27;
+    pub const ARROW_RIGHT: u8 = 28;
+    pub const NEGATE: u8 = 29;
+    pub const EXP: u8 = 30;
+    pub const MOD: u8 = 31;
+    pub const REMAINDER: u8 = 32;
+    pub const PRIMITIVE: u8 = 33;
+    pub const PAIR: u8 = 34;
+    pub const CLOSURE: u8 = 35;
+    pub const PROMISE: u8 = 36;
+    pub const BIG_INT: u8 = 37;
+    pub const RATIONAL: u8 = 38;
+    pub const CHAR: u8 = 39;
+    pub const VECTOR: u8 = 40;
+    pub const MAP: u8 = 41;
+    pub const RECORD_TYPE: u8 = 42;
+    pub const RECORD: u8 = 43;
+}
+
+pub(crate) struct Encoder<'w, W: Write> {
+    writer: &'w mut W,
+    // Number given to each shared object written so far, by address.
+    shared: HashMap<usize, u64>,
+    // The frames closures may close over, by id.
+    scope: Vec<usize>,
+    // Whether values that cannot be written are written as nil.
+    lossy: bool,
+    depth: usize,
+}
+
+pub(crate) struct Decoder<'arena> {
+    arena: &'arena Arena<'arena>,
+    data: &'arena [u8],
+    position: usize,
+    // Shared objects decoded so far, indexed by the encoder's numbering.
+    shared: Vec<Atom<'arena>>,
+    // The frame restored closures close over, made for the first.
+    env: Option<Env<'arena>>,
+    // Where symbol names are interned, made for the first and later shared
+    // with `env`.
+    symbols: Option<&'arena RefCell<SymbolTable<'arena>>>,
+    depth: usize,
+}
+
+/// Writes every binding visible from `env`, together with the strings and
+/// expression trees they reference, to `writer`.
+///
+/// Fails with `InvalidInput` on a value the image cannot hold: a task, a
+/// coroutine, a closure over a local frame rather than one of `env`'s
+/// (such as one returned from another procedure), or data nested more
+/// than 512 levels deep.
+pub fn snapshot<W: Write>(env: &Env, writer: &mut W) -> io::Result<()> {
+    write_image(env, writer, false)
+}
+
+/// Like `snapshot`, but writes a value that has no meaning outside this
+/// process (a task, a coroutine or a closure over a local frame) as nil
+/// instead of failing. A spawned task starts from such an image of its
+/// caller.
+pub(crate) fn snapshot_lossy<W: Write>(env: &Env, writer: &mut W) -> io::Result<()> {
+    write_image(env, writer, true)
+}
+
+/// Whether `snapshot` could write `atom` as part of an image of `env`.
+pub(crate) fn can_write(env: &Env, atom: &Atom) -> bool {
+    let mut sink = io::sink();
+    let mut encoder = Encoder::new(&mut sink);
+    encoder.scope = scope(env);
+    encoder.atom(atom).is_ok()
+}
+
+fn write_image<W: Write>(env: &Env, writer: &mut W, lossy: bool) -> io::Result<()> {
+    let vars = env.flatten();
+    let mut encoder = Encoder::new(writer);
+    encoder.scope = scope(env);
+    encoder.lossy = lossy;
+
+    encoder.bytes(MAGIC)?;
+    encoder.u32(VERSION)?;
+    encoder.u64(vars.len() as u64)?;
+
+    for (name, atom) in vars.iter() {
+        encoder.str(name)?;
+        encoder.atom(atom)?;
+    }
+
+    encoder.writer.flush()
+}
+
+// The ids of `env` and its parents, the frames an image of it can hold
+// closures over.
+fn scope(env: &Env) -> Vec<usize> {
+    let mut ids = vec![env.id()];
+    let mut parent = env.parent();
+    while let Some(frame) = parent {
+        ids.push(frame.id());
+        parent = frame.parent();
+    }
+    ids
+}
+
+/// Rebuilds an `Env` from an image produced by `snapshot`. The image is
+/// copied into `arena` once and strings are borrowed from that copy.
+///
+/// The bindings are restored into one global frame, which the restored
+/// closures share.
+pub fn restore<'arena>(
+    arena: &'arena Arena<'arena>,
+    image: &[u8],
+) -> Result<Env<'arena>, &'static str> {
+    let mut decoder = Decoder::new(arena, image)?;
+
+    if decoder.bytes(MAGIC.len())? != MAGIC {
+        return Err("Not a tyson image");
+    }
+
+    if decoder.u32()? != VERSION {
+        return Err("Unsupported image version");
+    }
+
+    let mut vars = Vec::new();
+    for _ in 0..decoder.u64()? {
+        let name = decoder.str()?;
+        let atom = decoder.atom()?;
+        vars.push((name, atom));
+    }
+
+    decoder.finish()?;
+
+    // Unless a closure needed it earlier, the environment is only built
+    // once the whole image decoded, so a rejected image does not leave
+    // frames behind in the arena.
+    let mut env = match decoder.env {
+        Some(env) => env,
+        None => {
+            let symbols = decoder.symbols()?;
+            Env::with_symbols(arena, symbols).ok_or("Failed to allocate environment")?
+        }
+    };
+    for (name, atom) in vars {
+        env.define(name, atom)
+            .ok_or("Failed to bind image variable")?;
+    }
+    Ok(env)
+}
+
+pub fn save<P: AsRef<Path>>(env: &Env, path: P) -> io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    snapshot(env, &mut io::BufWriter::new(file))
+}
+
+pub fn load<'arena, P: AsRef<Path>>(
+    arena: &'arena Arena<'arena>,
+    path: P,
+) -> Result<Env<'arena>, &'static str> {
+    let image = std::fs::read(path).map_err(|_| "Unable to read image file")?;
+    restore(arena, &image)
+}
+
+impl<'w, W: Write> Encoder<'w, W> {
+    pub(crate) fn new(writer: &'w mut W) -> Self {
+        Encoder {
+            writer,
+            shared: HashMap::new(),
+            scope: Vec::new(),
+            lossy: false,
+            depth: 0,
+        }
+    }
+
+    // Writes the number of the shared object at `address`, and whether its
+    // contents follow because this is the first time it is written.
+    fn shared<T>(&mut self, address: *const T) -> io::Result<bool> {
+        let next = self.shared.len() as u64;
+        let id = *self.shared.entry(address as usize).or_insert(next);
+        self.u64(id)?;
+        Ok(id == next)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.writer.write_all(bytes)
+    }
+
+    fn u8(&mut self, value: u8) -> io::Result<()> {
+        self.bytes(&[value])
+    }
+
+    fn u32(&mut self, value: u32) -> io::Result<()> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn u64(&mut self, value: u64) -> io::Result<()> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn str(&mut self, value: &str) -> io::Result<()> {
+        self.u64(value.len() as u64)?;
+        self.bytes(value.as_bytes())
+    }
+
+    fn big(&mut self, value: &BigInt) -> io::Result<()> {
+        self.u8(value.negative as u8)?;
+        self.u64(value.limbs.len() as u64)?;
+        for limb in value.limbs {
+            self.u32(*limb)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn expressions(&mut self, body: &[Expression]) -> io::Result<()> {
+        self.u64(body.len() as u64)?;
+        for expr in body.iter() {
+            self.u64(expr.depth as u64)?;
+            self.atom(&expr.payload)?;
+        }
+        Ok(())
+    }
+
+    fn atom(&mut self, atom: &Atom) -> io::Result<()> {
+        if self.depth == MAX_DEPTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "value nested too deeply for an image",
+            ));
+        }
+
+        self.depth += 1;
+        let written = self.payload(atom);
+        self.depth -= 1;
+        written
+    }
+
+    fn payload(&mut self, atom: &Atom) -> io::Result<()> {
+        match *atom {
+            Atom::True => self.u8(tag::TRUE),
+            Atom::False => self.u8(tag::FALSE),
+            Atom::Void => self.u8(tag::VOID),
+            Atom::Nil => self.u8(tag::NIL),
+            Atom::Int { inner } => {
+                self.u8(tag::INT)?;
+                self.bytes(&inner.to_le_bytes())
+            }
+            Atom::BigInt { ref inner } => {
+                self.u8(tag::BIG_INT)?;
+                self.big(inner)
+            }
+            Atom::Rational { inner } => {
+                self.u8(tag::RATIONAL)?;
+                self.big(&inner.numerator)?;
+                self.big(&inner.denominator)
+            }
+            Atom::Number { inner } => {
+                self.u8(tag::NUMBER)?;
+                self.bytes(&inner.to_le_bytes())
+            }
+            Atom::Char { inner } => {
+                self.u8(tag::CHAR)?;
+                self.u32(inner as u32)
+            }
+            Atom::String { inner } => {
+                self.u8(tag::STRING)?;
+                self.str(inner)
+            }
+            Atom::Buffer { data } => {
+                self.u8(tag::BUFFER)?;
+                self.u64(data.len() as u64)?;
+                self.bytes(data)
+            }
+            Atom::File { path, lazy } => {
+                self.u8(tag::FILE)?;
+                self.str(path)?;
+                self.u8(lazy as u8)
+            }
+            Atom::Define => self.u8(tag::DEFINE),
+            Atom::Cons => self.u8(tag::CONS),
+            Atom::Head => self.u8(tag::HEAD),
+            Atom::Tail => self.u8(tag::TAIL),
+            Atom::Symbol { name } => {
+                self.u8(tag::SYMBOL)?;
+                self.str(name)
+            }
+            Atom::Quoted { name } => {
+                self.u8(tag::QUOTED)?;
+                self.str(name)
+            }
+            Atom::List { ref body } => {
+                self.u8(tag::LIST)?;
+                self.expressions(&body[..body.len()])
+            }
+            Atom::Code { ref body } => {
+                self.u8(tag::CODE)?;
+                self.expressions(&body[..body.len()])
+            }
+            Atom::Primitive { name } => {
+                self.u8(tag::PRIMITIVE)?;
+                self.str(name)
+            }
+            // The cars of a chain of pairs, then whatever ends it, so a long
+            // list does not nest.
+            Atom::Pair { .. } => {
+                self.u8(tag::PAIR)?;
+                let mut cars = pair::items(*atom);
+                let count = cars.by_ref().count();
+                self.u64(count as u64)?;
+                for car in pair::items(*atom).take(count) {
+                    self.atom(&car)?;
+                }
+                self.atom(&cars.rest())
+            }
+            Atom::Closure { closure } => {
+                if !self.scope.contains(&closure.env().id()) {
+                    if self.lossy {
+                        return self.u8(tag::NIL);
+                    }
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "closure over a local frame",
+                    ));
+                }
+
+                self.u8(tag::CLOSURE)?;
+                if !self.shared(closure)? {
+                    return Ok(());
+                }
+                self.u64(closure.params().len() as u64)?;
+                for param in closure.params() {
+                    self.str(param)?;
+                }
+                match closure.rest() {
+                    Some(rest) => {
+                        self.u8(1)?;
+                        self.str(rest)?;
+                    }
+                    None => self.u8(0)?,
+                }
+                self.expressions(closure.body())
+            }
+            // A task is a running thread and a coroutine holds part of the
+            // evaluator's stack, neither of which has meaning in an image.
+            Atom::Task { .. } | Atom::Coroutine { .. } if self.lossy => self.u8(tag::NIL),
+            Atom::Task { .. } | Atom::Coroutine { .. } => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "task or coroutine in an image",
+            )),
+            Atom::Vector { ref body } => {
+                self.u8(tag::VECTOR)?;
+                self.expressions(&body[..body.len()])
+            }
+            Atom::Map { map } => {
+                self.u8(tag::MAP)?;
+                self.expressions(&map.body())
+            }
+            Atom::Promise { promise } => {
+                self.u8(tag::PROMISE)?;
+                if !self.shared(promise)? {
+                    return Ok(());
+                }
+                match promise.state() {
+                    State::Pending { thunk } => {
+                        self.u8(0)?;
+                        self.atom(&thunk)
+                    }
+                    State::Forced { value } => {
+                        self.u8(1)?;
+                        self.atom(&value)
+                    }
+                }
+            }
+            Atom::RecordType { kind } => {
+                self.u8(tag::RECORD_TYPE)?;
+                if !self.shared(kind)? {
+                    return Ok(());
+                }
+                self.str(kind.name)?;
+                self.u64(kind.fields.len() as u64)?;
+                for field in kind.fields {
+                    self.str(field)?;
+                }
+                Ok(())
+            }
+            Atom::Record { record } => {
+                self.u8(tag::RECORD)?;
+                if !self.shared(record)? {
+                    return Ok(());
+                }
+                self.atom(&record.type_of())?;
+                for value in record.values() {
+                    self.atom(&value)?;
+                }
+                Ok(())
+            }
+            Atom::Add => self.u8(tag::ADD),
+            Atom::Subtract => self.u8(tag::SUBTRACT),
+            Atom::Multiply => self.u8(tag::MULTIPLY),
+            Atom::Divide => self.u8(tag::DIVIDE),
+            Atom::Eq => self.u8(tag::EQ),
+            Atom::Neq => self.u8(tag::NEQ),
+            Atom::LT => self.u8(tag::LT),
+            Atom::GT => self.u8(tag::GT),
+            Atom::LTE => self.u8(tag::LTE),
+            Atom::GTE => self.u8(tag::GTE),
+            Atom::ArrowLeft => self.u8(tag::ARROW_LEFT),
+            Atom::ArrowRight => self.u8(tag::ARROW_RIGHT),
+            Atom::Negate => self.u8(tag::NEGATE),
+            Atom::Exp => self.u8(tag::EXP),
+            Atom::Mod => self.u8(tag::MOD),
+            Atom::Remainder => self.u8(tag::REMAINDER),
+        }
+    }
+}
+
+impl<'arena> Decoder<'arena> {
+    pub(crate) fn new(arena: &'arena Arena<'arena>, image: &[u8]) -> Result<Self, &'static str> {
+        let data = make!(arena, u8, image.len()).ok_or("Failed to allocate image")?;
+        data.copy_from_slice(image);
+
+        Ok(Decoder {
+            arena,
+            data,
+            position: 0,
+            shared: Vec::new(),
+            env: None,
+            symbols: None,
+            depth: 0,
+        })
+    }
+
+    pub(crate) fn finish(&self) -> Result<(), &'static str> {
+        if self.position != self.data.len() {
+            return Err("Trailing data in image");
+        }
+
+        Ok(())
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'arena [u8], &'static str> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or("Truncated image")?;
+
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], &'static str> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, &'static str> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, &'static str> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, &'static str> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn len(&mut self) -> Result<usize, &'static str> {
+        usize::try_from(self.u64()?).map_err(|_| "Length out of range")
+    }
+
+    // A symbol name, interned so that it is `eq?` to the same name read
+    // into the restored environment later.
+    fn name(&mut self) -> Result<&'arena str, &'static str> {
+        let name = self.str()?;
+        self.symbols()?
+            .borrow_mut()
+            .intern(name)
+            .map(|symbol| symbol.name())
+            .ok_or("Failed to intern symbol")
+    }
+
+    fn symbols(&mut self) -> Result<&'arena RefCell<SymbolTable<'arena>>, &'static str> {
+        match self.symbols {
+            Some(symbols) => Ok(symbols),
+            None => Ok(*self
+                .symbols
+                .insert(env::symbol_table(self.arena).ok_or("Failed to allocate symbol table")?)),
+        }
+    }
+
+    fn str(&mut self) -> Result<&'arena str, &'static str> {
+        let len = self.len()?;
+        core::str::from_utf8(self.bytes(len)?).map_err(|_| "Invalid UTF-8 in image")
+    }
+
+    // The shared object numbered next in the image, or `None` if this is
+    // its first appearance and its contents follow. The caller then fills in
+    // the slot reserved for it at the end of `shared`.
+    fn shared(&mut self) -> Result<Option<Atom<'arena>>, &'static str> {
+        let id = self.len()?;
+        match self.shared.get(id) {
+            Some(atom) => Ok(Some(*atom)),
+            None if id == self.shared.len() => {
+                self.shared.push(Atom::Void);
+                Ok(None)
+            }
+            None => Err("Invalid reference in image"),
+        }
+    }
+
+    fn pair(&mut self) -> Result<Atom<'arena>, &'static str> {
+        let len = self.len()?;
+        if len > self.data.len() - self.position {
+            return Err("Truncated image");
+        }
+
+        let mut cars = Vec::with_capacity(len);
+        for _ in 0..len {
+            cars.push(self.atom()?);
+        }
+
+        let mut list = self.atom()?;
+        for car in cars.into_iter().rev() {
+            list = pair::cons(self.arena, car, list)?;
+        }
+        Ok(list)
+    }
+
+    fn closure(&mut self) -> Result<Atom<'arena>, &'static str> {
+        if let Some(closure) = self.shared()? {
+            return match closure {
+                Atom::Closure { .. } => Ok(closure),
+                _ => Err("Invalid reference in image"),
+            };
+        }
+
+        let id = self.shared.len() - 1;
+        let len = self.len()?;
+        if len > (self.data.len() - self.position) / 8 {
+            return Err("Truncated image");
+        }
+
+        let mut params = Vec::with_capacity(len);
+        for _ in 0..len {
+            params.push(self.name()?);
+        }
+        let rest = match self.u8()? {
+            0 => None,
+            _ => Some(self.name()?),
+        };
+        let body = self.expressions()?;
+
+        let env = match self.env {
+            Some(env) => env,
+            None => {
+                let symbols = self.symbols()?;
+                *self.env.insert(
+                    Env::with_symbols(self.arena, symbols)
+                        .ok_or("Failed to allocate environment")?,
+                )
+            }
+        };
+        let closure = eval::closure(self.arena, &params, rest, body, env)?;
+        self.shared[id] = closure;
+        Ok(closure)
+    }
+
+    fn big(&mut self) -> Result<BigInt<'arena>, &'static str> {
+        let negative = self.u8()? != 0;
+        let len = self.len()?;
+
+        if len > (self.data.len() - self.position) / 4 {
+            return Err("Truncated image");
+        }
+
+        let arena = self.arena;
+        let limbs = make!(arena, u32, len).ok_or("Failed to allocate limbs")?;
+        for limb in limbs.iter_mut() {
+            *limb = self.u32()?;
+        }
+
+        Ok(BigInt { negative, limbs })
+    }
+
+    fn promise(&mut self) -> Result<Atom<'arena>, &'static str> {
+        if let Some(promise) = self.shared()? {
+            return match promise {
+                Atom::Promise { .. } => Ok(promise),
+                _ => Err("Invalid reference in image"),
+            };
+        }
+
+        let id = self.shared.len() - 1;
+        let atom = lazy::restore(self.arena).ok_or("Failed to allocate promise")?;
+        let Atom::Promise { promise } = atom else {
+            unreachable!();
+        };
+        self.shared[id] = atom;
+
+        let forced = self.u8()? != 0;
+        let inner = self.atom()?;
+        promise.set_state(match forced {
+            false => State::Pending { thunk: inner },
+            true => State::Forced { value: inner },
+        });
+        Ok(atom)
+    }
+
+    fn record_type(&mut self) -> Result<Atom<'arena>, &'static str> {
+        if let Some(kind) = self.shared()? {
+            return match kind {
+                Atom::RecordType { .. } => Ok(kind),
+                _ => Err("Invalid reference in image"),
+            };
+        }
+
+        let id = self.shared.len() - 1;
+        let name = self.str()?;
+        let len = self.len()?;
+
+        if len > (self.data.len() - self.position) / 8 {
+            return Err("Truncated image");
+        }
+
+        let mut fields = Vec::with_capacity(len);
+        for _ in 0..len {
+            fields.push(self.str()?);
+        }
+
+        let kind = record::record_type(self.arena, name, &fields)
+            .ok_or("Failed to allocate record type")?;
+        self.shared[id] = kind;
+        Ok(kind)
+    }
+
+    fn record(&mut self) -> Result<Atom<'arena>, &'static str> {
+        if let Some(record) = self.shared()? {
+            return match record {
+                Atom::Record { .. } => Ok(record),
+                _ => Err("Invalid reference in image"),
+            };
+        }
+
+        let id = self.shared.len() - 1;
+        let kind = self.atom()?;
+        let Atom::RecordType { kind: header } = kind else {
+            return Err("Record without a record type");
+        };
+
+        // Filled in after it is registered, as its fields may refer to it.
+        let values = vec![Atom::Void; header.fields.len()];
+        let record = record::make_record(self.arena, &kind, &values)?;
+        self.shared[id] = record;
+
+        for index in 0..values.len() {
+            let value = self.atom()?;
+            record::record_set(&kind, &record, index, value)?;
+        }
+        Ok(record)
+    }
+
+    pub(crate) fn expressions(&mut self) -> Result<Array<Expression<'arena>>, &'static str> {
+        let len = self.len()?;
+
+        // Every expression takes at least nine bytes, which bounds the
+        // allocation before trusting the length.
+        if len > (self.data.len() - self.position) / 9 {
+            return Err("Truncated image");
+        }
+
+        let arena = self.arena;
+        let mut body = make!(arena, Expression, len)
+            .map(Array::new)
+            .ok_or("Failed to allocate expressions")?;
+
+        for _ in 0..len {
+            let depth = self.len()?;
+            let payload = self.atom()?;
+            body.push(&Expression {
+                depth,
+                payload,
+                span: Span::default(),
+            });
+        }
+
+        Ok(body)
+    }
+
+    fn atom(&mut self) -> Result<Atom<'arena>, &'static str> {
+        if self.depth == MAX_DEPTH {
+            return Err("Image nested too deeply");
+        }
+
+        self.depth += 1;
+        let atom = self.payload();
+        self.depth -= 1;
+        atom
+    }
+
+    fn payload(&mut self) -> Result<Atom<'arena>, &'static str> {
+        let atom = match self.u8()? {
+            tag::TRUE => Atom::True,
+            tag::FALSE => Atom::False,
+            tag::VOID => Atom::Void,
+            tag::NIL => Atom::Nil,
+            tag::INT => Atom::Int {
+                inner: i64::from_le_bytes(self.array()?),
+            },
+            tag::NUMBER => Atom::Number {
+                inner: f64::from_le_bytes(self.array()?),
+            },
+            tag::BIG_INT => Atom::BigInt { inner: self.big()? },
+            tag::RATIONAL => {
+                let numerator = self.big()?;
+                let denominator = self.big()?;
+                let arena = self.arena;
+                let ratio = make!(arena, Ratio).ok_or("Failed to allocate ratio")?;
+                *ratio = Ratio {
+                    numerator,
+                    denominator,
+                };
+                Atom::Rational { inner: ratio }
+            }
+            tag::CHAR => Atom::Char {
+                inner: char::from_u32(self.u32()?).ok_or("Invalid character in image")?,
+            },
+            tag::STRING => Atom::String { inner: self.str()? },
+            tag::BUFFER => {
+                let len = self.len()?;
+                Atom::Buffer {
+                    data: self.bytes(len)?,
+                }
+            }
+            tag::FILE => Atom::File {
+                path: self.str()?,
+                lazy: self.u8()? != 0,
+            },
+            tag::DEFINE => Atom::Define,
+            tag::CONS => Atom::Cons,
+            tag::HEAD => Atom::Head,
+            tag::TAIL => Atom::Tail,
+            tag::SYMBOL => Atom::Symbol { name: self.name()? },
+            tag::QUOTED => Atom::Quoted { name: self.name()? },
+            tag::LIST => Atom::List {
+                body: self.expressions()?,
+            },
+            tag::CODE => Atom::Code {
+                body: self.expressions()?,
+            },
+            tag::PRIMITIVE => Atom::Primitive { name: self.str()? },
+            tag::PAIR => self.pair()?,
+            tag::CLOSURE => self.closure()?,
+            tag::VECTOR => Atom::Vector {
+                body: self.expressions()?,
+            },
+            tag::MAP => {
+                let body = self.expressions()?;
+                Atom::Map {
+                    map: Map::build(self.arena, &body[..body.len()])
+                        .map_err(|_| "Malformed map")?,
+                }
+            }
+            tag::PROMISE => self.promise()?,
+            tag::RECORD_TYPE => self.record_type()?,
+            tag::RECORD => self.record()?,
+            tag::ADD => Atom::Add,
+            tag::SUBTRACT => Atom::Subtract,
+            tag::MULTIPLY => Atom::Multiply,
+            tag::DIVIDE => Atom::Divide,
+            tag::EQ => Atom::Eq,
+            tag::NEQ => Atom::Neq,
+            tag::LT => Atom::LT,
+            tag::GT => Atom::GT,
+            tag::LTE => Atom::LTE,
+            tag::GTE => Atom::GTE,
+            tag::ARROW_LEFT => Atom::ArrowLeft,
+            tag::ARROW_RIGHT => Atom::ArrowRight,
+            tag::NEGATE => Atom::Negate,
+            tag::EXP => Atom::Exp,
+            tag::MOD => Atom::Mod,
+            tag::REMAINDER => Atom::Remainder,
+            _ => return Err("Unknown atom in image"),
+        };
+
+        Ok(atom)
+    }
+}
@@ -0,0 +1,92 @@
+use crate::read::Atom;
+use crate::{Arena, make};
+use core::cell::Cell;
+
+pub const STREAMS: &str = include_str!("streams.tyson");
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State<'arena> {
+    Pending { thunk: Atom<'arena> },
+    Forced { value: Atom<'arena> },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Promise<'arena> {
+    state: Cell<State<'arena>>,
+}
+
+impl<'arena> Promise<'arena> {
+    pub fn state(&self) -> State<'arena> {
+        self.state.get()
+    }
+
+    pub fn is_forced(&self) -> bool {
+        matches!(self.state(), State::Forced { .. })
+    }
+
+    /// Returns the memoised value, running `eval` on the thunk the first time.
+    /// If the thunk forces this promise again, the first value to be stored
+    /// wins, as in R7RS.
+    pub fn force<E>(
+        &self,
+        eval: impl FnOnce(Atom<'arena>) -> Result<Atom<'arena>, E>,
+    ) -> Result<Atom<'arena>, E> {
+        let thunk = match self.state() {
+            State::Forced { value } => return Ok(value),
+            State::Pending { thunk } => thunk,
+        };
+
+        let value = eval(thunk)?;
+        if let State::Forced { value } = self.state() {
+            return Ok(value);
+        }
+
+        self.state.set(State::Forced { value });
+        Ok(value)
+    }
+
+    pub(crate) fn set_state(&self, state: State<'arena>) {
+        self.state.set(state);
+    }
+}
+
+fn promise<'arena>(arena: &'arena Arena<'arena>, state: State<'arena>) -> Option<Atom<'arena>> {
+    make!(arena, Promise).map(|promise| {
+        *promise = Promise {
+            state: Cell::new(state),
+        };
+
+        Atom::Promise { promise }
+    })
+}
+
+pub fn delay<'arena>(arena: &'arena Arena<'arena>, thunk: Atom<'arena>) -> Option<Atom<'arena>> {
+    promise(arena, State::Pending { thunk })
+}
+
+pub fn make_promise<'arena>(
+    arena: &'arena Arena<'arena>,
+    value: Atom<'arena>,
+) -> Option<Atom<'arena>> {
+    match value {
+        Atom::Promise { .. } => Some(value),
+        _ => promise(arena, State::Forced { value }),
+    }
+}
+
+// A promise for an image to fill in once the atom it holds is decoded,
+// since that atom may refer back to the promise.
+pub(crate) fn restore<'arena>(arena: &'arena Arena<'arena>) -> Option<Atom<'arena>> {
+    promise(arena, State::Pending { thunk: Atom::Void })
+}
+
+// Forcing anything that is not a promise yields the value itself.
+pub fn force<'arena, E>(
+    value: Atom<'arena>,
+    eval: impl FnOnce(Atom<'arena>) -> Result<Atom<'arena>, E>,
+) -> Result<Atom<'arena>, E> {
+    match value {
+        Atom::Promise { promise } => promise.force(eval),
+        _ => Ok(value),
+    }
+}
@@ -5,10 +5,26 @@
 mod alloc;
 mod collections;
 
+pub mod check;
+pub mod chars;
+pub mod cst;
 pub mod env;
+pub mod expand;
+pub mod image;
+pub mod lazy;
+pub mod map;
+pub mod numeric;
+pub mod prelude;
 pub mod read;
+pub mod record;
+pub mod sandbox;
+pub mod symbol;
 pub mod eval;
+pub mod pair;
+pub mod primitive;
 pub mod print;
+pub mod thread;
+pub mod types;
 
 pub use alloc::*;
 pub use collections::*;
@@ -40,9 +40,7 @@ fn main() {
     let arena = block.arena(megabytes(16)).unwrap();
     let mut expressions = List::new(&arena);
 
-    for source in &[CODE] {
-        expressions.push_back(&parse(&arena, source).expect("Unable to parse code!"));
-    }
+    expressions.push_back(&parse(&arena, CODE).expect("Unable to parse code!"));
 
     for (expression, code) in expressions.iter().zip(&[CODE]) {
         let mut string = String::with_capacity(4096);
@@ -59,9 +57,7 @@ fn main() {
         }
     }
 
-    for source in &[CODE] {
-        expressions.push_back(&parse(&arena, source).expect("Unable to parse code!"));
-    }
+    expressions.push_back(&parse(&arena, CODE).expect("Unable to parse code!"));
 
     println!("{:#?}", arena);
 }
@@ -0,0 +1,162 @@
+use crate::read::{Atom, Expression, Span};
+use crate::{Arena, Table, make};
+
+/// Why the forms of a `{k v ...}` literal do not make a map. Indices point
+/// at the offending key within the forms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapError {
+    OddLength,
+    InvalidKey(usize),
+    DuplicateKey(usize),
+    OutOfMemory,
+}
+
+/// A map key. Only atoms that compare by value can be keys, so a lookup
+/// never depends on where either key was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MapKey<'arena> {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    Char(char),
+    String(&'arena str),
+    Symbol(&'arena str),
+}
+
+/// The map read from a `{k v ...}` literal. Keys are taken as written and
+/// never evaluated. Entries keep the order they were read in, so a map
+/// prints back as it was written.
+#[derive(Debug, Clone, Copy)]
+pub struct Map<'arena> {
+    entries: Table<MapKey<'arena>, Expression<'arena>>,
+}
+
+impl<'arena> MapKey<'arena> {
+    pub fn new(atom: Atom<'arena>) -> Option<Self> {
+        let key = match atom {
+            Atom::Nil => MapKey::Nil,
+            Atom::True => MapKey::Bool(true),
+            Atom::False => MapKey::Bool(false),
+            Atom::Int { inner } => MapKey::Int(inner),
+            Atom::Char { inner } => MapKey::Char(inner),
+            Atom::String { inner } => MapKey::String(inner),
+            Atom::Symbol { name } => MapKey::Symbol(name),
+            _ => return None,
+        };
+        Some(key)
+    }
+
+    pub fn atom(self) -> Atom<'arena> {
+        match self {
+            MapKey::Nil => Atom::Nil,
+            MapKey::Bool(true) => Atom::True,
+            MapKey::Bool(false) => Atom::False,
+            MapKey::Int(inner) => Atom::Int { inner },
+            MapKey::Char(inner) => Atom::Char { inner },
+            MapKey::String(inner) => Atom::String { inner },
+            MapKey::Symbol(name) => Atom::Symbol { name },
+        }
+    }
+}
+
+impl<'arena> Map<'arena> {
+    /// Builds the map whose keys and values alternate in `body`.
+    pub fn build(
+        arena: &'arena Arena<'arena>,
+        body: &[Expression<'arena>],
+    ) -> Result<&'arena Map<'arena>, MapError> {
+        if !body.len().is_multiple_of(2) {
+            return Err(MapError::OddLength);
+        }
+
+        let mut entries = Table::try_new(arena, body.len() / 2).ok_or(MapError::OutOfMemory)?;
+        for (index, pair) in body.chunks(2).enumerate() {
+            let index = index * 2;
+            let key = MapKey::new(pair[0].payload).ok_or(MapError::InvalidKey(index))?;
+            if entries.contains(&key) {
+                return Err(MapError::DuplicateKey(index));
+            }
+            entries.insert(&key, &pair[1]);
+        }
+
+        Self::alloc(arena, entries)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The value bound to `key`, or `None` if it is missing or `key` could
+    /// not be a key at all.
+    pub fn get(&self, key: Atom<'arena>) -> Option<&Expression<'arena>> {
+        self.entries.get(&MapKey::new(key)?)
+    }
+
+    pub fn contains(&self, key: Atom<'arena>) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Entries in the order they were read.
+    pub fn iter(&self) -> impl Iterator<Item = (Atom<'arena>, &Expression<'arena>)> {
+        self.entries.iter().map(|(key, value)| (key.atom(), value))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Expression<'arena>> {
+        self.entries.values()
+    }
+
+    /// Keys and values interleaved as in the literal. Keys sit at the depth
+    /// of their value and carry no span.
+    pub fn body(&self) -> Vec<Expression<'arena>> {
+        self.iter()
+            .flat_map(|(key, value)| {
+                let key = Expression {
+                    depth: value.depth,
+                    payload: key,
+                    span: Span::default(),
+                };
+                [key, *value]
+            })
+            .collect()
+    }
+
+    /// A map with the same keys and `values` in place of the old ones, as
+    /// the expander builds once it has rewritten each value.
+    pub(crate) fn with_values(
+        &self,
+        arena: &'arena Arena<'arena>,
+        values: &[Expression<'arena>],
+    ) -> Result<&'arena Map<'arena>, &'static str> {
+        let mut entries = Table::try_new(arena, self.len()).ok_or("Failed to allocate map")?;
+        for (key, value) in self.entries.keys().zip(values) {
+            entries.insert(key, value);
+        }
+
+        Self::alloc(arena, entries).map_err(|_| "Failed to allocate map")
+    }
+
+    fn alloc(
+        arena: &'arena Arena<'arena>,
+        entries: Table<MapKey<'arena>, Expression<'arena>>,
+    ) -> Result<&'arena Map<'arena>, MapError> {
+        let map = make!(arena, Map).ok_or(MapError::OutOfMemory)?;
+        *map = Map { entries };
+        Ok(map)
+    }
+}
+
+// Maps are equal when they bind the same keys to the same values, whatever
+// order the entries were written in.
+impl PartialEq for Map<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .entries
+                .iter()
+                .all(|(key, value)| other.entries.get(key) == Some(value))
+    }
+}
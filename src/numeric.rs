@@ -0,0 +1,688 @@
+use crate::read::Atom;
+use crate::{Arena, make};
+use core::cmp::Ordering;
+use core::fmt::{Display, Formatter};
+
+type NumericResult<'arena> = Result<Atom<'arena>, &'static str>;
+
+// Magnitudes are little-endian base 2^32 limbs without trailing zeros.
+// Integers that fit in an i64 are always stored as `Atom::Int`, so an
+// `Atom::BigInt` never holds a value that has a smaller representation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BigInt<'arena> {
+    pub negative: bool,
+    pub limbs: &'arena [u32],
+}
+
+// Always in lowest terms with a denominator greater than one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ratio<'arena> {
+    pub numerator: BigInt<'arena>,
+    pub denominator: BigInt<'arena>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Integer {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+enum Number {
+    Exact(Integer, Integer),
+    Inexact(f64),
+}
+
+pub fn parse_integer<'arena>(arena: &'arena Arena<'arena>, text: &str) -> Option<Atom<'arena>> {
+    parse_integer_radix(arena, text, 10)
+}
+
+/// Parses an integer written in base `radix`, from 2 to 36.
+pub fn parse_integer_radix<'arena>(
+    arena: &'arena Arena<'arena>,
+    text: &str,
+    radix: u32,
+) -> Option<Atom<'arena>> {
+    if let Ok(inner) = i64::from_str_radix(text, radix) {
+        return Some(Atom::Int { inner });
+    }
+
+    integer_atom(arena, Integer::parse(text, radix)?)
+}
+
+pub fn parse_rational<'arena>(arena: &'arena Arena<'arena>, text: &str) -> Option<Atom<'arena>> {
+    parse_rational_radix(arena, text, 10)
+}
+
+pub fn parse_rational_radix<'arena>(
+    arena: &'arena Arena<'arena>,
+    text: &str,
+    radix: u32,
+) -> Option<Atom<'arena>> {
+    let (numerator, denominator) = text.split_once('/')?;
+    let numerator = Integer::parse(numerator, radix)?;
+    let denominator = Integer::parse(denominator, radix)?;
+
+    if denominator.negative {
+        return None;
+    }
+
+    rational_atom(arena, numerator, denominator).ok()
+}
+
+pub fn is_number(atom: &Atom) -> bool {
+    matches!(
+        atom,
+        Atom::Int { .. } | Atom::BigInt { .. } | Atom::Rational { .. } | Atom::Number { .. }
+    )
+}
+
+pub fn is_exact(atom: &Atom) -> bool {
+    matches!(
+        atom,
+        Atom::Int { .. } | Atom::BigInt { .. } | Atom::Rational { .. }
+    )
+}
+
+pub fn add<'arena>(arena: &'arena Arena<'arena>, a: &Atom, b: &Atom) -> NumericResult<'arena> {
+    if let (Atom::Int { inner: x }, Atom::Int { inner: y }) = (a, b)
+        && let Some(inner) = x.checked_add(*y)
+    {
+        return Ok(Atom::Int { inner });
+    }
+
+    match (Number::from_atom(a)?, Number::from_atom(b)?) {
+        (Number::Exact(an, ad), Number::Exact(bn, bd)) => {
+            let numerator = an.mul(&bd).add(&bn.mul(&ad));
+            rational_atom(arena, numerator, ad.mul(&bd))
+        }
+        (x, y) => Ok(Atom::Number {
+            inner: x.to_f64() + y.to_f64(),
+        }),
+    }
+}
+
+pub fn sub<'arena>(arena: &'arena Arena<'arena>, a: &Atom, b: &Atom) -> NumericResult<'arena> {
+    if let (Atom::Int { inner: x }, Atom::Int { inner: y }) = (a, b)
+        && let Some(inner) = x.checked_sub(*y)
+    {
+        return Ok(Atom::Int { inner });
+    }
+
+    match (Number::from_atom(a)?, Number::from_atom(b)?) {
+        (Number::Exact(an, ad), Number::Exact(bn, bd)) => {
+            let numerator = an.mul(&bd).add(&bn.mul(&ad).negate());
+            rational_atom(arena, numerator, ad.mul(&bd))
+        }
+        (x, y) => Ok(Atom::Number {
+            inner: x.to_f64() - y.to_f64(),
+        }),
+    }
+}
+
+pub fn mul<'arena>(arena: &'arena Arena<'arena>, a: &Atom, b: &Atom) -> NumericResult<'arena> {
+    if let (Atom::Int { inner: x }, Atom::Int { inner: y }) = (a, b)
+        && let Some(inner) = x.checked_mul(*y)
+    {
+        return Ok(Atom::Int { inner });
+    }
+
+    match (Number::from_atom(a)?, Number::from_atom(b)?) {
+        (Number::Exact(an, ad), Number::Exact(bn, bd)) => {
+            rational_atom(arena, an.mul(&bn), ad.mul(&bd))
+        }
+        (x, y) => Ok(Atom::Number {
+            inner: x.to_f64() * y.to_f64(),
+        }),
+    }
+}
+
+// Dividing exact numbers stays exact, so (/ 1 3) is the rational 1/3.
+pub fn div<'arena>(arena: &'arena Arena<'arena>, a: &Atom, b: &Atom) -> NumericResult<'arena> {
+    match (Number::from_atom(a)?, Number::from_atom(b)?) {
+        (Number::Exact(an, ad), Number::Exact(bn, bd)) => {
+            rational_atom(arena, an.mul(&bd), ad.mul(&bn))
+        }
+        (x, y) => Ok(Atom::Number {
+            inner: x.to_f64() / y.to_f64(),
+        }),
+    }
+}
+
+/// Orders two numbers, exactly unless either of them is inexact.
+pub fn compare_numbers(a: &Atom, b: &Atom) -> Result<Ordering, &'static str> {
+    if let (Atom::Int { inner: x }, Atom::Int { inner: y }) = (a, b) {
+        return Ok(x.cmp(y));
+    }
+
+    match (Number::from_atom(a)?, Number::from_atom(b)?) {
+        (Number::Exact(an, ad), Number::Exact(bn, bd)) => Ok(an.mul(&bd).order(&bn.mul(&ad))),
+        (x, y) => x
+            .to_f64()
+            .partial_cmp(&y.to_f64())
+            .ok_or("Cannot order NaN"),
+    }
+}
+
+// Remainders are taken on fixnums and floats only, which covers every
+// integer that fits in a machine word.
+pub fn remainder<'arena>(a: &Atom, b: &Atom) -> NumericResult<'arena> {
+    match (a, b) {
+        (Atom::Int { .. }, Atom::Int { inner: 0 }) => Err("Division by zero"),
+        (Atom::Int { inner: x }, Atom::Int { inner: y }) => Ok(Atom::Int {
+            inner: x.wrapping_rem(*y),
+        }),
+        _ => Ok(Atom::Number {
+            inner: Number::from_atom(a)?.to_f64() % Number::from_atom(b)?.to_f64(),
+        }),
+    }
+}
+
+/// Like `remainder`, but the result takes the sign of the divisor.
+pub fn modulo<'arena>(a: &Atom, b: &Atom) -> NumericResult<'arena> {
+    match (remainder(a, b)?, b) {
+        (Atom::Int { inner }, Atom::Int { inner: divisor })
+            if inner != 0 && (inner < 0) != (*divisor < 0) =>
+        {
+            Ok(Atom::Int {
+                inner: inner + divisor,
+            })
+        }
+        (Atom::Number { inner }, _) => {
+            let divisor = Number::from_atom(b)?.to_f64();
+            match inner != 0.0 && (inner < 0.0) != (divisor < 0.0) {
+                true => Ok(Atom::Number {
+                    inner: inner + divisor,
+                }),
+                false => Ok(Atom::Number { inner }),
+            }
+        }
+        (atom, _) => Ok(atom),
+    }
+}
+
+// Exact bases raised to integer powers stay exact, so (^ 2 100) is a big
+// integer and (^ 2 -1) is 1/2.
+pub fn expt<'arena>(
+    arena: &'arena Arena<'arena>,
+    a: &Atom<'arena>,
+    b: &Atom,
+) -> NumericResult<'arena> {
+    let Atom::Int { inner: exponent } = *b else {
+        return Ok(Atom::Number {
+            inner: Number::from_atom(a)?
+                .to_f64()
+                .powf(Number::from_atom(b)?.to_f64()),
+        });
+    };
+    if !is_exact(a) {
+        return Ok(Atom::Number {
+            inner: Number::from_atom(a)?.to_f64().powi(exponent as i32),
+        });
+    }
+
+    let mut result = Atom::Int { inner: 1 };
+    let mut base = *a;
+    let mut power = exponent.unsigned_abs();
+    while power > 0 {
+        if power & 1 == 1 {
+            result = mul(arena, &result, &base)?;
+        }
+        power >>= 1;
+        if power > 0 {
+            base = mul(arena, &base, &base)?;
+        }
+    }
+
+    match exponent < 0 {
+        true => div(arena, &Atom::Int { inner: 1 }, &result),
+        false => Ok(result),
+    }
+}
+
+pub fn exact<'arena>(arena: &'arena Arena<'arena>, a: &Atom<'arena>) -> NumericResult<'arena> {
+    match Number::from_atom(a)? {
+        Number::Exact(..) => Ok(*a),
+        Number::Inexact(value) => {
+            let (numerator, denominator) = Integer::from_f64(value)?;
+            rational_atom(arena, numerator, denominator)
+        }
+    }
+}
+
+pub fn inexact<'arena>(a: &Atom) -> NumericResult<'arena> {
+    Number::from_atom(a).map(|n| Atom::Number { inner: n.to_f64() })
+}
+
+pub fn numerator<'arena>(arena: &'arena Arena<'arena>, a: &Atom<'arena>) -> NumericResult<'arena> {
+    match a {
+        Atom::Int { .. } | Atom::BigInt { .. } => Ok(*a),
+        Atom::Rational { inner } => {
+            integer_atom(arena, Integer::from_big(&inner.numerator)).ok_or("Out of memory")
+        }
+        _ => numerator(arena, &exact(arena, a)?).and_then(|n| inexact(&n)),
+    }
+}
+
+pub fn denominator<'arena>(
+    arena: &'arena Arena<'arena>,
+    a: &Atom<'arena>,
+) -> NumericResult<'arena> {
+    match a {
+        Atom::Int { .. } | Atom::BigInt { .. } => Ok(Atom::Int { inner: 1 }),
+        Atom::Rational { inner } => {
+            integer_atom(arena, Integer::from_big(&inner.denominator)).ok_or("Out of memory")
+        }
+        _ => denominator(arena, &exact(arena, a)?).and_then(|n| inexact(&n)),
+    }
+}
+
+fn integer_atom<'arena>(arena: &'arena Arena<'arena>, value: Integer) -> Option<Atom<'arena>> {
+    match value.to_i64() {
+        Some(inner) => Some(Atom::Int { inner }),
+        None => big(arena, &value).map(|inner| Atom::BigInt { inner }),
+    }
+}
+
+fn rational_atom<'arena>(
+    arena: &'arena Arena<'arena>,
+    numerator: Integer,
+    denominator: Integer,
+) -> NumericResult<'arena> {
+    if denominator.is_zero() {
+        return Err("Division by zero");
+    }
+
+    let divisor = gcd(&numerator.magnitude, &denominator.magnitude);
+    let negative = numerator.negative != denominator.negative && !numerator.is_zero();
+    let numerator = Integer::new(negative, divmod(&numerator.magnitude, &divisor).0);
+    let denominator = Integer::new(false, divmod(&denominator.magnitude, &divisor).0);
+
+    if denominator.magnitude == [1] {
+        return integer_atom(arena, numerator).ok_or("Out of memory");
+    }
+
+    let numerator = big(arena, &numerator).ok_or("Out of memory")?;
+    let denominator = big(arena, &denominator).ok_or("Out of memory")?;
+
+    make!(arena, Ratio)
+        .map(|ratio| {
+            *ratio = Ratio {
+                numerator,
+                denominator,
+            };
+            Atom::Rational { inner: ratio }
+        })
+        .ok_or("Out of memory")
+}
+
+fn big<'arena>(arena: &'arena Arena<'arena>, value: &Integer) -> Option<BigInt<'arena>> {
+    let limbs = make!(arena, u32, value.magnitude.len())?;
+    limbs.copy_from_slice(&value.magnitude);
+
+    Some(BigInt {
+        negative: value.negative,
+        limbs,
+    })
+}
+
+impl Number {
+    fn from_atom(atom: &Atom) -> Result<Self, &'static str> {
+        let one = || Integer::from_i64(1);
+
+        match atom {
+            Atom::Int { inner } => Ok(Number::Exact(Integer::from_i64(*inner), one())),
+            Atom::BigInt { inner } => Ok(Number::Exact(Integer::from_big(inner), one())),
+            Atom::Rational { inner } => Ok(Number::Exact(
+                Integer::from_big(&inner.numerator),
+                Integer::from_big(&inner.denominator),
+            )),
+            Atom::Number { inner } => Ok(Number::Inexact(*inner)),
+            _ => Err("Not a number"),
+        }
+    }
+
+    fn to_f64(&self) -> f64 {
+        match self {
+            Number::Inexact(value) => *value,
+            Number::Exact(numerator, denominator) => numerator.to_f64() / denominator.to_f64(),
+        }
+    }
+}
+
+impl Integer {
+    fn new(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        normalize(&mut magnitude);
+        let negative = negative && !magnitude.is_empty();
+        Integer {
+            negative,
+            magnitude,
+        }
+    }
+
+    fn from_i64(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        Integer::new(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+
+    fn from_big(value: &BigInt) -> Self {
+        Integer::new(value.negative, value.limbs.to_vec())
+    }
+
+    // Splits a finite float into an exact numerator and power-of-two
+    // denominator.
+    fn from_f64(value: f64) -> Result<(Self, Self), &'static str> {
+        if !value.is_finite() {
+            return Err("Cannot convert a non-finite number to an exact number");
+        }
+
+        let bits = value.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        let mantissa = match exponent {
+            0 => (bits & 0xfffffffffffff) << 1,
+            _ => (bits & 0xfffffffffffff) | (1 << 52),
+        };
+        let exponent = exponent - 1075;
+        let numerator = Integer::new(
+            value.is_sign_negative(),
+            vec![mantissa as u32, (mantissa >> 32) as u32],
+        );
+
+        if exponent >= 0 {
+            let shifted = shl(&numerator.magnitude, exponent as usize);
+            return Ok((
+                Integer::new(numerator.negative, shifted),
+                Integer::from_i64(1),
+            ));
+        }
+
+        let denominator = shl(&[1], (-exponent) as usize);
+        Ok((numerator, Integer::new(false, denominator)))
+    }
+
+    fn parse(text: &str, radix: u32) -> Option<Self> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+
+        let mut magnitude = Vec::new();
+        if radix != 10 {
+            for c in digits.chars() {
+                magnitude = mul_small(&magnitude, radix, c.to_digit(radix)?);
+            }
+            return Some(Integer::new(negative, magnitude));
+        }
+
+        for chunk in digits.as_bytes().chunks(9) {
+            let value: u32 = core::str::from_utf8(chunk).ok()?.parse().ok()?;
+            let scale = 10u32.pow(chunk.len() as u32);
+            magnitude = mul_small(&magnitude, scale, value);
+        }
+
+        Some(Integer::new(negative, magnitude))
+    }
+
+    fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+
+        let low = self.magnitude.first().copied().unwrap_or(0) as u64;
+        let high = self.magnitude.get(1).copied().unwrap_or(0) as u64;
+        let magnitude = (high << 32) | low;
+
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &limb| acc * 4294967296.0 + limb as f64);
+
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    fn negate(self) -> Self {
+        Integer::new(!self.negative, self.magnitude)
+    }
+
+    fn add(&self, other: &Integer) -> Integer {
+        if self.negative == other.negative {
+            return Integer::new(
+                self.negative,
+                add_magnitudes(&self.magnitude, &other.magnitude),
+            );
+        }
+
+        match compare(&self.magnitude, &other.magnitude) {
+            Ordering::Less => Integer::new(
+                other.negative,
+                sub_magnitudes(&other.magnitude, &self.magnitude),
+            ),
+            _ => Integer::new(
+                self.negative,
+                sub_magnitudes(&self.magnitude, &other.magnitude),
+            ),
+        }
+    }
+
+    fn order(&self, other: &Integer) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare(&self.magnitude, &other.magnitude),
+            (true, true) => compare(&other.magnitude, &self.magnitude),
+        }
+    }
+
+    fn mul(&self, other: &Integer) -> Integer {
+        Integer::new(
+            self.negative != other.negative,
+            mul_magnitudes(&self.magnitude, &other.magnitude),
+        )
+    }
+}
+
+fn normalize(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+fn compare(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+
+    result.push(carry as u32);
+    normalize(&mut result);
+    result
+}
+
+// Requires a >= b.
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+
+    for (i, &limb) in a.iter().enumerate() {
+        let mut difference = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if difference < 0 {
+            difference += 1 << 32;
+            borrow = 1;
+        }
+        result.push(difference as u32);
+    }
+
+    normalize(&mut result);
+    result
+}
+
+fn mul_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = vec![0u32; a.len() + b.len()];
+
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let product = x as u64 * y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+
+    normalize(&mut result);
+    result
+}
+
+// Computes a * scale + addend.
+fn mul_small(a: &[u32], scale: u32, addend: u32) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry = addend as u64;
+
+    for &limb in a {
+        let product = limb as u64 * scale as u64 + carry;
+        result.push(product as u32);
+        carry = product >> 32;
+    }
+
+    result.push(carry as u32);
+    normalize(&mut result);
+    result
+}
+
+fn divmod_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+
+    for i in (0..a.len()).rev() {
+        let current = (remainder << 32) | a[i] as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+
+    normalize(&mut quotient);
+    (quotient, remainder as u32)
+}
+
+fn shl(a: &[u32], bits: usize) -> Vec<u32> {
+    let (limbs, bits) = (bits / 32, bits % 32);
+    let mut result = vec![0u32; limbs];
+    let mut carry = 0u32;
+
+    for &limb in a {
+        result.push((limb << bits) | carry);
+        carry = if bits == 0 { 0 } else { limb >> (32 - bits) };
+    }
+
+    result.push(carry);
+    normalize(&mut result);
+    result
+}
+
+// Binary long division; `b` must be non-zero.
+fn divmod(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = b {
+        let (quotient, remainder) = divmod_small(a, *divisor);
+        let mut remainder = vec![remainder];
+        normalize(&mut remainder);
+        return (quotient, remainder);
+    }
+
+    if compare(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = Vec::new();
+
+    for bit in (0..a.len() * 32).rev() {
+        remainder = shl(&remainder, 1);
+        if a[bit / 32] >> (bit % 32) & 1 == 1 {
+            remainder = add_magnitudes(&remainder, &[1]);
+        }
+
+        if compare(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitudes(&remainder, b);
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+
+    normalize(&mut quotient);
+    (quotient, remainder)
+}
+
+fn gcd(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (mut a, mut b) = (a.to_vec(), b.to_vec());
+
+    while !b.is_empty() {
+        let remainder = divmod(&a, &b).1;
+        a = b;
+        b = remainder;
+    }
+
+    a
+}
+
+impl Display for BigInt<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        const CHUNK: u32 = 1_000_000_000;
+
+        if self.limbs.is_empty() {
+            return write!(f, "0");
+        }
+
+        let mut chunks = Vec::new();
+        let mut magnitude = self.limbs.to_vec();
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = divmod_small(&magnitude, CHUNK);
+            chunks.push(remainder);
+            magnitude = quotient;
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{first}")?;
+        }
+        for chunk in chunks {
+            write!(f, "{chunk:09}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Ratio<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
@@ -0,0 +1,109 @@
+use crate::read::{Atom, Expression, Span};
+use crate::{Arena, Array, make};
+
+type PairResult<'arena> = Result<Atom<'arena>, &'static str>;
+
+// Lists come in two shapes. Code read as data and lists built by `list`
+// are arrays, whose tails are views into the same buffer; `cons` makes
+// pairs, whose tail may be anything at all, as streams need. Every list
+// procedure accepts both, and `()` ends either one.
+#[derive(Debug, PartialEq)]
+pub struct Pair<'arena> {
+    pub car: Atom<'arena>,
+    pub cdr: Atom<'arena>,
+}
+
+/// The elements of a list, whatever its shape. Once it is exhausted,
+/// `rest` is what was left: `()` for a proper list.
+#[derive(Debug, Clone, Copy)]
+pub struct Items<'arena> {
+    rest: Atom<'arena>,
+}
+
+pub fn cons<'arena>(
+    arena: &'arena Arena<'arena>,
+    car: Atom<'arena>,
+    cdr: Atom<'arena>,
+) -> PairResult<'arena> {
+    make!(arena, Pair)
+        .map(|pair| {
+            *pair = Pair { car, cdr };
+            Atom::Pair { pair }
+        })
+        .ok_or("Failed to allocate pair")
+}
+
+pub fn car<'arena>(list: &Atom<'arena>) -> PairResult<'arena> {
+    match *list {
+        Atom::Pair { pair } => Ok(pair.car),
+        Atom::List { body } | Atom::Code { body } if !body.is_empty() => Ok(body[0].payload),
+        _ => Err("Not a pair"),
+    }
+}
+
+pub fn cdr<'arena>(list: &Atom<'arena>) -> PairResult<'arena> {
+    match *list {
+        Atom::Pair { pair } => Ok(pair.cdr),
+        Atom::List { body } | Atom::Code { body } if body.len() == 1 => Ok(Atom::Void),
+        Atom::List { body } | Atom::Code { body } if !body.is_empty() => {
+            Ok(Atom::List { body: body.tail(1) })
+        }
+        _ => Err("Not a pair"),
+    }
+}
+
+pub fn is_pair(atom: &Atom) -> bool {
+    match atom {
+        Atom::Pair { .. } => true,
+        Atom::List { body } | Atom::Code { body } => !body.is_empty(),
+        _ => false,
+    }
+}
+
+pub fn is_null(atom: &Atom) -> bool {
+    match atom {
+        Atom::Void => true,
+        Atom::List { body } | Atom::Code { body } => body.is_empty(),
+        _ => false,
+    }
+}
+
+/// A list of `items` as one array.
+pub fn list<'arena>(arena: &'arena Arena<'arena>, items: &[Atom<'arena>]) -> PairResult<'arena> {
+    if items.is_empty() {
+        return Ok(Atom::Void);
+    }
+
+    let mut body = make!(arena, Expression, items.len())
+        .map(Array::new)
+        .ok_or("Failed to allocate list")?;
+    for payload in items {
+        body.push(&Expression {
+            depth: 1,
+            payload: *payload,
+            span: Span::default(),
+        });
+    }
+
+    Ok(Atom::List { body })
+}
+
+pub fn items<'arena>(list: Atom<'arena>) -> Items<'arena> {
+    Items { rest: list }
+}
+
+impl<'arena> Items<'arena> {
+    pub fn rest(&self) -> Atom<'arena> {
+        self.rest
+    }
+}
+
+impl<'arena> Iterator for Items<'arena> {
+    type Item = Atom<'arena>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = car(&self.rest).ok()?;
+        self.rest = cdr(&self.rest).ok()?;
+        Some(item)
+    }
+}
@@ -0,0 +1,26 @@
+use crate::Arena;
+use crate::env::Env;
+use crate::eval::{GENERATORS, Interpreter};
+use crate::lazy::STREAMS;
+use crate::sandbox::Preset;
+
+pub const PRELUDE: &str = include_str!("prelude.tyson");
+
+/// Evaluates the prelude into a fresh root environment that binds every
+/// primitive. User environments extend the root with `Env::extend`, so the
+/// prelude is only evaluated once per arena.
+pub fn root<'arena>(arena: &'arena Arena<'arena>) -> Result<Env<'arena>, &'static str> {
+    let env = Preset::Full.env(arena)?;
+    load(arena, env)?;
+    Ok(env)
+}
+
+/// Evaluates the prelude, the generator procedures and the stream library
+/// in `env`, which must bind the primitives they call, as the environment
+/// of a sandbox preset does.
+pub fn load<'arena>(arena: &'arena Arena<'arena>, env: Env<'arena>) -> Result<(), &'static str> {
+    let mut interpreter = Interpreter::new(arena, env);
+    interpreter.run(PRELUDE)?;
+    interpreter.run(GENERATORS)?;
+    interpreter.run(STREAMS).map(|_| ())
+}
@@ -0,0 +1,448 @@
+use crate::chars;
+use crate::env::Env;
+use crate::eval;
+use crate::lazy;
+use crate::numeric::{self, is_number};
+use crate::pair;
+use crate::print::print_value;
+use crate::read::Atom;
+use crate::record;
+use crate::sandbox;
+use crate::symbol;
+use crate::thread::Task;
+use crate::{Arena, make};
+use core::cmp::Ordering;
+use std::io::{BufRead, Write};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+type PrimitiveResult<'arena> = Result<Atom<'arena>, &'static str>;
+
+/// The name a procedure is called by if it is built in: a primitive bound
+/// by a sandbox preset, or an operator the reader made an atom of.
+pub fn name<'arena>(atom: &Atom<'arena>) -> Option<&'arena str> {
+    let name = match *atom {
+        Atom::Primitive { name } => name,
+        Atom::Cons => "cons",
+        Atom::Head => "car",
+        Atom::Tail => "cdr",
+        Atom::Add => "+",
+        Atom::Subtract => "-",
+        Atom::Multiply => "*",
+        Atom::Divide => "/",
+        Atom::Eq => "=",
+        Atom::Neq => "!=",
+        Atom::LT => "<",
+        Atom::GT => ">",
+        Atom::LTE => "<=",
+        Atom::GTE => ">=",
+        Atom::Negate => "!",
+        Atom::Exp => "^",
+        Atom::Mod => "%",
+        Atom::Remainder => "//",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn boolean<'arena>(value: bool) -> Atom<'arena> {
+    if value { Atom::True } else { Atom::False }
+}
+
+// Operators are not in the sandbox table, as every preset has them.
+fn check_arity(name: &str, count: usize) -> Result<(), &'static str> {
+    let accepts = match name {
+        "+" | "*" => true,
+        "-" | "/" | "=" | "<" | ">" | "<=" | ">=" => count >= 1,
+        "!" => count == 1,
+        "cons" | "!=" | "^" | "%" | "//" => count == 2,
+        _ => sandbox::arity(name)
+            .ok_or("Unknown primitive")?
+            .accepts(count),
+    };
+
+    match accepts {
+        true => Ok(()),
+        false => Err("Wrong number of arguments"),
+    }
+}
+
+/// Calls the primitive `name` on `args`.
+pub(crate) fn call<'arena>(
+    arena: &'arena Arena<'arena>,
+    env: &Env<'arena>,
+    name: &str,
+    args: &[Atom<'arena>],
+) -> PrimitiveResult<'arena> {
+    check_arity(name, args.len())?;
+
+    match (name, args) {
+        // Numbers
+        ("+", _) => fold(arena, Atom::Int { inner: 0 }, args, numeric::add),
+        ("*", _) => fold(arena, Atom::Int { inner: 1 }, args, numeric::mul),
+        ("-", [x]) => numeric::sub(arena, &Atom::Int { inner: 0 }, x),
+        ("-", [x, rest @ ..]) => fold(arena, *x, rest, numeric::sub),
+        ("/", [x]) => numeric::div(arena, &Atom::Int { inner: 1 }, x),
+        ("/", [x, rest @ ..]) => fold(arena, *x, rest, numeric::div),
+        ("=", _) => chain(args, |a, b| match is_number(a) && is_number(b) {
+            true => Ok(numeric::compare_numbers(a, b)? == Ordering::Equal),
+            false => Ok(equal(a, b)),
+        }),
+        ("!=", [a, b]) => match is_number(a) && is_number(b) {
+            true => Ok(boolean(numeric::compare_numbers(a, b)? != Ordering::Equal)),
+            false => Ok(boolean(!equal(a, b))),
+        },
+        ("<", _) => compare(args, Ordering::is_lt),
+        (">", _) => compare(args, Ordering::is_gt),
+        ("<=", _) => compare(args, Ordering::is_le),
+        (">=", _) => compare(args, Ordering::is_ge),
+        ("!", [x]) if is_number(x) => numeric::sub(arena, &Atom::Int { inner: 0 }, x),
+        ("!" | "not", [x]) => Ok(boolean(matches!(x, Atom::False | Atom::Nil))),
+        ("^", [a, b]) => numeric::expt(arena, a, b),
+        ("%", [a, b]) => numeric::modulo(a, b),
+        ("//", [a, b]) => numeric::remainder(a, b),
+        ("exact", [x]) => numeric::exact(arena, x),
+        ("inexact", [x]) => numeric::inexact(x),
+        ("numerator", [x]) => numeric::numerator(arena, x),
+        ("denominator", [x]) => numeric::denominator(arena, x),
+
+        // Lists
+        ("cons", [car, cdr]) => pair::cons(arena, *car, *cdr),
+        ("car", [list]) => pair::car(list),
+        ("cdr", [list]) => pair::cdr(list),
+        ("list", _) => pair::list(arena, args),
+        ("null?", [x]) => Ok(boolean(pair::is_null(x))),
+        ("pair?", [x]) => Ok(boolean(pair::is_pair(x))),
+        ("eq?", [a @ (Atom::Symbol { .. } | Atom::Quoted { .. }), b]) => Ok(symbol::eq(a, b)),
+        ("eq?" | "eqv?", [a, b]) => Ok(boolean(eqv(a, b))),
+        ("equal?", [a, b]) => Ok(boolean(equal(a, b))),
+
+        // Characters, strings and symbols
+        ("char->integer", [c]) => chars::char_to_integer(c),
+        ("integer->char", [n]) => chars::integer_to_char(n),
+        ("char-alphabetic?", [c]) => chars::char_alphabetic(c),
+        ("string-length", [Atom::String { inner }]) => Ok(Atom::Int {
+            inner: inner.chars().count() as i64,
+        }),
+        ("string-ref", [s, k]) => chars::string_ref(s, k),
+        ("substring", [s, start, end]) => substring(s, start, end),
+        ("string-append", _) => string_append(arena, args),
+        ("string=?", [Atom::String { inner: a }, Atom::String { inner: b }]) => Ok(boolean(a == b)),
+        ("string->list", [s]) => chars::string_to_list(arena, s),
+        ("list->string", [list]) => chars::list_to_string(arena, list),
+        ("string->symbol", [s]) => symbol::string_to_symbol(&mut env.symbols(), s),
+        ("symbol->string", [s]) => symbol::symbol_to_string(&env.symbols(), s),
+        ("string-length" | "string=?", _) => Err("Not a string"),
+
+        // Vectors
+        ("vector?", [x]) => Ok(boolean(matches!(x, Atom::Vector { .. }))),
+        ("vector-length", [Atom::Vector { body }]) => Ok(Atom::Int {
+            inner: body.len() as i64,
+        }),
+        ("vector-ref", [Atom::Vector { body }, Atom::Int { inner }]) => usize::try_from(*inner)
+            .ok()
+            .filter(|index| *index < body.len())
+            .map(|index| body[index].payload)
+            .ok_or("Vector index out of range"),
+        ("%vector-tail", [Atom::Vector { body }, Atom::Int { inner }]) => usize::try_from(*inner)
+            .ok()
+            .filter(|start| *start <= body.len())
+            .map(|start| Atom::Vector {
+                body: body.tail(start),
+            })
+            .ok_or("Vector index out of range"),
+        ("vector-length" | "vector-ref" | "%vector-tail", _) => Err("Not a vector"),
+
+        // Promises, which the evaluator forces itself
+        ("promise?", [x]) => Ok(boolean(matches!(x, Atom::Promise { .. }))),
+        ("make-promise", [x]) => lazy::make_promise(arena, *x).ok_or("Failed to allocate promise"),
+        ("%delay", [thunk]) => lazy::delay(arena, *thunk).ok_or("Failed to allocate promise"),
+
+        // Records, which define-record-type expands into
+        ("%record-type", [name, fields @ ..]) => {
+            let name = symbol_name(name)?;
+            let fields = fields
+                .iter()
+                .map(symbol_name)
+                .collect::<Result<Vec<_>, _>>()?;
+            record::record_type(arena, name, &fields).ok_or("Failed to allocate record type")
+        }
+        ("%record", [kind, values @ ..]) => record::make_record(arena, kind, values),
+        ("%record?", [kind, value]) => record::is_record(kind, value),
+        ("%record-ref", [kind, value, index]) => record::record_ref(kind, value, index_of(index)?),
+        ("%record-set!", [kind, value, index, new]) => {
+            record::record_set(kind, value, index_of(index)?, *new)
+        }
+        ("%match-error", [_]) => Err("No match clause matched"),
+
+        // Coroutines, which the evaluator switches between itself
+        ("coroutine?", [x]) => Ok(boolean(matches!(x, Atom::Coroutine { .. }))),
+        ("coroutine-done?", [Atom::Coroutine { coroutine }]) => Ok(boolean(coroutine.is_done())),
+        ("make-coroutine", [procedure]) if eval::is_procedure(procedure) => {
+            eval::coroutine(arena, *procedure)
+        }
+        ("make-coroutine", _) => Err("Not a procedure"),
+        ("resume", [_, _, _, ..]) => Err("Wrong number of arguments"),
+        ("coroutine-done?" | "resume", _) => Err("Not a coroutine"),
+
+        // Files and the console
+        ("read-file", [Atom::String { inner }]) => {
+            let text = std::fs::read_to_string(inner).map_err(|_| "Unable to read file")?;
+            string(arena, &text)
+        }
+        ("write-file", [Atom::String { inner: path }, Atom::String { inner }]) => {
+            std::fs::write(path, inner).map_err(|_| "Unable to write file")?;
+            Ok(Atom::Nil)
+        }
+        ("read-line", []) => {
+            let mut line = String::new();
+            match std::io::stdin().lock().read_line(&mut line) {
+                Ok(0) => Ok(Atom::Nil),
+                Ok(_) => string(arena, line.trim_end_matches(['\n', '\r'])),
+                Err(_) => Err("Unable to read line"),
+            }
+        }
+        ("display", [value]) => {
+            let mut text = String::new();
+            match value {
+                Atom::String { inner } => text.push_str(inner),
+                value => print_value(&mut text, value).map_err(|_| "Unable to print value")?,
+            }
+            let mut stdout = std::io::stdout().lock();
+            stdout
+                .write_all(text.as_bytes())
+                .and_then(|_| stdout.flush())
+                .map_err(|_| "Unable to write output")?;
+            Ok(Atom::Nil)
+        }
+        ("newline", []) => {
+            println!();
+            Ok(Atom::Nil)
+        }
+        ("read-file" | "write-file", _) => Err("Expected a path and a string"),
+
+        // The process
+        ("system", [Atom::String { inner }]) => std::process::Command::new("sh")
+            .arg("-c")
+            .arg(inner)
+            .status()
+            .map(|status| Atom::Int {
+                inner: status.code().unwrap_or(-1) as i64,
+            })
+            .map_err(|_| "Unable to run command"),
+        ("getenv", [Atom::String { inner }]) => match std::env::var(inner) {
+            Ok(value) => string(arena, &value),
+            Err(_) => Ok(Atom::False),
+        },
+        ("exit", []) => std::process::exit(0),
+        ("exit", [Atom::Int { inner }, ..]) => std::process::exit(*inner as i32),
+        ("exit", [Atom::False, ..]) => std::process::exit(1),
+        ("exit", _) => std::process::exit(0),
+        ("system" | "getenv", _) => Err("Not a string"),
+        ("spawn", [procedure, args @ ..]) => Task::start(arena, env, *procedure, args),
+        ("join", [Atom::Task { task }]) => task.join(arena),
+        ("join", _) => Err("Not a task"),
+
+        // The clock
+        ("current-time", []) => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| Atom::Number {
+                inner: elapsed.as_secs_f64(),
+            })
+            .map_err(|_| "Clock is before the epoch"),
+        ("current-jiffy", []) => {
+            static START: OnceLock<Instant> = OnceLock::new();
+            let elapsed = START.get_or_init(Instant::now).elapsed();
+            Ok(Atom::Int {
+                inner: elapsed.as_nanos() as i64,
+            })
+        }
+        ("sleep", [seconds]) if is_number(seconds) => {
+            let seconds = match numeric::inexact(seconds)? {
+                Atom::Number { inner } => inner,
+                _ => 0.0,
+            };
+            let duration = Duration::try_from_secs_f64(seconds).map_err(|_| "Invalid duration")?;
+            std::thread::sleep(duration);
+            Ok(Atom::Nil)
+        }
+
+        _ if is_number_operator(name) => Err("Not a number"),
+        _ => Err("Invalid arguments"),
+    }
+}
+
+fn is_number_operator(name: &str) -> bool {
+    matches!(name, "-" | "/" | "^" | "%" | "//" | "sleep")
+}
+
+fn fold<'arena>(
+    arena: &'arena Arena<'arena>,
+    initial: Atom<'arena>,
+    args: &[Atom<'arena>],
+    op: fn(&'arena Arena<'arena>, &Atom, &Atom) -> PrimitiveResult<'arena>,
+) -> PrimitiveResult<'arena> {
+    let mut value = initial;
+    for arg in args {
+        value = op(arena, &value, arg)?;
+    }
+    Ok(value)
+}
+
+// Holds for every neighbouring pair, so (< 1 2 3) is true.
+fn chain<'arena>(
+    args: &[Atom<'arena>],
+    test: impl Fn(&Atom<'arena>, &Atom<'arena>) -> Result<bool, &'static str>,
+) -> PrimitiveResult<'arena> {
+    for pair in args.windows(2) {
+        if !test(&pair[0], &pair[1])? {
+            return Ok(Atom::False);
+        }
+    }
+    Ok(Atom::True)
+}
+
+fn compare<'arena>(args: &[Atom<'arena>], test: fn(Ordering) -> bool) -> PrimitiveResult<'arena> {
+    if let [single] = args
+        && !is_number(single)
+    {
+        return Err("Not a number");
+    }
+    chain(args, |a, b| numeric::compare_numbers(a, b).map(test))
+}
+
+/// `eqv?`: the same number, symbol or constant, or the same object.
+pub fn eqv<'a>(a: &Atom<'a>, b: &Atom<'a>) -> bool {
+    match (*a, *b) {
+        (
+            Atom::Symbol { name: a } | Atom::Quoted { name: a },
+            Atom::Symbol { name: b } | Atom::Quoted { name: b },
+        ) => a == b,
+        (Atom::String { inner: a }, Atom::String { inner: b }) => core::ptr::eq(a, b),
+        (Atom::Buffer { data: a }, Atom::Buffer { data: b }) => core::ptr::eq(a, b),
+        (Atom::Pair { pair: a }, Atom::Pair { pair: b }) => core::ptr::eq(a, b),
+        (Atom::Promise { promise: a }, Atom::Promise { promise: b }) => core::ptr::eq(a, b),
+        (Atom::Record { record: a }, Atom::Record { record: b }) => core::ptr::eq(a, b),
+        (Atom::RecordType { kind: a }, Atom::RecordType { kind: b }) => core::ptr::eq(a, b),
+        (
+            Atom::List { body: a } | Atom::Code { body: a },
+            Atom::List { body: b } | Atom::Code { body: b },
+        )
+        | (Atom::Vector { body: a }, Atom::Vector { body: b }) => {
+            a.buffer() == b.buffer() && a.len() == b.len()
+        }
+        (Atom::Map { map: a }, Atom::Map { map: b }) => core::ptr::eq(a, b),
+        (a, b) => a == b,
+    }
+}
+
+/// `equal?`: like `eqv?`, but lists, strings, vectors and maps are equal
+/// when their contents are.
+pub fn equal<'a>(a: &Atom<'a>, b: &Atom<'a>) -> bool {
+    let (mut a, mut b) = (*a, *b);
+    loop {
+        match (a, b) {
+            _ if pair::is_pair(&a) && pair::is_pair(&b) => {
+                let (Ok(car_a), Ok(car_b)) = (pair::car(&a), pair::car(&b)) else {
+                    return false;
+                };
+                if !equal(&car_a, &car_b) {
+                    return false;
+                }
+                match (pair::cdr(&a), pair::cdr(&b)) {
+                    (Ok(cdr_a), Ok(cdr_b)) => (a, b) = (cdr_a, cdr_b),
+                    _ => return false,
+                }
+            }
+            _ if pair::is_null(&a) && pair::is_null(&b) => return true,
+            (Atom::String { inner: a }, Atom::String { inner: b }) => return a == b,
+            (Atom::Vector { body: a }, Atom::Vector { body: b }) => {
+                return a.len() == b.len()
+                    && a.iter()
+                        .zip(b.iter())
+                        .all(|(a, b)| equal(&a.payload, &b.payload));
+            }
+            (Atom::Map { map: a }, Atom::Map { map: b }) => {
+                return a.len() == b.len()
+                    && a.iter().all(|(key, value)| {
+                        b.get(key)
+                            .is_some_and(|other| equal(&value.payload, &other.payload))
+                    });
+            }
+            (a, b) => return eqv(&a, &b),
+        }
+    }
+}
+
+// Character positions, as string-length counts them.
+fn substring<'arena>(s: &Atom<'arena>, start: &Atom, end: &Atom) -> PrimitiveResult<'arena> {
+    let (Atom::String { inner }, Atom::Int { inner: start }, Atom::Int { inner: end }) =
+        (*s, *start, *end)
+    else {
+        return Err("Expected a string and two indices");
+    };
+
+    let offset = |index: i64| {
+        let index = usize::try_from(index).ok()?;
+        inner
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain([inner.len()])
+            .nth(index)
+    };
+
+    match (offset(start), offset(end)) {
+        (Some(start), Some(end)) if start <= end => Ok(Atom::String {
+            inner: &inner[start..end],
+        }),
+        _ => Err("String index out of range"),
+    }
+}
+
+fn string_append<'arena>(
+    arena: &'arena Arena<'arena>,
+    args: &[Atom<'arena>],
+) -> PrimitiveResult<'arena> {
+    let mut len = 0;
+    for arg in args {
+        match arg {
+            Atom::String { inner } => len += inner.len(),
+            _ => return Err("Not a string"),
+        }
+    }
+
+    let buffer = make!(arena, u8, len).ok_or("Failed to allocate string")?;
+    let mut used = 0;
+    for arg in args {
+        if let Atom::String { inner } = arg {
+            buffer[used..used + inner.len()].copy_from_slice(inner.as_bytes());
+            used += inner.len();
+        }
+    }
+
+    core::str::from_utf8(buffer)
+        .map(|inner| Atom::String { inner })
+        .map_err(|_| "Invalid UTF-8")
+}
+
+fn string<'arena>(arena: &'arena Arena<'arena>, text: &str) -> PrimitiveResult<'arena> {
+    let buffer = make!(arena, u8, text.len()).ok_or("Failed to allocate string")?;
+    buffer.copy_from_slice(text.as_bytes());
+    core::str::from_utf8(buffer)
+        .map(|inner| Atom::String { inner })
+        .map_err(|_| "Invalid UTF-8")
+}
+
+fn symbol_name<'arena>(atom: &Atom<'arena>) -> Result<&'arena str, &'static str> {
+    match *atom {
+        Atom::Symbol { name } | Atom::Quoted { name } => Ok(name),
+        _ => Err("Not a symbol"),
+    }
+}
+
+fn index_of(atom: &Atom) -> Result<usize, &'static str> {
+    match *atom {
+        Atom::Int { inner } => usize::try_from(inner).map_err(|_| "Index out of range"),
+        _ => Err("Not an index"),
+    }
+}
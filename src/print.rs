@@ -1,9 +1,17 @@
-use crate::read::{Atom, Expression};
+use crate::chars::{char_name, escape_char};
+use crate::pair;
+use crate::read::{Atom, Expression, Span};
 use std::fmt::{Error, Write};
 
-pub fn print<W: Write>(
+pub fn print<W: Write>(strbuf: &mut W, root: &[Expression], quoted: bool) -> Result<(), Error> {
+    let open = if quoted { "(quote " } else { "(" };
+    print_body(strbuf, root, open, quoted)
+}
+
+fn print_body<W: Write>(
     strbuf: &mut W,
     root: &[Expression],
+    open: &str,
     quoted: bool,
 ) -> Result<(), Error> {
     for (position, expr) in root.iter().enumerate() {
@@ -14,11 +22,7 @@ pub fn print<W: Write>(
                 write!(strbuf, "  ")?;
             }
 
-            if quoted {
-                write!(strbuf, "(quote ")?;
-            } else {
-                write!(strbuf, "(")?;
-            }
+            write!(strbuf, "{open}")?;
         }
 
         if position != 0 {
@@ -36,9 +40,75 @@ pub fn print<W: Write>(
                 print(strbuf, body, true)?;
                 write!(strbuf, ")")?;
             }
+            Atom::Pair { .. } => {
+                print_value(strbuf, &expr.payload)?;
+            }
+            Atom::Closure { .. } => {
+                write!(strbuf, "#<procedure>")?;
+            }
+            Atom::Coroutine { .. } => {
+                write!(strbuf, "#<coroutine>")?;
+            }
+            Atom::Task { .. } => {
+                write!(strbuf, "#<task>")?;
+            }
+            Atom::Primitive { name } => {
+                write!(strbuf, "#<primitive {}>", name)?;
+            }
+            Atom::Vector { ref body } if body.is_empty() => {
+                write!(strbuf, "[]")?;
+            }
+            Atom::Vector { ref body } => {
+                writeln!(strbuf)?;
+                print_body(strbuf, body, "[", false)?;
+                write!(strbuf, "]")?;
+            }
+            Atom::Map { map } if map.is_empty() => {
+                write!(strbuf, "{{}}")?;
+            }
+            Atom::Map { map } => {
+                writeln!(strbuf)?;
+                print_body(strbuf, &map.body(), "{", false)?;
+                write!(strbuf, "}}")?;
+            }
+            Atom::RecordType { kind } => {
+                write!(strbuf, "#<record-type {}>", kind.name)?;
+            }
+            Atom::Record { record } => {
+                write!(strbuf, "#<record {}", record.kind().name)?;
+                for payload in record.values() {
+                    write!(strbuf, " ")?;
+                    let value = Expression {
+                        depth: 0,
+                        payload,
+                        span: Span::default(),
+                    };
+                    print_body(strbuf, &[value], "(", false)?;
+                }
+                write!(strbuf, ">")?;
+            }
+            Atom::Promise { .. } => {
+                write!(strbuf, "#<promise>")?;
+            }
             Atom::Int { inner } => {
                 write!(strbuf, "{}", inner)?;
             }
+            Atom::BigInt { inner } => {
+                write!(strbuf, "{}", inner)?;
+            }
+            Atom::Rational { inner } => {
+                write!(strbuf, "{}", inner)?;
+            }
+            Atom::Number { inner } if inner.is_nan() => {
+                write!(strbuf, "+nan.0")?;
+            }
+            Atom::Number { inner } if inner.is_infinite() => {
+                write!(strbuf, "{}inf.0", if inner > 0.0 { "+" } else { "-" })?;
+            }
+            // A whole float keeps its point, so it does not read as exact.
+            Atom::Number { inner } if inner.fract() == 0.0 => {
+                write!(strbuf, "{:.1}", inner)?;
+            }
             Atom::Number { inner } => {
                 write!(strbuf, "{}", inner)?;
             }
@@ -58,18 +128,24 @@ pub fn print<W: Write>(
             Atom::Nil => {
                 write!(strbuf, "#nil")?;
             }
+            Atom::Char { inner } => match char_name(inner) {
+                Some(name) => write!(strbuf, "#\\{name}")?,
+                None if inner.is_control() => write!(strbuf, "#\\x{:x}", inner as u32)?,
+                None => write!(strbuf, "#\\{inner}")?,
+            },
             Atom::String { inner } => {
-                write!(strbuf, "{}", inner)?;
+                write_string(strbuf, inner)?;
             }
             Atom::Buffer { data } => {
                 write!(strbuf, "buffer {}", data.len())?;
             }
             Atom::File { path, lazy } => {
                 if lazy {
-                    write!(strbuf, "lazyload \"{}\"", path)?;
+                    write!(strbuf, "lazyload ")?;
                 } else {
-                    write!(strbuf, "load \"{}\"", path)?;
+                    write!(strbuf, "load ")?;
                 }
+                write_string(strbuf, path)?;
             }
             Atom::Define => {
                 write!(strbuf, "define")?;
@@ -86,6 +162,9 @@ pub fn print<W: Write>(
             Atom::Symbol { name } => {
                 write!(strbuf, "{name}")?;
             }
+            Atom::Quoted { name } => {
+                write!(strbuf, "'{name}")?;
+            }
             Atom::Add => {
                 write!(strbuf, "+")?;
             }
@@ -117,10 +196,10 @@ pub fn print<W: Write>(
                 write!(strbuf, "<=")?;
             }
             Atom::ArrowLeft => {
-                write!(strbuf, "->")?;
+                write!(strbuf, "<-")?;
             }
             Atom::ArrowRight => {
-                write!(strbuf, "<-")?;
+                write!(strbuf, "->")?;
             }
             Atom::Negate => {
                 write!(strbuf, "!")?;
@@ -139,3 +218,69 @@ pub fn print<W: Write>(
 
     Ok(())
 }
+
+/// Writes a value computed at run time on one line. Lists print the same
+/// whether they are arrays or pairs, with a final ` . tail` if they are
+/// improper.
+pub fn print_value<W: Write>(strbuf: &mut W, value: &Atom) -> Result<(), Error> {
+    match *value {
+        Atom::Pair { .. } | Atom::List { .. } | Atom::Code { .. } => {
+            write!(strbuf, "(")?;
+            let mut items = pair::items(*value);
+            for (position, item) in items.by_ref().enumerate() {
+                if position != 0 {
+                    write!(strbuf, " ")?;
+                }
+                print_value(strbuf, &item)?;
+            }
+            if !pair::is_null(&items.rest()) {
+                write!(strbuf, " . ")?;
+                print_value(strbuf, &items.rest())?;
+            }
+            write!(strbuf, ")")
+        }
+        Atom::Vector { ref body } => {
+            write!(strbuf, "[")?;
+            for (position, expr) in body.iter().enumerate() {
+                if position != 0 {
+                    write!(strbuf, " ")?;
+                }
+                print_value(strbuf, &expr.payload)?;
+            }
+            write!(strbuf, "]")
+        }
+        Atom::Map { map } => {
+            write!(strbuf, "{{")?;
+            for (position, (key, value)) in map.iter().enumerate() {
+                if position != 0 {
+                    write!(strbuf, " ")?;
+                }
+                print_value(strbuf, &key)?;
+                write!(strbuf, " ")?;
+                print_value(strbuf, &value.payload)?;
+            }
+            write!(strbuf, "}}")
+        }
+        atom => {
+            let expr = Expression {
+                depth: 0,
+                payload: atom,
+                span: Span::default(),
+            };
+            print(strbuf, &[expr], false)
+        }
+    }
+}
+
+// Writes `s` as a string literal that reads back as the same string.
+fn write_string<W: Write>(strbuf: &mut W, s: &str) -> Result<(), Error> {
+    write!(strbuf, "\"")?;
+    for c in s.chars() {
+        match escape_char(c) {
+            Some(letter) => write!(strbuf, "\\{letter}")?,
+            None if c.is_control() => write!(strbuf, "\\x{:x};", c as u32)?,
+            None => write!(strbuf, "{c}")?,
+        }
+    }
+    write!(strbuf, "\"")
+}
@@ -1,8 +1,21 @@
+use crate::chars;
+use crate::eval::{Closure, Coroutine};
+use crate::lazy::Promise;
+use crate::map::{Map, MapError};
+use crate::numeric::{self, BigInt, Ratio};
+use crate::pair::Pair;
+use crate::record::{Record, RecordType};
+use crate::symbol::SymbolTable;
+use crate::thread::Task;
 use crate::{Arena, Array, Box as ArenaBox, List, Node, make};
+use core::fmt::{Display, Formatter};
 use core::str::CharIndices;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-enum Token<'code> {
+pub(crate) enum Token<'code> {
     LParen,
     RParen,
     LBracket,
@@ -15,31 +28,49 @@ enum Token<'code> {
     Quote,
     Quasiquote,
     Integer(&'code str),
+    Rational(&'code str),
     Float(&'code str),
+    Char(&'code str),
     String(&'code str),
     Symbol(&'code str),
     Comment(&'code str),
+    DatumComment,
 }
 
 struct Tokenizer<'code> {
     code: &'code str,
     char_indices: CharIndices<'code>,
     current: Option<(usize, char)>,
+    line: u32,
+    column: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Delimiter {
+    Paren,
+    Bracket,
+    Brace,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Lexeme<'arena> {
     Unit,
+    EmptyVector,
+    EmptyMap,
     Null,
     True,
     False,
     Integer(&'arena str),
+    Rational(&'arena str),
     Double(&'arena str),
+    Char(&'arena str),
     String(&'arena str),
     Symbol(&'arena str, bool),
-    Operator(&'arena str),
-    List(ArenaBox<Node<Lexeme<'arena>>>, usize),
-    Quoted(ArenaBox<Node<Lexeme<'arena>>>, usize),
+    Operator(Atom<'arena>),
+    List(ArenaBox<Node<Spanned<'arena>>>, usize),
+    Quoted(ArenaBox<Node<Spanned<'arena>>>, usize),
+    Vector(ArenaBox<Node<Spanned<'arena>>>, usize, bool),
+    Map(ArenaBox<Node<Spanned<'arena>>>, usize, bool),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -49,7 +80,10 @@ pub enum Atom<'arena> {
     Void,
     Nil,
     Int { inner: i64 },
+    BigInt { inner: BigInt<'arena> },
+    Rational { inner: &'arena Ratio<'arena> },
     Number { inner: f64 },
+    Char { inner: char },
     String { inner: &'arena str },
     Buffer { data: &'arena [u8] },
     File { path: &'arena str, lazy: bool },
@@ -58,8 +92,19 @@ pub enum Atom<'arena> {
     Head,
     Tail,
     Symbol { name: &'arena str },
+    Quoted { name: &'arena str },
     List { body: Array<Expression<'arena>> },
     Code { body: Array<Expression<'arena>> },
+    Vector { body: Array<Expression<'arena>> },
+    Map { map: &'arena Map<'arena> },
+    Promise { promise: &'arena Promise<'arena> },
+    Pair { pair: &'arena Pair<'arena> },
+    Closure { closure: &'arena Closure<'arena> },
+    Coroutine { coroutine: &'arena Coroutine<'arena> },
+    Task { task: &'arena Task },
+    Primitive { name: &'arena str },
+    RecordType { kind: &'arena RecordType<'arena> },
+    Record { record: &'arena Record<'arena> },
     Add,
     Subtract,
     Multiply,
@@ -78,18 +123,336 @@ pub enum Atom<'arena> {
     Remainder,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+type Spanned<'arena> = (Lexeme<'arena>, Span);
+
+/// Where an expression was read from: the byte range `start..end` and the
+/// 1-based line and column of its first character. Expressions built by
+/// the expander or decoded from an image have an empty span.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: u32,
+    pub end: u32,
+    pub line: u32,
+    pub column: u32,
+}
+
+// Spans are not part of an expression's value, so two expressions read
+// from different places still compare equal.
+#[derive(Clone, Copy, Debug)]
 pub struct Expression<'arena> {
     pub depth: usize,
     pub payload: Atom<'arena>,
+    pub span: Span,
+}
+
+impl Span {
+    /// The span from the start of `self` to the end of `end`.
+    pub fn to(self, end: Span) -> Span {
+        Span {
+            end: end.end,
+            ..self
+        }
+    }
+}
+
+impl PartialEq for Expression<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.depth == other.depth && self.payload == other.payload
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    UnbalancedDelimiter,
+    UnexpectedEof,
+    UnterminatedString,
+    InvalidNumber,
+    InvalidCharacter,
+    InvalidQuote,
+    MalformedComment,
+    UnterminatedComment,
+    InvalidEscape,
+    OddMapLiteral,
+    InvalidMapKey,
+    DuplicateMapKey,
+    OutOfMemory,
+    InvalidUtf8,
+    Io,
+}
+
+/// Why `code` could not be read, and where.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub span: Span,
 }
 
+type ParseResult<T> = Result<T, ParseError>;
+
+impl ParseError {
+    pub(crate) fn new(kind: ParseErrorKind, span: Span) -> Self {
+        ParseError { kind, span }
+    }
+
+    /// Whether more input could still complete the code, as with an open
+    /// list, an unterminated string or a trailing quote.
+    pub fn is_incomplete(&self) -> bool {
+        matches!(
+            self.kind,
+            ParseErrorKind::UnexpectedEof
+                | ParseErrorKind::UnterminatedString
+                | ParseErrorKind::UnterminatedComment
+        )
+    }
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let message = match self {
+            ParseErrorKind::UnbalancedDelimiter => "unbalanced delimiter",
+            ParseErrorKind::UnexpectedEof => "unexpected end of input",
+            ParseErrorKind::UnterminatedString => "unterminated string",
+            ParseErrorKind::InvalidNumber => "invalid number",
+            ParseErrorKind::InvalidCharacter => "invalid character literal",
+            ParseErrorKind::InvalidQuote => "only lists, symbols and literals can be quoted",
+            ParseErrorKind::MalformedComment => "datum comment without a datum",
+            ParseErrorKind::UnterminatedComment => "unterminated block comment",
+            ParseErrorKind::InvalidEscape => "invalid escape in string",
+            ParseErrorKind::OddMapLiteral => "map literal needs an even number of forms",
+            ParseErrorKind::InvalidMapKey => {
+                "map keys must be symbols, strings, characters, integers, booleans or nil"
+            }
+            ParseErrorKind::DuplicateMapKey => "duplicate key in map literal",
+            ParseErrorKind::OutOfMemory => "out of memory",
+            ParseErrorKind::InvalidUtf8 => "input is not valid UTF-8",
+            ParseErrorKind::Io => "unable to read input",
+        };
+        write!(f, "{message}")
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}: {}", self.span.line, self.span.column, self.kind)
+    }
+}
+
+/// Parses `code`. Strings and symbols in the result borrow from `code`, so
+/// it must live as long as the arena; see `parse_copy` for input that
+/// does not. Symbols are interned in a table of their own; code meant for
+/// an environment should be read with `parse_with` and its table.
 pub fn parse<'arena>(
-    arena: &'arena Arena,
-    code: &'static str,
-) -> Option<Array<Expression<'arena>>> {
-    let (root, count) = lexer(arena, code).ok()?;
-    parse_list(arena, root, count, 0)
+    arena: &'arena Arena<'arena>,
+    code: &'arena str,
+) -> ParseResult<Array<Expression<'arena>>> {
+    let mut symbols = SymbolTable::with_capacity(arena, 16);
+    parse_with(arena, code, &mut symbols)
+}
+
+/// Parses `code`, interning every symbol name in `symbols` so that names
+/// read from different sources share one address.
+pub fn parse_with<'arena>(
+    arena: &'arena Arena<'arena>,
+    code: &'arena str,
+    symbols: &mut SymbolTable<'arena>,
+) -> ParseResult<Array<Expression<'arena>>> {
+    let (root, count) = lexer(arena, code)?;
+    parse_list(arena, symbols, root.as_ref(), count, 0)
+}
+
+/// Copies `code` into the arena and parses the copy.
+pub fn parse_copy<'arena>(
+    arena: &'arena Arena<'arena>,
+    code: &str,
+) -> ParseResult<Array<Expression<'arena>>> {
+    parse(arena, copy(arena, code.as_bytes())?)
+}
+
+/// Reads all of `reader` into the arena and parses it.
+pub fn parse_reader<'arena, R: Read>(
+    arena: &'arena Arena<'arena>,
+    mut reader: R,
+) -> ParseResult<Array<Expression<'arena>>> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|_| ParseError::new(ParseErrorKind::Io, Span::default()))?;
+    parse(arena, copy(arena, &bytes)?)
+}
+
+pub fn parse_file<'arena, P: AsRef<Path>>(
+    arena: &'arena Arena<'arena>,
+    path: P,
+) -> ParseResult<Array<Expression<'arena>>> {
+    let mut symbols = SymbolTable::with_capacity(arena, 16);
+    parse_file_with(arena, path, &mut symbols)
+}
+
+/// Like `parse_file`, but interns symbols in `symbols`, as `parse_with`
+/// does.
+pub fn parse_file_with<'arena, P: AsRef<Path>>(
+    arena: &'arena Arena<'arena>,
+    path: P,
+    symbols: &mut SymbolTable<'arena>,
+) -> ParseResult<Array<Expression<'arena>>> {
+    let mut bytes = Vec::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut bytes))
+        .map_err(|_| ParseError::new(ParseErrorKind::Io, Span::default()))?;
+    parse_with(arena, copy(arena, &bytes)?, symbols)
+}
+
+fn copy<'arena>(arena: &'arena Arena<'arena>, bytes: &[u8]) -> ParseResult<&'arena str> {
+    let copy = make!(arena, u8, bytes.len()).ok_or(ParseError::new(
+        ParseErrorKind::OutOfMemory,
+        Span::default(),
+    ))?;
+    copy.copy_from_slice(bytes);
+
+    core::str::from_utf8(copy).map_err(|error| {
+        let start = error.valid_up_to();
+        let valid = core::str::from_utf8(&bytes[..start]).unwrap_or_default();
+        let line = valid.matches('\n').count() + 1;
+        let column = valid[valid.rfind('\n').map_or(0, |i| i + 1)..]
+            .chars()
+            .count()
+            + 1;
+        let span = Span {
+            start: start as u32,
+            end: start as u32 + 1,
+            line: line as u32,
+            column: column as u32,
+        };
+        ParseError::new(ParseErrorKind::InvalidUtf8, span)
+    })
+}
+
+/// Reads top-level forms from input that arrives in pieces, such as lines
+/// typed at a REPL. Each form is returned as soon as it is closed; spans
+/// count from the end of the form before it.
+pub struct Reader<'arena> {
+    arena: &'arena Arena<'arena>,
+    symbols: SymbolTable<'arena>,
+    pending: String,
+}
+
+enum Scan {
+    Empty,
+    Incomplete,
+    Datum(usize),
+    Error(ParseError),
+}
+
+impl<'arena> Reader<'arena> {
+    pub fn new(arena: &'arena Arena<'arena>) -> Self {
+        Reader {
+            arena,
+            symbols: SymbolTable::with_capacity(arena, 16),
+            pending: String::new(),
+        }
+    }
+
+    pub fn feed(&mut self, chunk: &str) {
+        self.pending.push_str(chunk);
+    }
+
+    /// Whether the input so far holds the start of a form that has not
+    /// been closed yet.
+    pub fn needs_more(&self) -> bool {
+        matches!(self.scan(), Scan::Incomplete)
+    }
+
+    /// The next complete form, or `None` until more input is fed. A syntax
+    /// error discards the input it was found in.
+    pub fn next_form(&mut self) -> ParseResult<Option<Expression<'arena>>> {
+        match self.scan() {
+            Scan::Empty | Scan::Incomplete => Ok(None),
+            Scan::Datum(end) => self.read(end),
+            Scan::Error(error) => {
+                self.pending.clear();
+                Err(error)
+            }
+        }
+    }
+
+    /// Marks the end of the input and reads the next form left in it, so
+    /// call it until it returns `None`. An unclosed form is an error.
+    pub fn finish(&mut self) -> ParseResult<Option<Expression<'arena>>> {
+        self.pending.push('\n');
+        match self.scan() {
+            Scan::Empty => {
+                self.pending.clear();
+                Ok(None)
+            }
+            Scan::Datum(end) => self.read(end),
+            Scan::Error(error) => {
+                self.pending.clear();
+                Err(error)
+            }
+            Scan::Incomplete => {
+                let pending = core::mem::take(&mut self.pending);
+                let code = copy(self.arena, pending.as_bytes())?;
+                parse_with(self.arena, code, &mut self.symbols).map(|_| None)
+            }
+        }
+    }
+
+    fn read(&mut self, end: usize) -> ParseResult<Option<Expression<'arena>>> {
+        let code = copy(self.arena, &self.pending.as_bytes()[..end])?;
+        self.pending.drain(..end);
+
+        let root = parse_with(self.arena, code, &mut self.symbols)?;
+        Ok(root[..root.len()].first().copied())
+    }
+
+    // Finds where the first top-level form in the pending input ends. A
+    // symbol or number that runs into the end of the input may still grow,
+    // so it only counts once something follows it, and a form removed by
+    // `#;` does not count at all.
+    fn scan(&self) -> Scan {
+        let mut depth = 0usize;
+        let mut skipped = 0usize;
+        let mut seen = false;
+
+        for token in tokenize(&self.pending) {
+            let (token, span) = match token {
+                Ok(token) => token,
+                Err(error) if error.is_incomplete() => return Scan::Incomplete,
+                Err(error) => return Scan::Error(error),
+            };
+            if let Token::Comment(_) = token {
+                continue;
+            }
+            seen = true;
+
+            match token {
+                Token::Quote | Token::Quasiquote => continue,
+                Token::DatumComment => {
+                    skipped += usize::from(depth == 0);
+                    continue;
+                }
+                Token::LParen | Token::LBracket | Token::LBrace => {
+                    depth += 1;
+                    continue;
+                }
+                Token::RParen | Token::RBracket | Token::RBrace => {
+                    depth = depth.saturating_sub(1);
+                }
+                Token::String(_) => {}
+                _ if span.end as usize == self.pending.len() => return Scan::Incomplete,
+                _ => {}
+            }
+
+            if depth == 0 && skipped > 0 {
+                skipped -= 1;
+                seen = skipped > 0;
+            } else if depth == 0 {
+                return Scan::Datum(span.end as usize);
+            }
+        }
+
+        if seen { Scan::Incomplete } else { Scan::Empty }
+    }
 }
 
 impl<'code> Tokenizer<'code> {
@@ -100,14 +463,29 @@ impl<'code> Tokenizer<'code> {
             code,
             char_indices,
             current,
+            line: 1,
+            column: 1,
         }
     }
 
     fn advance(&mut self) -> Option<(usize, char)> {
+        match self.current {
+            Some((_, '\n')) => {
+                self.line += 1;
+                self.column = 1;
+            }
+            Some(_) => self.column += 1,
+            None => {}
+        }
+
         self.current = self.char_indices.next();
         self.current
     }
 
+    fn offset(&self) -> usize {
+        self.current.map_or(self.code.len(), |(i, _)| i)
+    }
+
     fn eat_whitespace(&mut self) {
         while let Some((_, c)) = self.current {
             if !c.is_whitespace() {
@@ -118,377 +496,692 @@ impl<'code> Tokenizer<'code> {
         }
     }
 
-    fn read_number(&mut self) -> Option<&'code str> {
-        let mut start = None;
-        let mut end = None;
-
-        while let Some((i, c)) = self.current {
-            if start.is_none() {
-                start = Some(i);
-            }
-
-            if !c.is_numeric() && c != '.' && c != '-' {
-                end = Some(i);
+    // Reads from the current character up to the first one that fails
+    // `part`, or to the end of the input.
+    fn read_while(&mut self, part: impl Fn(char) -> bool) -> &'code str {
+        let start = self.offset();
+        while let Some((_, c)) = self.current {
+            if !part(c) {
                 break;
             }
 
             self.advance();
         }
 
-        Some(&self.code[start?..end?])
+        &self.code[start..self.offset()]
     }
 
-    fn read_string(&mut self) -> Option<&'code str> {
-        let mut start = None;
-        let mut end = None;
+    // A number runs as far as a symbol would, so `12abc` is one malformed
+    // number rather than a number followed by a symbol.
+    fn read_number(&mut self) -> &'code str {
+        self.read_symbol()
+    }
+
+    // Whether a number starts here: a digit, a sign or `.` before one, or
+    // a radix prefix such as `#x`.
+    fn at_number(&self) -> bool {
+        let mut ahead = self.char_indices.clone().map(|(_, c)| c);
+        match self.current {
+            Some((_, c)) if c.is_ascii_digit() => true,
+            Some((_, '+' | '-')) => match ahead.next() {
+                Some('.') => ahead.next().is_some_and(|c| c.is_ascii_digit()),
+                next => next.is_some_and(|c| c.is_ascii_digit()),
+            },
+            Some((_, '.')) => ahead.next().is_some_and(|c| c.is_ascii_digit()),
+            Some((_, '#')) => matches!(
+                ahead.next(),
+                Some('x' | 'X' | 'b' | 'B' | 'o' | 'O' | 'd' | 'D')
+            ),
+            _ => false,
+        }
+    }
 
+    // Returns the text between the quotes with its escapes still in place;
+    // they are decoded by `unescape`.
+    fn read_string(&mut self) -> Result<&'code str, ParseErrorKind> {
         self.advance(); // Skip the opening quote
-        while let Some((i, c)) = self.current {
-            if start.is_none() {
-                start = Some(i);
-            }
+        let start = self.offset();
 
-            if c == '"' {
-                end = Some(i);
-                self.advance(); // Skip the closing quote
-                break;
+        loop {
+            match self.current {
+                Some((_, '"')) => break,
+                Some((_, '\\')) => {
+                    self.advance();
+                    self.advance();
+                }
+                Some(_) => {
+                    self.advance();
+                }
+                None => return Err(ParseErrorKind::UnterminatedString),
             }
-
-            self.advance();
         }
 
-        Some(&self.code[start?..end?])
+        let inner = &self.code[start..self.offset()];
+        self.advance(); // Skip the closing quote
+        Ok(inner)
     }
 
-    fn read_symbol(&mut self) -> Option<&'code str> {
-        let mut start = None;
-        let mut end = None;
+    fn read_char(&mut self) -> Result<&'code str, ParseErrorKind> {
+        self.advance(); // Skip the '#'
+        self.advance(); // Skip the backslash
 
-        while let Some((i, c)) = self.current {
-            if start.is_none() {
-                start = Some(i);
-            }
+        // The first character always belongs to the literal, so `#\(` and
+        // `#\ ` read as characters rather than delimiters.
+        let (start, _) = self.current.ok_or(ParseErrorKind::InvalidCharacter)?;
+        self.advance();
+        self.read_while(|c| !c.is_whitespace() && !is_surrounding_punctuation(c));
 
-            if c.is_whitespace() || is_surrounding_punctuation(c) {
-                end = Some(i);
-                break;
-            }
+        Ok(&self.code[start..self.offset()])
+    }
 
-            self.advance();
-        }
+    fn read_symbol(&mut self) -> &'code str {
+        self.read_while(|c| !c.is_whitespace() && !is_surrounding_punctuation(c))
+    }
 
-        Some(&self.code[start?..end?])
+    fn read_comment(&mut self) -> &'code str {
+        self.read_while(|c| c != '\n')
     }
 
-    fn read_comment(&mut self) -> Option<&'code str> {
-        let mut start = None;
-        let mut end = None;
+    // Reads a `#| ... |#` comment, which may contain other block comments.
+    fn read_block_comment(&mut self) -> Result<&'code str, ParseErrorKind> {
+        let start = self.offset();
+        let mut depth = 0;
 
-        while let Some((i, c)) = self.current {
-            if start.is_none() {
-                start = Some(i);
-            }
+        loop {
+            let Some((_, c)) = self.current else {
+                return Err(ParseErrorKind::UnterminatedComment);
+            };
+            let next = self.char_indices.clone().next().map(|(_, c)| c);
 
-            if c == '\n' {
-                end = Some(i);
-                break;
+            match (c, next) {
+                ('#', Some('|')) => depth += 1,
+                ('|', Some('#')) => depth -= 1,
+                _ => {
+                    self.advance();
+                    continue;
+                }
             }
 
             self.advance();
+            self.advance();
+            if depth == 0 {
+                return Ok(&self.code[start..self.offset()]);
+            }
         }
-
-        Some(&self.code[start?..end?])
     }
 }
 
 impl<'code> Iterator for Tokenizer<'code> {
-    type Item = Token<'code>;
+    type Item = ParseResult<(Token<'code>, Span)>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.eat_whitespace();
-        match self.current? {
-            (_, '(') => {
-                self.advance();
-                Some(Token::LParen)
-            }
-            (_, ')') => {
-                self.advance();
-                Some(Token::RParen)
-            }
-            (_, '[') => {
-                self.advance();
-                Some(Token::LBracket)
-            }
-            (_, ']') => {
-                self.advance();
-                Some(Token::RBracket)
-            }
-            (_, '{') => {
-                self.advance();
-                Some(Token::LBrace)
-            }
-            (_, '}') => {
-                self.advance();
-                Some(Token::RBrace)
-            }
-            (_, '\'') => {
-                self.advance();
-                Some(Token::Quote)
+        let (start, _) = self.current?;
+        let (line, column) = (self.line, self.column);
+
+        let token = self.token();
+        let span = Span {
+            start: start as u32,
+            end: self.offset() as u32,
+            line,
+            column,
+        };
+
+        Some(match token {
+            Ok(token) => Ok((token, span)),
+            Err(kind) => {
+                // Stop after the first error rather than reading on from the
+                // middle of a broken token.
+                self.current = None;
+                Err(ParseError::new(kind, span))
             }
-            (_, '`') => {
-                self.advance();
-                Some(Token::Quasiquote)
+        })
+    }
+}
+
+impl<'code> Tokenizer<'code> {
+    fn token(&mut self) -> Result<Token<'code>, ParseErrorKind> {
+        let Some((_, c)) = self.current else {
+            return Err(ParseErrorKind::UnexpectedEof);
+        };
+
+        let token = match c {
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '\'' => Token::Quote,
+            '`' => Token::Quasiquote,
+            ';' => return Ok(Token::Comment(self.read_comment())),
+            '#' if matches!(self.char_indices.clone().next(), Some((_, '|'))) => {
+                return self.read_block_comment().map(Token::Comment);
             }
-            (_, ';') => {
+            '#' if matches!(self.char_indices.clone().next(), Some((_, ';'))) => {
                 self.advance();
-                match self.current? {
-                    (_, ';') => self.read_comment().map(Token::Comment),
-                    _ => {
-                        panic!("Malformed comment");
-                    }
-                }
+                Token::DatumComment
             }
-            (_, '"') => self.read_string().map(Token::String),
-            (_, c) if c.is_numeric() => {
-                let val = self.read_number()?;
-                if val.contains('.') {
-                    Some(Token::Float(val))
-                } else {
-                    Some(Token::Integer(val))
-                }
+            '"' => return self.read_string().map(Token::String),
+            '#' if matches!(self.char_indices.clone().next(), Some((_, '\\'))) => {
+                return self.read_char().map(Token::Char);
             }
-            (_, c) => match c {
-                '-' => {
-                    if let Some((_, next_char)) = self.char_indices.clone().peekable().peek() && next_char.is_numeric() {
-			let val = self.read_number()?;
-			if val.contains('.') {
-			    return Some(Token::Float(val));
-			} else {
-			    return Some(Token::Integer(val));
-                        }
-                    }
-
-                    self.read_symbol().map(|s| match s {
-                        "#f" | "false" => Token::False,
-                        "#t" | "true" => Token::True,
-                        "nil" => Token::Nil,
-                        _ => Token::Symbol(s),
-                    })
-                }
-                _ => self.read_symbol().map(|s| match s {
+            _ if self.at_number() => return Ok(number_token(self.read_number())),
+            _ => {
+                return Ok(match self.read_symbol() {
                     "#f" | "false" => Token::False,
                     "#t" | "true" => Token::True,
                     "nil" => Token::Nil,
-                    _ => Token::Symbol(s),
-                }),
-            },
-        }
+                    s @ ("+inf.0" | "-inf.0" | "+nan.0" | "-nan.0") => Token::Float(s),
+                    s => Token::Symbol(s),
+                });
+            }
+        };
+
+        self.advance();
+        Ok(token)
     }
 }
 
 fn lexer<'arena>(
     arena: &'arena Arena,
     code: &'arena str,
-) -> Result<(Node<Lexeme<'arena>>, usize), &'static str> {
+) -> ParseResult<(Option<Node<Spanned<'arena>>>, usize)> {
     let mut tokens = List::new(arena);
 
     for token in tokenize(code) {
-        tokens.push_back(&token);
-    }
-
-    let tree = lex_tokens(arena, &mut tokens, false)?;
-
-    if let Lexeme::List(head, len) = tree {
-        return Ok((*head, len));
+        let token = token?;
+        tokens
+            .push_back(&token)
+            .ok_or(ParseError::new(ParseErrorKind::OutOfMemory, token.1))?;
     }
 
-    Err("The program couldn't be parsed correctly.")
+    let mut list = List::new(arena);
+    lex_tokens(arena, &mut tokens, &mut list, None)?;
+    let count = list.len();
+    Ok((list.to_node(), count))
 }
 
+// Reads forms into `list` until the closing delimiter that matches `open`,
+// returning the span of that delimiter so the caller can cover the whole
+// form. At the top level, `open` is `None` and the input must run out.
 fn lex_tokens<'arena>(
     arena: &'arena Arena<'arena>,
-    tokens: &mut List<'arena, Token<'arena>>,
-    quoted: bool,
-) -> Result<Lexeme<'arena>, &'static str> {
-    let mut list: List<'arena, Lexeme> = List::new(arena);
+    tokens: &mut List<'arena, (Token<'arena>, Span)>,
+    list: &mut List<'arena, Spanned<'arena>>,
+    open: Option<(Delimiter, Span)>,
+) -> ParseResult<Span> {
+    // `#;` comments still waiting for the form they remove, and where the
+    // last one was.
+    let mut skipped = 0;
+    let mut comment = Span::default();
 
-    while !tokens.is_empty() {
-        match tokens.pop_front() {
-            None => {
-                return Err("Not enough tokens");
-            }
-            Some(token) => match token {
-                Token::Integer(i) => {
-                    list.push_back(&Lexeme::Integer(i));
-                }
-                Token::Float(f) => {
-                    list.push_back(&Lexeme::Double(f));
-                }
-                Token::Nil => {
-                    list.push_back(&Lexeme::Null);
-                }
-                Token::True => {
-                    list.push_back(&Lexeme::True);
-                }
-                Token::False => {
-                    list.push_back(&Lexeme::False);
-                }
-                Token::String(s) => {
-                    list.push_back(&Lexeme::String(s));
-                }
-                Token::Symbol(s) => {
-                    if is_operator(s) {
-                        list.push_back(&Lexeme::Operator(s));
-                    } else {
-                        list.push_back(&Lexeme::Symbol(s, false));
-                    }
-                }
-                Token::Quote => match tokens.pop_front().expect("You can't quote nothing.") {
+    while let Some((token, span)) = tokens.pop_front().copied() {
+        let (lexeme, span) = match token {
+            Token::Quote | Token::Quasiquote => {
+                let Some((next, end)) = tokens.pop_front().copied() else {
+                    return Err(ParseError::new(ParseErrorKind::UnexpectedEof, span));
+                };
+
+                let (lexeme, end) = match next {
                     Token::LParen | Token::LBrace | Token::LBracket => {
-                        let sub_list = lex_tokens(arena, tokens, true)?;
-                        list.push_back(&sub_list);
-                    }
-                    Token::Symbol(s) => {
-                        list.push_back(&Lexeme::Symbol(s, true));
+                        lex_form(arena, tokens, next, end, true)?
                     }
-                    _ => {
-                        panic!("Unable to quote.");
+                    Token::Symbol(s) => (Lexeme::Symbol(s, true), end),
+                    Token::Quote
+                    | Token::Quasiquote
+                    | Token::RParen
+                    | Token::RBrace
+                    | Token::RBracket
+                    | Token::Comment(_)
+                    | Token::DatumComment => {
+                        return Err(ParseError::new(ParseErrorKind::InvalidQuote, span.to(end)));
                     }
-                },
-                Token::Quasiquote => {
-                    match tokens.pop_front().expect("You can't quasiquote nothing.") {
-                        Token::LParen | Token::LBrace | Token::LBracket => {
-                            let sub_list = lex_tokens(arena, tokens, true)?;
-                            list.push_back(&sub_list);
-                        }
-                        Token::Symbol(s) => {
-                            list.push_back(&Lexeme::Symbol(s, true));
-                        }
-                        _ => {
-                            panic!("Unable to quasiquote.");
-                        }
-                    }
-                }
-                Token::LParen | Token::LBrace | Token::LBracket => {
-                    let sub_list = lex_tokens(arena, tokens, false)?;
-                    list.push_back(&sub_list);
-                }
-                Token::RParen | Token::RBrace | Token::RBracket => {
-                    return make!(arena, Node<Lexeme>)
-                        .map(ArenaBox::new)
-                        .map(|mut b| {
-                            let count = list.len();
-                            if count == 0 {
-                                return Lexeme::Unit;
-                            }
-
-                            *b = list.to_node().unwrap();
-                            if quoted {
-                                Lexeme::Quoted(b, count)
-                            } else {
-                                Lexeme::List(b, count)
-                            }
-                        })
-                        .ok_or("Failed to close list");
+                    // Literals evaluate to themselves, so quoting them is
+                    // harmless.
+                    literal => (literal_lexeme(literal), end),
+                };
+                (lexeme, span.to(end))
+            }
+            Token::LParen | Token::LBrace | Token::LBracket => {
+                let (lexeme, end) = lex_form(arena, tokens, token, span, false)?;
+                (lexeme, span.to(end))
+            }
+            Token::RParen | Token::RBrace | Token::RBracket => {
+                if skipped > 0 {
+                    return Err(ParseError::new(ParseErrorKind::MalformedComment, comment));
                 }
-                Token::Comment(_) => (), // NOTE: Comments aren't used in the AST for now.
-            },
+
+                return match open {
+                    Some((open, _)) if Some(open) == delimiter(token) => Ok(span),
+                    _ => Err(ParseError::new(ParseErrorKind::UnbalancedDelimiter, span)),
+                };
+            }
+            Token::Comment(_) => continue, // Comments are kept by `cst::parse`.
+            Token::DatumComment => {
+                skipped += 1;
+                comment = span;
+                continue;
+            }
+            literal => (literal_lexeme(literal), span),
+        };
+
+        if skipped > 0 {
+            skipped -= 1;
+            continue;
         }
+
+        list.push_back(&(lexeme, span))
+            .ok_or(ParseError::new(ParseErrorKind::OutOfMemory, span))?;
+    }
+
+    match open {
+        Some((_, span)) => Err(ParseError::new(ParseErrorKind::UnexpectedEof, span)),
+        None if skipped > 0 => Err(ParseError::new(ParseErrorKind::UnexpectedEof, comment)),
+        None => Ok(Span::default()),
+    }
+}
+
+// Reads the rest of a list, vector or map whose opening delimiter was
+// `token` at `span`.
+fn lex_form<'arena>(
+    arena: &'arena Arena<'arena>,
+    tokens: &mut List<'arena, (Token<'arena>, Span)>,
+    token: Token<'arena>,
+    span: Span,
+    quoted: bool,
+) -> ParseResult<(Lexeme<'arena>, Span)> {
+    let open =
+        delimiter(token).ok_or(ParseError::new(ParseErrorKind::UnbalancedDelimiter, span))?;
+    let mut list = List::new(arena);
+    let close = lex_tokens(arena, tokens, &mut list, Some((open, span)))?;
+
+    let count = list.len();
+    let Some(node) = list.to_node() else {
+        let lexeme = match open {
+            Delimiter::Paren => Lexeme::Unit,
+            Delimiter::Bracket => Lexeme::EmptyVector,
+            Delimiter::Brace => Lexeme::EmptyMap,
+        };
+        return Ok((lexeme, close));
+    };
+
+    if open == Delimiter::Brace && !count.is_multiple_of(2) {
+        return Err(ParseError::new(
+            ParseErrorKind::OddMapLiteral,
+            span.to(close),
+        ));
     }
 
-    make!(arena, Node<Lexeme>)
-        .map(ArenaBox::new)
-        .map(|mut b| {
-            let count = list.len();
-            *b = list.to_node().unwrap();
-            Lexeme::List(b, count)
+    let b = make!(arena, Node<Spanned>)
+        .map(|b| {
+            *b = node;
+            ArenaBox::new(b)
         })
-        .ok_or("Failed to close list")
+        .ok_or(ParseError::new(ParseErrorKind::OutOfMemory, span))?;
+
+    let lexeme = match open {
+        Delimiter::Bracket => Lexeme::Vector(b, count, quoted),
+        Delimiter::Brace => Lexeme::Map(b, count, quoted),
+        Delimiter::Paren if quoted => Lexeme::Quoted(b, count),
+        Delimiter::Paren => Lexeme::List(b, count),
+    };
+    Ok((lexeme, close))
+}
+
+fn literal_lexeme(token: Token) -> Lexeme {
+    match token {
+        Token::Integer(i) => Lexeme::Integer(i),
+        Token::Rational(r) => Lexeme::Rational(r),
+        Token::Float(f) => Lexeme::Double(f),
+        Token::Nil => Lexeme::Null,
+        Token::True => Lexeme::True,
+        Token::False => Lexeme::False,
+        Token::Char(c) => Lexeme::Char(c),
+        Token::String(s) => Lexeme::String(s),
+        Token::Symbol(s) => match operator(s) {
+            Some(atom) => Lexeme::Operator(atom),
+            None => Lexeme::Symbol(s, false),
+        },
+        _ => Lexeme::Unit,
+    }
 }
 
-fn is_operator(s: &str) -> bool {
-    matches!(s, "+" | "-" | "*" | "/" | "//" | "=" | "!=" | ">" | "<" | ">=" | "<=" | "->" | "<-" | "!" | "^" | "%")
+fn delimiter(token: Token) -> Option<Delimiter> {
+    match token {
+        Token::LParen | Token::RParen => Some(Delimiter::Paren),
+        Token::LBracket | Token::RBracket => Some(Delimiter::Bracket),
+        Token::LBrace | Token::RBrace => Some(Delimiter::Brace),
+        _ => None,
+    }
+}
+
+fn operator<'a>(s: &str) -> Option<Atom<'a>> {
+    let atom = match s {
+        "+" => Atom::Add,
+        "-" => Atom::Subtract,
+        "*" => Atom::Multiply,
+        "/" => Atom::Divide,
+        "//" => Atom::Remainder,
+        "=" => Atom::Eq,
+        "!=" => Atom::Neq,
+        ">" => Atom::GT,
+        "<" => Atom::LT,
+        ">=" => Atom::GTE,
+        "<=" => Atom::LTE,
+        "->" => Atom::ArrowRight,
+        "<-" => Atom::ArrowLeft,
+        "!" => Atom::Negate,
+        "^" => Atom::Exp,
+        "%" => Atom::Mod,
+        _ => return None,
+    };
+    Some(atom)
 }
 
 fn parse_list<'arena>(
     arena: &'arena Arena<'arena>,
-    root: Node<Lexeme<'arena>>,
+    symbols: &mut SymbolTable<'arena>,
+    root: Option<&Node<Spanned<'arena>>>,
     count: usize,
     depth: usize,
-) -> Option<Array<Expression<'arena>>> {
-    make!(arena, Expression, count)
+) -> ParseResult<Array<Expression<'arena>>> {
+    let error = |kind, span| ParseError::new(kind, span);
+    let mut exprs = make!(arena, Expression, count)
         .map(Array::new)
-        .map(|mut exprs| {
-            for node in root.iter() {
-                let payload = match node {
-                    Lexeme::List(list, len) => Atom::List {
-                        body: parse_list(arena, **list, *len, depth + 1).unwrap(),
-                    },
-                    Lexeme::Quoted(list, len) => Atom::Code {
-                        body: parse_list(arena, **list, *len, depth + 1).unwrap(),
-                    },
-                    Lexeme::Symbol(name, _) => match *name {
-                        "define" | "def" => Atom::Define,
-                        "head" | "car" => Atom::Head,
-                        "tail" | "cdr" => Atom::Tail,
-                        "add" => Atom::Add,
-                        "sub" => Atom::Subtract,
-                        "mul" => Atom::Multiply,
-                        "div" => Atom::Divide,
-                        "rem" => Atom::Remainder,
-                        "eq" => Atom::Eq,
-                        "neq" => Atom::Neq,
-                        "lt" => Atom::LT,
-                        "gt" => Atom::GT,
-                        "lte" => Atom::LTE,
-                        "negate" | "neg" => Atom::Negate,
-                        "gte" => Atom::GTE,
-                        "exp" => Atom::Exp,
-                        "mod" => Atom::Mod,
-                        "cons" => Atom::Cons,
-                        _ => Atom::Symbol { name },
-                    },
-                    Lexeme::Unit => Atom::Void,
-                    Lexeme::Null => Atom::Nil,
-                    Lexeme::True => Atom::True,
-                    Lexeme::False => Atom::False,
-                    Lexeme::Integer(i) => Atom::Int {
-                        inner: i.parse().expect("Unable to parse integer value."),
-                    },
-                    Lexeme::Double(f) => Atom::Number {
-                        inner: f.parse().expect("Unable to parse floating point value."),
-                    },
-                    Lexeme::String(s) => Atom::String { inner: s },
-                    Lexeme::Operator(s) => match *s {
-                        "+" => Atom::Add,
-                        "-" => Atom::Subtract,
-                        "*" => Atom::Multiply,
-                        "/" => Atom::Divide,
-                        "//" => Atom::Remainder,
-                        "=" => Atom::Eq,
-                        "!=" => Atom::Neq,
-                        ">" => Atom::GT,
-                        "<" => Atom::LT,
-                        ">=" => Atom::GTE,
-                        "<=" => Atom::LTE,
-                        "->" => Atom::ArrowRight,
-                        "<-" => Atom::ArrowLeft,
-                        "!" => Atom::Negate,
-                        "^" => Atom::Exp,
-                        "%" => Atom::Mod,
-                        _ => panic!("Unsupported operator!"),
-                    },
+        .ok_or(error(ParseErrorKind::OutOfMemory, Span::default()))?;
+
+    for (node, span) in root.into_iter().flat_map(Node::iter) {
+        let span = *span;
+        let out_of_memory = error(ParseErrorKind::OutOfMemory, span);
+
+        let payload = match node {
+            Lexeme::List(list, len) => Atom::List {
+                body: parse_list(arena, symbols, Some(list), *len, depth + 1)?,
+            },
+            Lexeme::Quoted(list, len) => Atom::Code {
+                body: parse_list(arena, symbols, Some(list), *len, depth + 1)?,
+            },
+            Lexeme::Vector(list, len, quoted) => {
+                let mut body = parse_list(arena, symbols, Some(list), *len, depth + 1)?;
+                if *quoted {
+                    for item in body.iter_mut() {
+                        quote(arena, item).ok_or(out_of_memory)?;
+                    }
+                }
+                Atom::Vector { body }
+            }
+            Lexeme::Map(list, len, quoted) => {
+                let mut body = parse_list(arena, symbols, Some(list), *len, depth + 1)?;
+                if *quoted {
+                    for value in body.iter_mut().skip(1).step_by(2) {
+                        quote(arena, value).ok_or(out_of_memory)?;
+                    }
+                }
+                Atom::Map {
+                    map: map(arena, &body[..body.len()], span)?,
+                }
+            }
+            Lexeme::EmptyVector => Atom::Vector {
+                body: parse_list(arena, symbols, None, 0, depth + 1)?,
+            },
+            Lexeme::EmptyMap => Atom::Map {
+                map: map(arena, &[], span)?,
+            },
+            Lexeme::Symbol(name, true) => Atom::Quoted {
+                name: symbols.intern(name).ok_or(out_of_memory)?.name(),
+            },
+            Lexeme::Symbol(name, false) => match *name {
+                "define" | "def" => Atom::Define,
+                "head" | "car" => Atom::Head,
+                "tail" | "cdr" => Atom::Tail,
+                "add" => Atom::Add,
+                "sub" => Atom::Subtract,
+                "mul" => Atom::Multiply,
+                "div" => Atom::Divide,
+                "rem" => Atom::Remainder,
+                "eq" => Atom::Eq,
+                "neq" => Atom::Neq,
+                "lt" => Atom::LT,
+                "gt" => Atom::GT,
+                "lte" => Atom::LTE,
+                "negate" | "neg" => Atom::Negate,
+                "gte" => Atom::GTE,
+                "exp" => Atom::Exp,
+                "mod" => Atom::Mod,
+                "cons" => Atom::Cons,
+                _ => symbols.intern(name).ok_or(out_of_memory)?.atom(),
+            },
+            Lexeme::Unit => Atom::Void,
+            Lexeme::Null => Atom::Nil,
+            Lexeme::True => Atom::True,
+            Lexeme::False => Atom::False,
+            Lexeme::Integer(_) | Lexeme::Rational(_) | Lexeme::Double(_) => {
+                number(arena, node).map_err(|kind| error(kind, span))?
+            }
+            Lexeme::Char(c) => Atom::Char {
+                inner: chars::parse_char(c).ok_or(error(ParseErrorKind::InvalidCharacter, span))?,
+            },
+            Lexeme::String(s) => Atom::String {
+                inner: unescape(arena, s).map_err(|kind| error(kind, span))?,
+            },
+            Lexeme::Operator(atom) => *atom,
+        };
+
+        exprs
+            .push(&Expression {
+                depth,
+                payload,
+                span,
+            })
+            .ok_or(out_of_memory)?;
+    }
+
+    Ok(exprs)
+}
+
+// Builds the map read from `{...}` at `span`, pointing errors at the
+// offending key.
+// Turns `expr` into a literal that evaluates to itself as data, the way
+// everything inside a quoted list already reads: symbols stay symbols,
+// lists stay lists, and the items of vectors and the values of maps are
+// quoted in turn.
+fn quote<'arena>(arena: &'arena Arena<'arena>, expr: &mut Expression<'arena>) -> Option<()> {
+    expr.payload = match expr.payload {
+        Atom::Symbol { name } => Atom::Quoted { name },
+        Atom::List { body } => Atom::Code { body },
+        Atom::Vector { mut body } => {
+            for item in body.iter_mut() {
+                quote(arena, item)?;
+            }
+            Atom::Vector { body }
+        }
+        Atom::Map { map } => {
+            let mut values: Vec<_> = map.values().copied().collect();
+            for value in &mut values {
+                quote(arena, value)?;
+            }
+            Atom::Map {
+                map: map.with_values(arena, &values).ok()?,
+            }
+        }
+        atom => atom,
+    };
+    Some(())
+}
+
+fn map<'arena>(
+    arena: &'arena Arena<'arena>,
+    body: &[Expression<'arena>],
+    span: Span,
+) -> ParseResult<&'arena Map<'arena>> {
+    Map::build(arena, body).map_err(|error| match error {
+        MapError::OddLength => ParseError::new(ParseErrorKind::OddMapLiteral, span),
+        MapError::InvalidKey(index) => {
+            ParseError::new(ParseErrorKind::InvalidMapKey, body[index].span)
+        }
+        MapError::DuplicateKey(index) => {
+            ParseError::new(ParseErrorKind::DuplicateMapKey, body[index].span)
+        }
+        MapError::OutOfMemory => ParseError::new(ParseErrorKind::OutOfMemory, span),
+    })
+}
+
+// Decodes the escapes in the text of a string literal. Text without any is
+// returned as is; otherwise the decoded string is written to the arena,
+// where it never needs more room than the source.
+fn unescape<'arena>(
+    arena: &'arena Arena<'arena>,
+    text: &'arena str,
+) -> Result<&'arena str, ParseErrorKind> {
+    if !text.contains('\\') {
+        return Ok(text);
+    }
+
+    let buffer = make!(arena, u8, text.len()).ok_or(ParseErrorKind::OutOfMemory)?;
+    let mut used = 0;
+    let mut push = |s: &str| {
+        buffer[used..used + s.len()].copy_from_slice(s.as_bytes());
+        used += s.len();
+    };
+
+    let scalar = |hex: &str| {
+        u32::from_str_radix(hex, 16)
+            .ok()
+            .filter(|_| !hex.starts_with('+'))
+            .and_then(char::from_u32)
+            .ok_or(ParseErrorKind::InvalidEscape)
+    };
+
+    let mut rest = text;
+    while let Some(index) = rest.find('\\') {
+        push(&rest[..index]);
+        let mut escape = rest[index + 1..].chars();
+        let letter = escape.next().ok_or(ParseErrorKind::InvalidEscape)?;
+        rest = escape.as_str();
+
+        let c = match letter {
+            'x' => {
+                let (hex, tail) = rest.split_once(';').ok_or(ParseErrorKind::InvalidEscape)?;
+                rest = tail;
+                scalar(hex)?
+            }
+            'u' => {
+                let (hex, tail) = rest
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .ok_or(ParseErrorKind::InvalidEscape)?;
+                rest = tail;
+                scalar(hex)?
+            }
+            // A backslash at the end of a line joins it to the next,
+            // dropping the indentation around the line break.
+            c if c.is_whitespace() => {
+                let tail = rest.trim_start_matches([' ', '\t']);
+                let tail = match c {
+                    '\n' => tail,
+                    '\r' => tail.strip_prefix('\n').unwrap_or(tail),
+                    _ => tail
+                        .strip_prefix("\r\n")
+                        .or(tail.strip_prefix('\n'))
+                        .ok_or(ParseErrorKind::InvalidEscape)?,
                 };
-                exprs.push(&Expression { depth, payload });
+                rest = tail.trim_start_matches([' ', '\t']);
+                continue;
             }
-            exprs
-        })
+            letter => chars::unescape_char(letter).ok_or(ParseErrorKind::InvalidEscape)?,
+        };
+        push(c.encode_utf8(&mut [0; 4]));
+    }
+    push(rest);
+
+    core::str::from_utf8(&buffer[..used]).map_err(|_| ParseErrorKind::InvalidEscape)
+}
+
+fn number_token(val: &str) -> Token<'_> {
+    let radix = val.starts_with('#');
+    if val.contains('/') {
+        Token::Rational(val)
+    } else if !radix && val.contains(['.', 'e', 'E']) {
+        Token::Float(val)
+    } else {
+        Token::Integer(val)
+    }
+}
+
+fn number<'arena>(
+    arena: &'arena Arena<'arena>,
+    lexeme: &Lexeme<'arena>,
+) -> Result<Atom<'arena>, ParseErrorKind> {
+    let invalid = ParseErrorKind::InvalidNumber;
+    let inner = match lexeme {
+        Lexeme::Double("+inf.0") => f64::INFINITY,
+        Lexeme::Double("-inf.0") => f64::NEG_INFINITY,
+        Lexeme::Double("+nan.0" | "-nan.0") => f64::NAN,
+        Lexeme::Double(text) => match digits(arena, text)? {
+            (10, digits) => digits.parse().map_err(|_| invalid)?,
+            _ => return Err(invalid),
+        },
+        Lexeme::Integer(text) => {
+            let (radix, digits) = digits(arena, text)?;
+            return numeric::parse_integer_radix(arena, digits, radix).ok_or(invalid);
+        }
+        Lexeme::Rational(text) => {
+            let (radix, digits) = digits(arena, text)?;
+            return numeric::parse_rational_radix(arena, digits, radix).ok_or(invalid);
+        }
+        _ => return Err(invalid),
+    };
+    Ok(Atom::Number { inner })
+}
+
+// Splits a number into its radix and digits, dropping the `_` separators
+// allowed between two digits. Only text that has separators is copied.
+fn digits<'arena>(
+    arena: &'arena Arena<'arena>,
+    text: &'arena str,
+) -> Result<(u32, &'arena str), ParseErrorKind> {
+    let (radix, text) = match text.as_bytes() {
+        [b'#', prefix, ..] => match prefix.to_ascii_lowercase() {
+            b'x' => (16, &text[2..]),
+            b'b' => (2, &text[2..]),
+            b'o' => (8, &text[2..]),
+            b'd' => (10, &text[2..]),
+            _ => return Err(ParseErrorKind::InvalidNumber),
+        },
+        _ => (10, text),
+    };
+
+    if !text.contains('_') {
+        return Ok((radix, text));
+    }
+
+    let bytes = text.as_bytes();
+    let digit = |i: usize| bytes.get(i).is_some_and(|b| (*b as char).is_digit(radix));
+    let separated = (0..bytes.len())
+        .filter(|&i| bytes[i] == b'_')
+        .all(|i| i > 0 && digit(i - 1) && digit(i + 1));
+    if !separated {
+        return Err(ParseErrorKind::InvalidNumber);
+    }
+
+    let len = bytes.iter().filter(|b| **b != b'_').count();
+    let buffer = make!(arena, u8, len).ok_or(ParseErrorKind::OutOfMemory)?;
+    for (slot, byte) in buffer.iter_mut().zip(bytes.iter().filter(|b| **b != b'_')) {
+        *slot = *byte;
+    }
+
+    core::str::from_utf8(buffer)
+        .map(|digits| (radix, digits))
+        .map_err(|_| ParseErrorKind::InvalidNumber)
 }
 
 fn is_surrounding_punctuation(c: char) -> bool {
     c == '(' || c == ')' || c == '[' || c == ']' || c == '{' || c == '}'
 }
 
-fn tokenize<'code>(code: &'code str) -> impl Iterator<Item = Token<'code>> {
+pub(crate) fn tokenize<'code>(
+    code: &'code str,
+) -> impl Iterator<Item = ParseResult<(Token<'code>, Span)>> {
     Tokenizer::new(code)
 }
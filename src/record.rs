@@ -0,0 +1,133 @@
+use crate::read::Atom;
+use crate::{Arena, make};
+use core::cell::Cell;
+
+type RecordResult<'arena> = Result<Atom<'arena>, &'static str>;
+
+#[derive(Debug, PartialEq)]
+pub struct RecordType<'arena> {
+    pub name: &'arena str,
+    pub fields: &'arena [&'arena str],
+}
+
+// Field values live in one arena slice beside the header, so a record costs
+// two allocations no matter how many fields it has.
+#[derive(Debug, PartialEq)]
+pub struct Record<'arena> {
+    kind: &'arena RecordType<'arena>,
+    values: &'arena [Cell<Atom<'arena>>],
+}
+
+impl<'arena> RecordType<'arena> {
+    pub fn index(&self, field: &str) -> Option<usize> {
+        self.fields.iter().position(|name| *name == field)
+    }
+}
+
+impl<'arena> Record<'arena> {
+    pub fn kind(&self) -> &'arena RecordType<'arena> {
+        self.kind
+    }
+
+    pub fn type_of(&self) -> Atom<'arena> {
+        Atom::RecordType { kind: self.kind }
+    }
+
+    pub fn get(&self, index: usize) -> Option<Atom<'arena>> {
+        self.values.get(index).map(Cell::get)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = Atom<'arena>> + '_ {
+        self.values.iter().map(Cell::get)
+    }
+
+    fn is_a(&self, kind: &RecordType) -> bool {
+        core::ptr::eq(self.kind, kind)
+    }
+}
+
+pub fn record_type<'arena>(
+    arena: &'arena Arena<'arena>,
+    name: &'arena str,
+    fields: &[&'arena str],
+) -> Option<Atom<'arena>> {
+    let copy = make!(arena, &str, fields.len())?;
+    copy.copy_from_slice(fields);
+
+    make!(arena, RecordType).map(|kind| {
+        *kind = RecordType { name, fields: copy };
+        Atom::RecordType { kind }
+    })
+}
+
+pub fn make_record<'arena>(
+    arena: &'arena Arena<'arena>,
+    kind: &Atom<'arena>,
+    values: &[Atom<'arena>],
+) -> RecordResult<'arena> {
+    let Atom::RecordType { kind } = *kind else {
+        return Err("Not a record type");
+    };
+
+    if values.len() != kind.fields.len() {
+        return Err("Wrong number of record fields");
+    }
+
+    let cells = make!(arena, Cell<Atom>, values.len()).ok_or("Failed to allocate record")?;
+    for (cell, value) in cells.iter_mut().zip(values) {
+        *cell = Cell::new(*value);
+    }
+
+    make!(arena, Record)
+        .map(|record| {
+            *record = Record {
+                kind,
+                values: cells,
+            };
+            Atom::Record { record }
+        })
+        .ok_or("Failed to allocate record")
+}
+
+pub fn is_record<'arena>(kind: &Atom, value: &Atom) -> RecordResult<'arena> {
+    let Atom::RecordType { kind } = kind else {
+        return Err("Not a record type");
+    };
+
+    match value {
+        Atom::Record { record } if record.is_a(kind) => Ok(Atom::True),
+        _ => Ok(Atom::False),
+    }
+}
+
+pub fn record_ref<'arena>(kind: &Atom, value: &Atom<'arena>, index: usize) -> RecordResult<'arena> {
+    let cell = field(kind, value, index)?;
+    Ok(cell.get())
+}
+
+pub fn record_set<'arena>(
+    kind: &Atom,
+    value: &Atom<'arena>,
+    index: usize,
+    new: Atom<'arena>,
+) -> RecordResult<'arena> {
+    let cell = field(kind, value, index)?;
+    cell.set(new);
+    Ok(Atom::Void)
+}
+
+fn field<'arena>(
+    kind: &Atom,
+    value: &Atom<'arena>,
+    index: usize,
+) -> Result<&'arena Cell<Atom<'arena>>, &'static str> {
+    let (Atom::RecordType { kind }, Atom::Record { record }) = (kind, value) else {
+        return Err("Expected a record type and a record");
+    };
+
+    if !record.is_a(kind) {
+        return Err("Record has the wrong type");
+    }
+
+    record.values.get(index).ok_or("Record field out of range")
+}
@@ -0,0 +1,173 @@
+use crate::Arena;
+use crate::check::{Arity, Checker};
+use crate::env::Env;
+use crate::read::Atom;
+use core::str::FromStr;
+
+/// What a primitive can reach outside the interpreter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    Pure,
+    Io,
+    Process,
+    Clock,
+}
+
+/// A named set of capabilities: "pure" code only computes, "io" may also
+/// touch files and the console, and "full" may do anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Pure,
+    Io,
+    Full,
+}
+
+const PRIMITIVES: &[(&str, Capability, Arity)] = &[
+    ("car", Capability::Pure, Arity::Exact(1)),
+    ("cdr", Capability::Pure, Arity::Exact(1)),
+    ("list", Capability::Pure, Arity::AtLeast(0)),
+    ("null?", Capability::Pure, Arity::Exact(1)),
+    ("pair?", Capability::Pure, Arity::Exact(1)),
+    ("not", Capability::Pure, Arity::Exact(1)),
+    ("eq?", Capability::Pure, Arity::Exact(2)),
+    ("eqv?", Capability::Pure, Arity::Exact(2)),
+    ("equal?", Capability::Pure, Arity::Exact(2)),
+    ("exact", Capability::Pure, Arity::Exact(1)),
+    ("inexact", Capability::Pure, Arity::Exact(1)),
+    ("numerator", Capability::Pure, Arity::Exact(1)),
+    ("denominator", Capability::Pure, Arity::Exact(1)),
+    ("char->integer", Capability::Pure, Arity::Exact(1)),
+    ("integer->char", Capability::Pure, Arity::Exact(1)),
+    ("char-alphabetic?", Capability::Pure, Arity::Exact(1)),
+    ("string-length", Capability::Pure, Arity::Exact(1)),
+    ("string-ref", Capability::Pure, Arity::Exact(2)),
+    ("substring", Capability::Pure, Arity::Exact(3)),
+    ("string-append", Capability::Pure, Arity::AtLeast(0)),
+    ("string=?", Capability::Pure, Arity::Exact(2)),
+    ("string->list", Capability::Pure, Arity::Exact(1)),
+    ("list->string", Capability::Pure, Arity::Exact(1)),
+    ("string->symbol", Capability::Pure, Arity::Exact(1)),
+    ("symbol->string", Capability::Pure, Arity::Exact(1)),
+    ("vector?", Capability::Pure, Arity::Exact(1)),
+    ("vector-length", Capability::Pure, Arity::Exact(1)),
+    ("vector-ref", Capability::Pure, Arity::Exact(2)),
+    ("promise?", Capability::Pure, Arity::Exact(1)),
+    ("force", Capability::Pure, Arity::Exact(1)),
+    ("make-promise", Capability::Pure, Arity::Exact(1)),
+    ("coroutine?", Capability::Pure, Arity::Exact(1)),
+    ("coroutine-done?", Capability::Pure, Arity::Exact(1)),
+    ("make-coroutine", Capability::Pure, Arity::Exact(1)),
+    ("resume", Capability::Pure, Arity::AtLeast(1)),
+    ("yield", Capability::Pure, Arity::Exact(1)),
+    ("%delay", Capability::Pure, Arity::Exact(1)),
+    ("%vector-tail", Capability::Pure, Arity::Exact(2)),
+    ("%match-error", Capability::Pure, Arity::Exact(1)),
+    ("%record-type", Capability::Pure, Arity::AtLeast(1)),
+    ("%record", Capability::Pure, Arity::AtLeast(1)),
+    ("%record?", Capability::Pure, Arity::Exact(2)),
+    ("%record-ref", Capability::Pure, Arity::Exact(3)),
+    ("%record-set!", Capability::Pure, Arity::Exact(4)),
+    ("load", Capability::Io, Arity::Exact(1)),
+    ("lazyload", Capability::Io, Arity::Exact(1)),
+    ("read-file", Capability::Io, Arity::Exact(1)),
+    ("write-file", Capability::Io, Arity::Exact(2)),
+    ("read-line", Capability::Io, Arity::Exact(0)),
+    ("display", Capability::Io, Arity::Exact(1)),
+    ("newline", Capability::Io, Arity::Exact(0)),
+    ("system", Capability::Process, Arity::Exact(1)),
+    ("getenv", Capability::Process, Arity::Exact(1)),
+    ("exit", Capability::Process, Arity::AtLeast(0)),
+    ("spawn", Capability::Process, Arity::AtLeast(1)),
+    ("join", Capability::Process, Arity::Exact(1)),
+    ("current-time", Capability::Clock, Arity::Exact(0)),
+    ("current-jiffy", Capability::Clock, Arity::Exact(0)),
+    ("sleep", Capability::Clock, Arity::Exact(1)),
+];
+
+impl Preset {
+    pub fn allows(&self, capability: Capability) -> bool {
+        match self {
+            Preset::Pure => capability == Capability::Pure,
+            Preset::Io => matches!(capability, Capability::Pure | Capability::Io),
+            Preset::Full => true,
+        }
+    }
+
+    /// A root environment binding only the primitives this preset allows.
+    ///
+    /// The environment is what enforces the sandbox at run time: the
+    /// evaluator reports a primitive missing from it as forbidden, and only
+    /// loads files when `load` is bound to the primitive.
+    pub fn env<'arena>(&self, arena: &'arena Arena<'arena>) -> Result<Env<'arena>, &'static str> {
+        let mut env = Env::new(arena).ok_or("Failed to allocate environment")?;
+        for (name, _) in self.primitives(true) {
+            env.define(name, Atom::Primitive { name })
+                .ok_or("Failed to bind primitive")?;
+        }
+        Ok(env)
+    }
+
+    /// A checker that knows the allowed primitives and reports references
+    /// to the others as forbidden.
+    pub fn checker<'arena>(&self) -> Checker<'arena> {
+        let mut checker = Checker::new();
+        for (name, arity) in self.primitives(true) {
+            checker.declare(name, Some(arity));
+        }
+        for (name, _) in self.primitives(false) {
+            checker.forbid(name);
+        }
+        checker
+    }
+
+    /// Looks `name` up in a sandboxed environment, telling a primitive the
+    /// preset leaves out apart from a name that was never bound.
+    pub fn lookup<'arena>(
+        &self,
+        env: &Env<'arena>,
+        name: &str,
+    ) -> Result<Atom<'arena>, &'static str> {
+        match env.get(name) {
+            Some(value) => Ok(value),
+            None if capability(name).is_some_and(|c| !self.allows(c)) => Err("Forbidden primitive"),
+            None => Err("Unbound variable"),
+        }
+    }
+
+    fn primitives(&self, allowed: bool) -> impl Iterator<Item = (&'static str, Arity)> + '_ {
+        PRIMITIVES
+            .iter()
+            .filter(move |(_, capability, _)| self.allows(*capability) == allowed)
+            .map(|(name, _, arity)| (*name, *arity))
+    }
+}
+
+impl FromStr for Preset {
+    type Err = &'static str;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "pure" => Ok(Preset::Pure),
+            "io" => Ok(Preset::Io),
+            "full" => Ok(Preset::Full),
+            _ => Err("Unknown sandbox preset"),
+        }
+    }
+}
+
+/// The capability a primitive needs, or `None` if `name` is not one.
+pub fn capability(name: &str) -> Option<Capability> {
+    PRIMITIVES
+        .iter()
+        .find(|(primitive, _, _)| *primitive == name)
+        .map(|(_, capability, _)| *capability)
+}
+
+/// The number of arguments a primitive takes, or `None` if `name` is not
+/// one.
+pub fn arity(name: &str) -> Option<Arity> {
+    PRIMITIVES
+        .iter()
+        .find(|(primitive, _, _)| *primitive == name)
+        .map(|(_, _, arity)| *arity)
+}
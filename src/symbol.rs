@@ -0,0 +1,167 @@
+use crate::read::{Atom, Expression};
+use crate::{Arena, HashMap, make};
+use core::hash::{Hash, Hasher};
+
+const BUCKETS: usize = 256;
+
+/// An interned symbol name. Every name is stored once per table, so two
+/// symbols are the same exactly when they point at the same bytes, and the
+/// address doubles as the symbol's id.
+#[derive(Debug, Clone, Copy)]
+pub struct Symbol<'arena> {
+    name: &'arena str,
+}
+
+#[derive(Debug)]
+pub struct SymbolTable<'arena> {
+    arena: &'arena Arena<'arena>,
+    symbols: HashMap<'arena, &'arena str, Symbol<'arena>>,
+    // The same symbols by address, so a name that was already interned is
+    // found without hashing its bytes.
+    addresses: HashMap<'arena, usize, Symbol<'arena>>,
+}
+
+impl<'arena> Symbol<'arena> {
+    pub fn name(&self) -> &'arena str {
+        self.name
+    }
+
+    pub fn id(&self) -> usize {
+        self.name.as_ptr() as usize
+    }
+
+    pub fn atom(&self) -> Atom<'arena> {
+        Atom::Symbol { name: self.name }
+    }
+}
+
+impl PartialEq for Symbol<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.name, other.name)
+    }
+}
+
+impl Eq for Symbol<'_> {}
+
+impl Hash for Symbol<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.id());
+    }
+}
+
+impl<'arena> SymbolTable<'arena> {
+    pub fn new(arena: &'arena Arena<'arena>) -> Self {
+        Self::with_capacity(arena, BUCKETS)
+    }
+
+    pub fn with_capacity(arena: &'arena Arena<'arena>, buckets: usize) -> Self {
+        Self::try_with_capacity(arena, buckets).expect("Failed to allocate symbol table")
+    }
+
+    /// Like `with_capacity`, but returns `None` if the arena is out of
+    /// memory.
+    pub fn try_with_capacity(arena: &'arena Arena<'arena>, buckets: usize) -> Option<Self> {
+        Some(SymbolTable {
+            arena,
+            symbols: HashMap::try_new(arena, buckets.max(1))?,
+            addresses: HashMap::try_new(arena, buckets.max(1))?,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Returns the symbol for `name`, copying the name into the arena the
+    /// first time it is seen.
+    pub fn intern(&mut self, name: &str) -> Option<Symbol<'arena>> {
+        if let Some(symbol) = self.get(name) {
+            return Some(symbol);
+        }
+
+        let arena = self.arena;
+        let bytes = make!(arena, u8, name.len())?;
+        bytes.copy_from_slice(name.as_bytes());
+        let name = core::str::from_utf8(bytes).ok()?;
+
+        let symbol = Symbol { name };
+        self.addresses.insert(&symbol.id(), &symbol)?;
+        self.symbols.insert(&name, &symbol).copied()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Symbol<'arena>> {
+        match self.addresses.find(&(name.as_ptr() as usize)) {
+            Some(symbol) if symbol.name.len() == name.len() => Some(*symbol),
+            _ => self.symbols.find(name).copied(),
+        }
+    }
+
+    /// Interns the name of every symbol in `code`, in place, so that names
+    /// made after reading, such as those the expander introduces, share
+    /// this table's addresses too.
+    pub fn resolve(&mut self, code: &mut [Expression<'arena>]) -> Option<()> {
+        for expr in code {
+            match &mut expr.payload {
+                Atom::Symbol { name } | Atom::Quoted { name } => {
+                    *name = self.intern(name)?.name;
+                }
+                Atom::List { body } | Atom::Vector { body } | Atom::Code { body } => {
+                    self.resolve(body)?;
+                }
+                _ => {}
+            }
+        }
+        Some(())
+    }
+
+    /// The symbol an atom read through this table refers to.
+    pub fn symbol(&self, atom: &Atom) -> Option<Symbol<'arena>> {
+        name(atom).and_then(|name| self.get(name))
+    }
+}
+
+pub fn string_to_symbol<'arena>(
+    symbols: &mut SymbolTable<'arena>,
+    s: &Atom,
+) -> Result<Atom<'arena>, &'static str> {
+    let Atom::String { inner } = s else {
+        return Err("Not a string");
+    };
+
+    symbols
+        .intern(inner)
+        .map(|symbol| symbol.atom())
+        .ok_or("Failed to intern symbol")
+}
+
+pub fn symbol_to_string<'arena>(
+    symbols: &SymbolTable<'arena>,
+    s: &Atom,
+) -> Result<Atom<'arena>, &'static str> {
+    symbols
+        .symbol(s)
+        .map(|symbol| Atom::String {
+            inner: symbol.name(),
+        })
+        .ok_or("Not an interned symbol")
+}
+
+/// `eq?` on symbols: interned names are compared by address only, so this
+/// only holds for symbols read, expanded or made through the same table.
+pub fn eq<'arena>(a: &Atom, b: &Atom) -> Atom<'arena> {
+    match (name(a), name(b)) {
+        (Some(a), Some(b)) if core::ptr::eq(a, b) => Atom::True,
+        _ => Atom::False,
+    }
+}
+
+fn name<'a>(atom: &Atom<'a>) -> Option<&'a str> {
+    match *atom {
+        Atom::Symbol { name } | Atom::Quoted { name } => Some(name),
+        _ => None,
+    }
+}
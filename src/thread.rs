@@ -0,0 +1,177 @@
+use crate::env::Env;
+use crate::eval::Interpreter;
+use crate::image::{Decoder, Encoder, can_write, restore, snapshot, snapshot_lossy};
+use crate::pair;
+use crate::read::{Atom, Expression};
+use crate::{Arena, Array, MemoryBlock, make};
+use core::cell::Cell;
+use core::fmt::{Debug, Formatter};
+use std::thread::{JoinHandle, Scope, ScopedJoinHandle};
+
+// Each task maps a block of its own. The task's arena spans all of it, but
+// pages are only committed as the task touches them.
+const TASK_MEMORY: usize = 256 * 1024 * 1024;
+
+// The bindings a task's call and its result travel under in an image.
+const CALL: &str = "%call";
+const RESULT: &str = "%result";
+
+// Expressions point into the arena that produced them and `Array` hands out
+// mutable access through copies, so they are never shared between threads.
+// A parcel carries an owned, position-independent copy instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parcel {
+    data: Vec<u8>,
+}
+
+type WorkerResult = Result<Parcel, &'static str>;
+
+impl Parcel {
+    pub fn pack(exprs: &[Expression]) -> Self {
+        let mut data = Vec::new();
+        Encoder::new(&mut data)
+            .expressions(exprs)
+            .expect("Writing to a Vec cannot fail");
+
+        Parcel { data }
+    }
+
+    pub fn unpack<'arena>(
+        &self,
+        arena: &'arena Arena<'arena>,
+    ) -> Result<Array<Expression<'arena>>, &'static str> {
+        let mut decoder = Decoder::new(arena, &self.data)?;
+        let exprs = decoder.expressions()?;
+        decoder.finish()?;
+        Ok(exprs)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+/// Runs `worker` on a new scoped thread that owns `arena`. The input parcel
+/// is unpacked into that arena and whatever the worker returns is packed
+/// again, so nothing allocated by the thread escapes it.
+pub fn spawn<'scope, 'env, F>(
+    scope: &'scope Scope<'scope, 'env>,
+    arena: Arena<'env>,
+    input: Parcel,
+    worker: F,
+) -> ScopedJoinHandle<'scope, WorkerResult>
+where
+    F: for<'a> FnOnce(
+            &'a Arena<'a>,
+            Array<Expression<'a>>,
+        ) -> Result<Array<Expression<'a>>, &'static str>
+        + Send
+        + 'scope,
+{
+    scope.spawn(move || {
+        let exprs = input.unpack(&arena)?;
+        let output = worker(&arena, exprs)?;
+        Ok(Parcel::pack(&output[..output.len()]))
+    })
+}
+
+/// A procedure call running on a thread of its own, started by `spawn`.
+/// The thread restores an image of the caller's environment into a block
+/// it owns, so caller and task share nothing but the images passed each
+/// way, and a value the task returns is copied back by `join`.
+pub struct Task {
+    handle: Cell<Option<JoinHandle<WorkerResult>>>,
+}
+
+impl Task {
+    /// Starts calling `procedure` with `args` on a new thread. Everything
+    /// visible from `env` is copied along, so the procedure may close over
+    /// `env` and its parents but not over a local frame. Bindings that
+    /// cannot be copied, such as other tasks, are nil in the copy.
+    pub fn start<'arena>(
+        arena: &'arena Arena<'arena>,
+        env: &Env<'arena>,
+        procedure: Atom<'arena>,
+        args: &[Atom<'arena>],
+    ) -> Result<Atom<'arena>, &'static str> {
+        let mut call = vec![procedure];
+        call.extend_from_slice(args);
+        let call = pair::list(arena, &call)?;
+        if !can_write(env, &call) {
+            return Err("Unable to send procedure");
+        }
+
+        let mut frame = Env::extend(env, 1).ok_or("Failed to allocate frame")?;
+        frame.define(CALL, call).ok_or("Failed to allocate frame")?;
+
+        let mut data = Vec::new();
+        snapshot_lossy(&frame, &mut data).map_err(|_| "Unable to send procedure")?;
+        let block =
+            MemoryBlock::try_with_capacity(TASK_MEMORY).ok_or("Failed to allocate task memory")?;
+        let handle = std::thread::spawn(move || run(block, Parcel { data }));
+
+        let task = make!(arena, Task).ok_or("Failed to allocate task")?;
+        // The zeroed memory is not a valid handle, so it must not be dropped.
+        unsafe {
+            core::ptr::write(
+                task,
+                Task {
+                    handle: Cell::new(Some(handle)),
+                },
+            );
+        }
+        Ok(Atom::Task { task })
+    }
+
+    /// Waits for the task to finish and copies the value it returned into
+    /// `arena`. A task can only be joined once.
+    pub fn join<'arena>(&self, arena: &'arena Arena<'arena>) -> Result<Atom<'arena>, &'static str> {
+        let handle = self.handle.take().ok_or("Task already joined")?;
+        let parcel = handle.join().map_err(|_| "Task panicked")??;
+
+        let env = restore(arena, &parcel.data)?;
+        env.get(RESULT).ok_or("Invalid task result")
+    }
+}
+
+impl Debug for Task {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Task").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Task {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self, other)
+    }
+}
+
+// The body of a task's thread: restores the caller's environment, makes
+// the call and sends back an image holding only the result, so a closure
+// returned by the task is rejected rather than copying its environment.
+fn run(block: MemoryBlock, image: Parcel) -> WorkerResult {
+    let arena = block
+        .arena(TASK_MEMORY)
+        .ok_or("Failed to allocate task memory")?;
+
+    let env = restore(&arena, &image.data)?;
+    let call = env.get(CALL).ok_or("Invalid task image")?;
+    let mut items = pair::items(call);
+    let procedure = items.next().ok_or("Invalid task image")?;
+    let args: Vec<_> = items.collect();
+
+    let value = Interpreter::new(&arena, env).apply(procedure, &args)?;
+
+    let mut result = Env::new(&arena).ok_or("Failed to allocate environment")?;
+    result
+        .define(RESULT, value)
+        .ok_or("Failed to allocate environment")?;
+
+    let mut data = Vec::new();
+    snapshot(&result, &mut data).map_err(|_| "Unable to send value")?;
+    Ok(Parcel { data })
+}
@@ -0,0 +1,619 @@
+use crate::check::{Diagnostic, DiagnosticKind, Pass, Scope};
+use crate::expand::{annotated, is_colon};
+use crate::read::{Atom, Expression};
+use crate::{Arena, List, make};
+use core::fmt::{Display, Formatter};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Type<'arena> {
+    Any,
+    Int,
+    Float,
+    Number,
+    Bool,
+    String,
+    Char,
+    Symbol,
+    List,
+    Vector,
+    Map,
+    Record,
+    Function {
+        params: &'arena [Type<'arena>],
+        ret: &'arena Type<'arena>,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct TypeChecker<'arena> {
+    globals: HashMap<&'arena str, Type<'arena>>,
+}
+
+// What a pass knows about a name: the type of its value, and whether an
+// annotation declared that type. Only declared types hold for `set!`; an
+// inferred one is just the type of the value it started with.
+#[derive(Debug, Clone, Copy)]
+struct Binding<'arena> {
+    kind: Type<'arena>,
+    declared: bool,
+}
+
+type Types<'s, 'arena> = Scope<'s, 'arena, Binding<'arena>>;
+
+impl<'arena> Type<'arena> {
+    /// Whether a value of type `found` may be used where `self` is expected.
+    /// `Any` is consistent with every type, which is what lets annotated and
+    /// un-annotated code call each other.
+    pub fn accepts(&self, found: &Type) -> bool {
+        match (self, found) {
+            (Type::Any, _) | (_, Type::Any) => true,
+            (Type::Number, Type::Int | Type::Float) => true,
+            (
+                Type::Function { params, ret },
+                Type::Function {
+                    params: other,
+                    ret: other_ret,
+                },
+            ) => {
+                params.len() == other.len()
+                    && params.iter().zip(other.iter()).all(|(p, o)| o.accepts(p))
+                    && ret.accepts(other_ret)
+            }
+            _ => self == found,
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(self, Type::Int | Type::Float | Type::Number)
+    }
+
+    fn join(self, other: Type<'arena>) -> Type<'arena> {
+        if self == other {
+            self
+        } else if self.is_numeric() && other.is_numeric() {
+            Type::Number
+        } else {
+            Type::Any
+        }
+    }
+}
+
+impl<'arena> TypeChecker<'arena> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn declare(&mut self, name: &'arena str, kind: Type<'arena>) {
+        self.globals.insert(name, kind);
+    }
+
+    /// Infers local types in `root` and reports where a value cannot have
+    /// the type its annotation or operator requires. Anything without an
+    /// annotation is typed `Any` and never reported.
+    pub fn check(
+        &self,
+        arena: &'arena Arena<'arena>,
+        root: &[Expression<'arena>],
+    ) -> List<'arena, Diagnostic<'arena>> {
+        let mut pass = Pass::new(arena, self);
+        let mut scope = Scope::default();
+        pass.body(&mut scope, root);
+        pass.diagnostics
+    }
+}
+
+impl<'arena> Binding<'arena> {
+    fn declared(kind: Type<'arena>) -> Self {
+        Binding {
+            kind,
+            declared: true,
+        }
+    }
+
+    fn inferred(kind: Type<'arena>) -> Self {
+        Binding {
+            kind,
+            declared: false,
+        }
+    }
+}
+
+impl<'c, 'arena> Pass<'c, 'arena, TypeChecker<'arena>> {
+    fn expect(&mut self, expected: Type<'arena>, found: Type<'arena>, expr: &Expression<'arena>) {
+        if !expected.accepts(&found) {
+            self.report(DiagnosticKind::TypeMismatch { expected, found }, expr);
+        }
+    }
+
+    fn resolve(&self, scope: &Types<'_, 'arena>, name: &str) -> Type<'arena> {
+        self.binding(scope, name).kind
+    }
+
+    // Globals declared on the checker count as annotated.
+    fn binding(&self, scope: &Types<'_, 'arena>, name: &str) -> Binding<'arena> {
+        scope
+            .lookup(name)
+            .or_else(|| {
+                let kind = *self.checker.globals.get(name)?;
+                Some(Binding::declared(kind))
+            })
+            .unwrap_or(Binding::inferred(Type::Any))
+    }
+
+    // Signatures are collected first so that calls in the body are checked
+    // against functions defined after them.
+    fn body(
+        &mut self,
+        scope: &mut Types<'_, 'arena>,
+        exprs: &[Expression<'arena>],
+    ) -> Type<'arena> {
+        for expr in exprs {
+            if let Some((name, binding)) = self.signature(expr) {
+                scope.bindings.insert(name, binding);
+            }
+        }
+
+        let mut last = Type::Any;
+        for expr in exprs {
+            last = match definition(expr) {
+                Some(args) => {
+                    self.define(scope, expr, args);
+                    Type::Any
+                }
+                None => self.infer(scope, expr),
+            };
+        }
+        last
+    }
+
+    // Annotations are read again when the definition itself is checked, so
+    // any problems with them are only reported then.
+    fn signature(&mut self, expr: &Expression<'arena>) -> Option<(&'arena str, Binding<'arena>)> {
+        let mark = self.diagnostics.len();
+        let signature = self.hoist(expr);
+        while self.diagnostics.len() > mark {
+            self.diagnostics.pop_back();
+        }
+        signature
+    }
+
+    fn hoist(&mut self, expr: &Expression<'arena>) -> Option<(&'arena str, Binding<'arena>)> {
+        let args = definition(expr)?;
+        let declared = declares(expr);
+
+        match args.first()?.payload {
+            Atom::Symbol { name } => match args {
+                [_, colon, annotation, _] if is_colon(colon) => {
+                    Some((name, Binding::declared(self.annotation(annotation))))
+                }
+                _ => Some((name, Binding::inferred(Type::Any))),
+            },
+            Atom::List { body } => {
+                let body = &body[..body.len()];
+                let Atom::Symbol { name } = body.first()?.payload else {
+                    return None;
+                };
+                let params = self.params(&mut Scope::default(), &body[1..]);
+                let ret = match args {
+                    [_, colon, annotation, ..] if is_colon(colon) => self.annotation(annotation),
+                    _ => Type::Any,
+                };
+                let kind = self.function(params, ret)?;
+                Some((name, Binding { kind, declared }))
+            }
+            _ => None,
+        }
+    }
+
+    fn define(
+        &mut self,
+        scope: &mut Types<'_, 'arena>,
+        expr: &Expression<'arena>,
+        args: &[Expression<'arena>],
+    ) {
+        match args.first().map(|e| e.payload) {
+            Some(Atom::Symbol { name }) => match args {
+                [_, colon, annotation, value] if is_colon(colon) => {
+                    let expected = self.annotation(annotation);
+                    let found = self.infer(scope, value);
+                    self.expect(expected, found, value);
+                    scope.bindings.insert(name, Binding::declared(expected));
+                }
+                [_, value] => {
+                    let found = self.infer(scope, value);
+                    scope.bindings.insert(name, Binding::inferred(found));
+                }
+                _ => {}
+            },
+            Some(Atom::List { body: signature }) => {
+                let signature = &signature[..signature.len()];
+                let (ret, body) = match args {
+                    [_, colon, annotation, body @ ..] if is_colon(colon) => {
+                        (self.annotation(annotation), body)
+                    }
+                    [_, body @ ..] => (Type::Any, body),
+                    _ => return,
+                };
+
+                let mut inner = Scope::extend(scope);
+                self.params(&mut inner, &signature[1..]);
+                let found = self.body(&mut inner, body);
+                self.expect(ret, found, body.last().unwrap_or(expr));
+            }
+            _ => {}
+        }
+    }
+
+    fn infer(&mut self, scope: &Types<'_, 'arena>, expr: &Expression<'arena>) -> Type<'arena> {
+        match expr.payload {
+            Atom::Int { .. } | Atom::BigInt { .. } => Type::Int,
+            Atom::Number { .. } => Type::Float,
+            Atom::Rational { .. } => Type::Number,
+            Atom::True | Atom::False => Type::Bool,
+            Atom::String { .. } => Type::String,
+            Atom::Char { .. } => Type::Char,
+            Atom::Quoted { .. } => Type::Symbol,
+            Atom::Void | Atom::Code { .. } => Type::List,
+            Atom::Record { .. } => Type::Record,
+            Atom::Symbol { name } => self.resolve(scope, name),
+            Atom::Vector { ref body } => {
+                for item in &body[..body.len()] {
+                    self.infer(scope, item);
+                }
+                Type::Vector
+            }
+            Atom::Map { map } => {
+                for value in map.values() {
+                    self.infer(scope, value);
+                }
+                Type::Map
+            }
+            Atom::List { ref body } => self.form(scope, expr, &body[..body.len()]),
+            _ => Type::Any,
+        }
+    }
+
+    fn form(
+        &mut self,
+        scope: &Types<'_, 'arena>,
+        expr: &Expression<'arena>,
+        body: &[Expression<'arena>],
+    ) -> Type<'arena> {
+        let (head, args) = match body.split_first() {
+            Some(split) => split,
+            None => return Type::Any,
+        };
+
+        match head.payload {
+            Atom::Define => {
+                let mut inner = Scope::extend(scope);
+                self.define(&mut inner, expr, args);
+                Type::Any
+            }
+            Atom::Add | Atom::Subtract | Atom::Multiply | Atom::Mod | Atom::Remainder => {
+                let types = self.numbers(scope, args);
+                arithmetic(&types)
+            }
+            Atom::Divide | Atom::Exp => {
+                let types = self.numbers(scope, args);
+                match types.contains(&Type::Float) {
+                    true => Type::Float,
+                    false => Type::Number,
+                }
+            }
+            Atom::LT | Atom::GT | Atom::LTE | Atom::GTE => {
+                self.numbers(scope, args);
+                Type::Bool
+            }
+            Atom::Eq | Atom::Neq | Atom::Negate => {
+                self.each(scope, args);
+                Type::Bool
+            }
+            Atom::Cons => {
+                self.each(scope, args);
+                Type::List
+            }
+            Atom::Symbol { name } => match name {
+                "quote" | "quasiquote" => Type::Any,
+                "lambda" => self.lambda(scope, args),
+                "if" => {
+                    self.each(scope, args.get(..1).unwrap_or_default());
+                    let branches = args.get(1..).unwrap_or_default();
+                    let types: Vec<_> = branches.iter().map(|b| self.infer(scope, b)).collect();
+                    match types.as_slice() {
+                        [then, otherwise] => then.join(*otherwise),
+                        _ => Type::Any,
+                    }
+                }
+                "begin" => {
+                    let mut inner = Scope::extend(scope);
+                    self.body(&mut inner, args)
+                }
+                "let" | "let*" | "letrec" | "letrec*" => self.let_form(scope, name, args),
+                "set!" => {
+                    if let [target, value] = args {
+                        let found = self.infer(scope, value);
+                        if let Atom::Symbol { name } = target.payload
+                            && let Binding {
+                                kind,
+                                declared: true,
+                            } = self.binding(scope, name)
+                        {
+                            self.expect(kind, found, value);
+                        }
+                    }
+                    Type::Any
+                }
+                _ => match self.resolve(scope, name) {
+                    Type::Function { params, ret } => {
+                        let found: Vec<_> = args.iter().map(|a| self.infer(scope, a)).collect();
+                        if params.len() == found.len() {
+                            for ((expected, found), arg) in params.iter().zip(found).zip(args) {
+                                self.expect(*expected, found, arg);
+                            }
+                        }
+                        *ret
+                    }
+                    _ => {
+                        self.each(scope, args);
+                        Type::Any
+                    }
+                },
+            },
+            _ => {
+                self.each(scope, body);
+                Type::Any
+            }
+        }
+    }
+
+    fn lambda(&mut self, scope: &Types<'_, 'arena>, args: &[Expression<'arena>]) -> Type<'arena> {
+        let Some((formals, rest)) = args.split_first() else {
+            return Type::Any;
+        };
+        let (ret, body) = match rest {
+            [colon, annotation, body @ ..] if is_colon(colon) => {
+                (self.annotation(annotation), body)
+            }
+            _ => (Type::Any, rest),
+        };
+
+        let mut inner = Scope::extend(scope);
+        let params = match formals.payload {
+            Atom::List { ref body } => self.params(&mut inner, &body[..body.len()]),
+            Atom::Void => Some(Vec::new()),
+            _ => None,
+        };
+
+        let found = self.body(&mut inner, body);
+        if let Some(last) = body.last() {
+            self.expect(ret, found, last);
+        }
+
+        params
+            .and_then(|params| self.function(Some(params), ret))
+            .unwrap_or(Type::Any)
+    }
+
+    fn let_form(
+        &mut self,
+        scope: &Types<'_, 'arena>,
+        form: &str,
+        args: &[Expression<'arena>],
+    ) -> Type<'arena> {
+        let mut outer = Scope::extend(scope);
+        let args = match args.first().map(|e| e.payload) {
+            Some(Atom::Symbol { name }) => {
+                outer.bindings.insert(name, Binding::inferred(Type::Any));
+                &args[1..]
+            }
+            _ => args,
+        };
+
+        let Some((bindings, body)) = args.split_first() else {
+            return Type::Any;
+        };
+
+        let mut inner = Scope::extend(&outer);
+        if let Atom::List { body: bindings } = bindings.payload {
+            for binding in &bindings[..bindings.len()] {
+                let Atom::List { body: pair } = binding.payload else {
+                    continue;
+                };
+                let (Atom::Symbol { name }, Some(value)) = (pair[0].payload, pair.get(1)) else {
+                    continue;
+                };
+
+                let found = match form {
+                    "let" => self.infer(&outer, value),
+                    _ => self.infer(&inner, value),
+                };
+                inner.bindings.insert(name, Binding::inferred(found));
+            }
+        }
+
+        self.body(&mut inner, body)
+    }
+
+    // Binds each parameter in `scope` and returns the parameter types of
+    // the function, or `None` for variadic formals, which are left untyped.
+    fn params(
+        &mut self,
+        scope: &mut Types<'_, 'arena>,
+        params: &[Expression<'arena>],
+    ) -> Option<Vec<Type<'arena>>> {
+        let mut types = Vec::with_capacity(params.len());
+        let mut rest = false;
+
+        for param in params {
+            let (name, binding) = match param.payload {
+                Atom::Symbol { name: "." } => {
+                    rest = true;
+                    continue;
+                }
+                Atom::Symbol { name } if rest => (name, Binding::inferred(Type::List)),
+                Atom::Symbol { name } => (name, Binding::inferred(Type::Any)),
+                Atom::List { ref body } => match &body[..body.len()] {
+                    [name, colon, annotation] if is_colon(colon) => match name.payload {
+                        Atom::Symbol { name } => {
+                            (name, Binding::declared(self.annotation(annotation)))
+                        }
+                        _ => continue,
+                    },
+                    _ => {
+                        self.report(DiagnosticKind::Malformed { form: "parameter" }, param);
+                        continue;
+                    }
+                },
+                _ => continue,
+            };
+
+            scope.bindings.insert(name, binding);
+            types.push(binding.kind);
+        }
+
+        (!rest).then_some(types)
+    }
+
+    fn function(
+        &self,
+        params: Option<Vec<Type<'arena>>>,
+        ret: Type<'arena>,
+    ) -> Option<Type<'arena>> {
+        let Some(params) = params else {
+            return Some(Type::Any);
+        };
+
+        let arena = self.arena;
+        let slice = make!(arena, Type, params.len())?;
+        slice.copy_from_slice(&params);
+        let boxed = make!(arena, Type)?;
+        *boxed = ret;
+
+        Some(Type::Function {
+            params: slice,
+            ret: boxed,
+        })
+    }
+
+    fn annotation(&mut self, expr: &Expression<'arena>) -> Type<'arena> {
+        match expr.payload {
+            Atom::Symbol { name } => match name {
+                "Any" => Type::Any,
+                "Int" => Type::Int,
+                "Float" => Type::Float,
+                "Number" => Type::Number,
+                "Bool" => Type::Bool,
+                "String" => Type::String,
+                "Char" => Type::Char,
+                "Symbol" => Type::Symbol,
+                "List" => Type::List,
+                "Vector" => Type::Vector,
+                "Map" => Type::Map,
+                "Record" => Type::Record,
+                _ => {
+                    self.report(DiagnosticKind::UnknownType { name }, expr);
+                    Type::Any
+                }
+            },
+            // (-> Int Int Bool) is a function of two integers returning a bool.
+            Atom::List { ref body } if body[0].payload == Atom::ArrowRight => {
+                let body = &body[1..body.len()];
+                let Some((ret, params)) = body.split_last() else {
+                    return Type::Any;
+                };
+                let params = params.iter().map(|p| self.annotation(p)).collect();
+                let ret = self.annotation(ret);
+                self.function(Some(params), ret).unwrap_or(Type::Any)
+            }
+            _ => {
+                self.report(DiagnosticKind::Malformed { form: "type" }, expr);
+                Type::Any
+            }
+        }
+    }
+
+    fn numbers(
+        &mut self,
+        scope: &Types<'_, 'arena>,
+        args: &[Expression<'arena>],
+    ) -> Vec<Type<'arena>> {
+        let mut types = Vec::with_capacity(args.len());
+        for arg in args {
+            let found = self.infer(scope, arg);
+            self.expect(Type::Number, found, arg);
+            types.push(if found.is_numeric() {
+                found
+            } else {
+                Type::Number
+            });
+        }
+        types
+    }
+
+    fn each(&mut self, scope: &Types<'_, 'arena>, exprs: &[Expression<'arena>]) {
+        for expr in exprs {
+            self.infer(scope, expr);
+        }
+    }
+}
+
+impl Display for Type<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Type::Any => write!(f, "Any"),
+            Type::Int => write!(f, "Int"),
+            Type::Float => write!(f, "Float"),
+            Type::Number => write!(f, "Number"),
+            Type::Bool => write!(f, "Bool"),
+            Type::String => write!(f, "String"),
+            Type::Char => write!(f, "Char"),
+            Type::Symbol => write!(f, "Symbol"),
+            Type::List => write!(f, "List"),
+            Type::Vector => write!(f, "Vector"),
+            Type::Map => write!(f, "Map"),
+            Type::Record => write!(f, "Record"),
+            Type::Function { params, ret } => {
+                write!(f, "(->")?;
+                for param in params.iter() {
+                    write!(f, " {param}")?;
+                }
+                write!(f, " {ret})")
+            }
+        }
+    }
+}
+
+// Integers stay integers and any float makes the result a float; anything
+// else, such as a rational, is only known to be a number.
+fn arithmetic<'arena>(types: &[Type<'arena>]) -> Type<'arena> {
+    if types.contains(&Type::Float) {
+        Type::Float
+    } else if types.iter().all(|t| *t == Type::Int) {
+        Type::Int
+    } else {
+        Type::Number
+    }
+}
+
+// Whether a definition carries any annotation, on its name, its result or
+// one of its parameters.
+fn declares(expr: &Expression) -> bool {
+    match expr.payload {
+        Atom::List { ref body } => annotated(&body[..body.len()]),
+        _ => false,
+    }
+}
+
+fn definition<'e, 'arena>(expr: &'e Expression<'arena>) -> Option<&'e [Expression<'arena>]> {
+    let Atom::List { ref body } = expr.payload else {
+        return None;
+    };
+    let body = &body[..body.len()];
+
+    match body.first()?.payload {
+        Atom::Define => Some(&body[1..]),
+        _ => None,
+    }
+}
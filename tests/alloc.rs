@@ -5,11 +5,26 @@ const TEST_CAPACITY: usize = 1024;
 #[test]
 fn test_block_with_capacity() {
     let block = MemoryBlock::with_capacity(TEST_CAPACITY);
-    assert!(block.buffer() != core::ptr::null_mut());
+    assert!(!block.buffer().is_null());
     assert_eq!(block.len(), 0);
     assert_eq!(block.capacity(), 1024);
 }
 
+#[test]
+fn test_block_try_with_capacity() {
+    assert!(MemoryBlock::try_with_capacity(TEST_CAPACITY).is_some());
+    assert!(MemoryBlock::try_with_capacity(usize::MAX).is_none());
+}
+
+#[test]
+fn test_block_arena_after_write() {
+    let mut block = MemoryBlock::with_capacity(TEST_CAPACITY);
+    block[0] = 7;
+    let arena = block.arena(TEST_CAPACITY).unwrap();
+    let value = make!(arena, u8).unwrap();
+    assert_eq!(*value, 0);
+}
+
 #[test]
 fn test_block_deref() {
     let block = MemoryBlock::with_capacity(TEST_CAPACITY);
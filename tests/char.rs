@@ -0,0 +1,134 @@
+use tyson::MemoryBlock as Block;
+use tyson::chars::{
+    char_alphabetic, char_to_integer, integer_to_char, list_to_string, string_ref, string_to_list,
+};
+use tyson::env::Env;
+use tyson::eval::Interpreter;
+use tyson::image::{restore, snapshot};
+use tyson::print::{print, print_value};
+use tyson::read::{Atom, parse};
+use tyson::sandbox::Preset;
+
+#[test]
+fn test_read_char_literals() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(#\\a #\\space #\\newline #\\( #\\x41 #\\λ)").unwrap();
+    let Atom::List { body } = root[0].payload else {
+        panic!("expected a list");
+    };
+
+    let chars: Vec<_> = body[..body.len()].iter().map(|e| e.payload).collect();
+    assert_eq!(
+        chars,
+        ['a', ' ', '\n', '(', 'A', 'λ'].map(|inner| Atom::Char { inner })
+    );
+}
+
+#[test]
+fn test_print_char_literals() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(#\\a #\\space #\\tab #\\) #\\x7)").unwrap();
+    let mut output = String::new();
+    print(&mut output, &root, false).unwrap();
+    assert_eq!(output, "\n(#\\a #\\space #\\tab #\\) #\\alarm)");
+}
+
+#[test]
+fn test_char_integer_conversion() {
+    let a = Atom::Char { inner: 'a' };
+    assert_eq!(char_to_integer(&a), Ok(Atom::Int { inner: 97 }));
+    assert_eq!(integer_to_char(&Atom::Int { inner: 97 }), Ok(a));
+    assert!(integer_to_char(&Atom::Int { inner: 0xD800 }).is_err());
+    assert!(integer_to_char(&Atom::Int { inner: -1 }).is_err());
+    assert!(char_to_integer(&Atom::Int { inner: 97 }).is_err());
+}
+
+#[test]
+fn test_char_alphabetic() {
+    assert_eq!(char_alphabetic(&Atom::Char { inner: 'é' }), Ok(Atom::True));
+    assert_eq!(char_alphabetic(&Atom::Char { inner: '7' }), Ok(Atom::False));
+    assert!(char_alphabetic(&Atom::Nil).is_err());
+}
+
+#[test]
+fn test_string_ref_and_lists() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let s = Atom::String { inner: "añb" };
+    assert_eq!(
+        string_ref(&s, &Atom::Int { inner: 1 }),
+        Ok(Atom::Char { inner: 'ñ' })
+    );
+    assert!(string_ref(&s, &Atom::Int { inner: 3 }).is_err());
+
+    let list = string_to_list(&arena, &s).unwrap();
+    let mut output = String::new();
+    print(
+        &mut output,
+        &[tyson::read::Expression {
+            depth: 0,
+            payload: list,
+            span: tyson::read::Span::default(),
+        }],
+        false,
+    )
+    .unwrap();
+    assert_eq!(output, "\n(#\\a #\\ñ #\\b)");
+
+    assert_eq!(list_to_string(&arena, &list), Ok(s));
+    assert_eq!(
+        string_to_list(&arena, &Atom::String { inner: "" }),
+        Ok(Atom::Void)
+    );
+    assert_eq!(
+        list_to_string(&arena, &Atom::Void),
+        Ok(Atom::String { inner: "" })
+    );
+
+    let root = parse(&arena, "(#\\a 1)").unwrap();
+    assert!(list_to_string(&arena, &root[0].payload).is_err());
+}
+
+#[test]
+fn test_char_image_round_trip() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let mut env = Env::new(&arena).unwrap();
+    env.define("c", Atom::Char { inner: '€' });
+
+    let mut image = Vec::new();
+    snapshot(&env, &mut image).unwrap();
+    let restored = restore(&arena, &image).unwrap();
+
+    assert_eq!(restored.get("c"), Some(Atom::Char { inner: '€' }));
+}
+
+#[test]
+fn test_evaluate_char_procedures() {
+    let block = Block::with_capacity(16 * 1024 * 1024);
+    let arena = block.arena(4 * 1024 * 1024).unwrap();
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+
+    let mut show = |code: &'static str| {
+        let mut text = String::new();
+        print_value(&mut text, &interpreter.run(code).unwrap()).unwrap();
+        text
+    };
+    assert_eq!(show("(char->integer #\\A)"), "65");
+    assert_eq!(show("(integer->char 955)"), "#\\λ");
+    assert_eq!(show("(string-ref \"abc\" 1)"), "#\\b");
+    assert_eq!(show("(string->list \"hi\")"), "(#\\h #\\i)");
+    assert_eq!(show("(list->string (cons #\\o (cons #\\k '())))"), "\"ok\"");
+    assert_eq!(show("(char-alphabetic? #\\space)"), "#f");
+
+    assert_eq!(
+        interpreter.run("(list->string (list #\\a 1))"),
+        Err("List contains a non-character")
+    );
+}
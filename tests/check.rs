@@ -0,0 +1,233 @@
+use tyson::MemoryBlock as Block;
+use tyson::check::{Arity, Checker, DiagnosticKind};
+use tyson::read::parse;
+use tyson::sandbox::Preset;
+
+fn kinds<'a>(
+    checker: &Checker<'a>,
+    arena: &'a tyson::Arena<'a>,
+    code: &'static str,
+) -> Vec<DiagnosticKind<'a>> {
+    let root = parse(arena, code).unwrap();
+    checker
+        .check(arena, &root[..root.len()])
+        .iter()
+        .map(|d| d.kind)
+        .collect()
+}
+
+#[test]
+fn test_check_bound_program() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let checker = Checker::new();
+
+    let code = "
+(define (square x) (* x x))
+(define (sum-squares a b) (+ (square a) (square b)))
+(sum-squares 3 4)
+";
+    assert!(kinds(&checker, &arena, code).is_empty());
+}
+
+#[test]
+fn test_check_unbound_symbol() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let checker = Checker::new();
+
+    let code = "(define (f x) (+ x y))";
+    assert_eq!(
+        kinds(&checker, &arena, code),
+        vec![DiagnosticKind::Unbound { name: "y" }]
+    );
+}
+
+#[test]
+fn test_check_unbound_operator_position() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let checker = Checker::new();
+
+    let diagnostics = kinds(&checker, &arena, "(frobnicate 1 2)");
+    assert_eq!(
+        diagnostics,
+        vec![DiagnosticKind::Unbound { name: "frobnicate" }]
+    );
+}
+
+#[test]
+fn test_check_arity_mismatch() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let checker = Checker::new();
+
+    let code = "
+(define (pair a b) (cons a b))
+(pair 1)
+(cons 1 2 3)
+";
+    assert_eq!(
+        kinds(&checker, &arena, code),
+        vec![
+            DiagnosticKind::ArityMismatch {
+                name: "pair",
+                expected: Arity::Exact(2),
+                found: 1
+            },
+            DiagnosticKind::ArityMismatch {
+                name: "cons",
+                expected: Arity::Exact(2),
+                found: 3
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_check_rest_parameters() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let checker = Checker::new();
+
+    let code = "
+(define (f a . rest) rest)
+(f 1 2 3)
+(f)
+";
+    assert_eq!(
+        kinds(&checker, &arena, code),
+        vec![DiagnosticKind::ArityMismatch {
+            name: "f",
+            expected: Arity::AtLeast(1),
+            found: 0
+        }]
+    );
+}
+
+#[test]
+fn test_check_duplicate_definition() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let checker = Checker::new();
+
+    let code = "
+(define x 1)
+(define x 2)
+(define (g a a) a)
+";
+    assert_eq!(
+        kinds(&checker, &arena, code),
+        vec![
+            DiagnosticKind::DuplicateDefinition { name: "x" },
+            DiagnosticKind::DuplicateDefinition { name: "a" },
+        ]
+    );
+}
+
+#[test]
+fn test_check_lexical_scopes() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let checker = Checker::new();
+
+    let code = "
+(define (outer n)
+  (define (inner m) (+ n m))
+  (let ((a 1) (b 2))
+    (let* ((c a) (d c))
+      ((lambda (e) (inner e)) d))))
+(let loop ((i 0))
+  (loop (+ i 1)))
+(define shadowed (let ((z 1)) z))
+z
+";
+    assert_eq!(
+        kinds(&checker, &arena, code),
+        vec![DiagnosticKind::Unbound { name: "z" }]
+    );
+}
+
+#[test]
+fn test_check_quoted_data_is_ignored() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let checker = Checker::new();
+
+    let code = "(define table '(a b c)) (define kind 'equal?)";
+    assert!(kinds(&checker, &arena, code).is_empty());
+}
+
+#[test]
+fn test_check_derived_forms() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(256 * 1024).unwrap();
+    let checker = Preset::Full.checker();
+
+    let code = "(define x '(1 2))
+                (match x ((a b) (+ a b)) (_ 0))
+                (case 2 ((1 2) 'low) (else 'high))
+                (-> 1 (+ 2) (* 3))
+                (define-record-type point (make-point x y) point? (x point-x) (y point-y))
+                (point-x (make-point 1 2))
+                (delay (point? x))";
+    assert!(kinds(&checker, &arena, code).is_empty());
+
+    assert_eq!(
+        kinds(&checker, &arena, "(match '(1) ((a) b))"),
+        vec![DiagnosticKind::Unbound { name: "b" }]
+    );
+    assert_eq!(
+        kinds(&checker, &arena, "(->) (case)"),
+        vec![
+            DiagnosticKind::Malformed { form: "->" },
+            DiagnosticKind::Malformed { form: "case" }
+        ]
+    );
+}
+
+#[test]
+fn test_check_declared_globals() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut checker = Checker::new();
+    checker.declare("list", Some(Arity::AtLeast(0)));
+    checker.declare("null?", Some(Arity::Exact(1)));
+
+    let code = "(null? (list 1 2 3)) (null? 1 2)";
+    assert_eq!(
+        kinds(&checker, &arena, code),
+        vec![DiagnosticKind::ArityMismatch {
+            name: "null?",
+            expected: Arity::Exact(1),
+            found: 2
+        }]
+    );
+}
+
+#[test]
+fn test_check_accepts_type_annotations() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let checker = Checker::new();
+
+    let code = "
+(define (f (x : Int) y) : Int (+ x y))
+(define n : Int (f 1 2))
+(define g : (-> Int Int) (lambda ((x : Int)) : Int (* x n)))
+(g (f n 3))
+";
+    assert!(kinds(&checker, &arena, code).is_empty());
+    assert_eq!(
+        kinds(&checker, &arena, "(define k : Int (lambda (a) a)) (k 1 2)"),
+        [DiagnosticKind::ArityMismatch {
+            name: "k",
+            expected: Arity::Exact(1),
+            found: 2
+        }]
+    );
+    assert_eq!(
+        kinds(&checker, &arena, "(define (h (x Int)) 1)"),
+        [DiagnosticKind::Malformed { form: "parameter" }]
+    );
+}
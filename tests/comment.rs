@@ -0,0 +1,77 @@
+use tyson::MemoryBlock as Block;
+use tyson::read::{ParseErrorKind, Reader, parse};
+
+#[test]
+fn test_line_comments() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "; one\n(a ;; two\n b) ;;; three").unwrap();
+    assert_eq!(root, parse(&arena, "(a b)").unwrap());
+}
+
+#[test]
+fn test_block_comments_nest() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let code = "#| outer #| inner (not code) |# still outer |# (a #|x|# b)";
+    assert_eq!(
+        parse(&arena, code).unwrap(),
+        parse(&arena, "(a b)").unwrap()
+    );
+    assert_eq!(parse(&arena, "#||#").unwrap().len(), 0);
+}
+
+#[test]
+fn test_datum_comments_skip_one_form() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let cases = [
+        ("(a #;b c)", "(a c)"),
+        ("(a #; (b (c)) d)", "(a d)"),
+        ("#;'quoted kept", "kept"),
+        ("[1 #;#; 2 3 4]", "[1 4]"),
+        ("(#;(nested #;x) y)", "(y)"),
+        ("{:a #;ignored 1}", "{:a 1}"),
+    ];
+    for (code, expected) in cases {
+        assert_eq!(
+            parse(&arena, code).unwrap(),
+            parse(&arena, expected).unwrap(),
+            "{code}"
+        );
+    }
+
+    let error = parse(&arena, "(a #;)").unwrap_err();
+    assert_eq!(error.kind, ParseErrorKind::MalformedComment);
+    assert_eq!(error.span.start, 3);
+
+    let error = parse(&arena, "a #;").unwrap_err();
+    assert!(error.is_incomplete());
+}
+
+#[test]
+fn test_reader_handles_comments() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut reader = Reader::new(&arena);
+
+    reader.feed("#| a block\n");
+    assert_eq!(reader.next_form(), Ok(None));
+    assert!(reader.needs_more());
+
+    reader.feed("comment |# #;(skipped form)\n");
+    assert_eq!(reader.next_form(), Ok(None));
+    assert!(!reader.needs_more());
+
+    reader.feed("#;");
+    assert!(reader.needs_more());
+    reader.feed(" skipped (kept)");
+    assert_eq!(
+        reader.next_form().unwrap().unwrap(),
+        parse(&arena, "(kept)").unwrap()[0]
+    );
+    assert_eq!(reader.finish(), Ok(None));
+}
@@ -0,0 +1,156 @@
+use tyson::MemoryBlock as Block;
+use tyson::env::Env;
+use tyson::eval::Interpreter;
+use tyson::expand::expand;
+use tyson::prelude::root;
+use tyson::print::print_value;
+use tyson::read::{Atom, parse};
+use tyson::sandbox::Preset;
+
+// Evaluates `code` in an environment extending the prelude and prints its
+// value.
+fn run(code: &'static str) -> Result<String, &'static str> {
+    let block = Block::with_capacity(64 * 1024 * 1024);
+    let arena = block.arena(32 * 1024 * 1024).unwrap();
+
+    let prelude = root(&arena)?;
+    let mut interpreter = Interpreter::new(&arena, Env::extend(&prelude, 4).unwrap());
+    let value = interpreter.run(code)?;
+
+    let mut text = String::new();
+    print_value(&mut text, &value).unwrap();
+    Ok(text)
+}
+
+fn assert_evals(code: &'static str, expected: &str) {
+    assert_eq!(run(code).as_deref(), Ok(expected), "{code}");
+}
+
+#[test]
+fn test_resume_and_yield() {
+    assert_evals(
+        "(define co (make-coroutine (lambda () (yield 1) (yield 2) 'done)))
+         (list (resume co) (resume co) (coroutine-done? co) (resume co) (coroutine-done? co))",
+        "(1 2 #f done #t)",
+    );
+    // The value passed to `resume` is what the suspended `yield` returns.
+    assert_evals(
+        "(define co
+           (make-coroutine
+             (lambda ()
+               (let loop ((total 0))
+                 (loop (+ total (yield total)))))))
+         (resume co)
+         (resume co 5)
+         (resume co 10)",
+        "15",
+    );
+}
+
+#[test]
+fn test_yield_from_nested_calls() {
+    // `yield` suspends the calls between it and `resume`, including ones
+    // halfway through evaluating their arguments.
+    assert_evals(
+        "(define (walk tree)
+           (if (pair? tree)
+               (begin (walk (car tree)) (walk (cdr tree)))
+               (if (null? tree) nil (yield tree))))
+         (define co (make-coroutine (lambda () (walk '((a b) (c (d)))))))
+         (list (resume co) (resume co) (resume co) (resume co))",
+        "(a b c d)",
+    );
+    assert_evals(
+        "(define co (make-coroutine (lambda () (list 1 (yield 'first) 3))))
+         (list (resume co) (resume co 2))",
+        "(first (1 2 3))",
+    );
+}
+
+#[test]
+fn test_expand_generator() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(generator (yield 1) (yield 2))").unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+    let expected = parse(
+        &arena,
+        "(make-coroutine (lambda () (begin (yield 1) (yield 2))))",
+    )
+    .unwrap();
+    assert_eq!(expanded, expected);
+
+    let root = parse(&arena, "(generator)").unwrap();
+    assert!(expand(&arena, &root[..root.len()]).is_err());
+}
+
+#[test]
+fn test_generators() {
+    assert_evals(
+        "(generator->list (generator (yield 'a) (yield 'b) 'ignored))",
+        "(a b)",
+    );
+    assert_evals(
+        "(define (tree-walker tree)
+           (make-generator
+             (lambda (yield)
+               (let walk ((tree tree))
+                 (if (pair? tree)
+                     (begin (walk (car tree)) (walk (cdr tree)))
+                     (if (null? tree) nil (yield tree)))))))
+         (generator->list (tree-walker '((a b) (c (d)) e)))",
+        "(a b c d e)",
+    );
+    assert_evals(
+        "(define (upto n)
+           (make-generator
+             (lambda (yield)
+               (let loop ((i 0))
+                 (if (< i n) (begin (yield i) (loop (+ i 1))))))))
+         (generator->list
+           (generator
+             (for-each yield (upto 4))
+             (for-each yield '(a b))))",
+        "(0 1 2 3 a b)",
+    );
+    // Generators are lazy, so an endless one is fine as long as it is
+    // only resumed a bounded number of times.
+    assert_evals(
+        "(define naturals
+           (make-generator (lambda (yield) (let loop ((i 0)) (yield i) (loop (+ i 1))))))
+         (resume naturals) (resume naturals)
+         (resume naturals)",
+        "2",
+    );
+}
+
+#[test]
+fn test_coroutine_errors() {
+    assert_eq!(run("(yield 1)"), Err("Yield outside a coroutine"));
+    assert_eq!(run("(resume 1)"), Err("Not a coroutine"));
+    assert_eq!(run("(make-coroutine 1)"), Err("Not a procedure"));
+    assert_eq!(
+        run("(define co (make-coroutine (lambda () 1))) (resume co) (resume co)"),
+        Err("Coroutine is finished")
+    );
+    assert_eq!(
+        run("(define co (make-coroutine (lambda () (resume co)))) (resume co)"),
+        Err("Coroutine is already running")
+    );
+}
+
+#[test]
+fn test_failed_coroutine_is_finished() {
+    let block = Block::with_capacity(16 * 1024 * 1024);
+    let arena = block.arena(8 * 1024 * 1024).unwrap();
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+
+    assert_eq!(
+        interpreter.run("(define co (make-coroutine (lambda () (yield 1) (car '())))) (resume co)"),
+        Ok(Atom::Int { inner: 1 })
+    );
+    assert_eq!(interpreter.run("(resume co)"), Err("Not a pair"));
+    assert_eq!(interpreter.run("(coroutine-done? co)"), Ok(Atom::True));
+    assert_eq!(interpreter.run("(resume co)"), Err("Coroutine is finished"));
+}
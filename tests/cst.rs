@@ -0,0 +1,125 @@
+use tyson::MemoryBlock as Block;
+use tyson::cst::{Trivia, TriviaKind, parse};
+use tyson::read::Atom;
+
+fn texts<'a>(trivia: &[Trivia<'a>]) -> Vec<&'a str> {
+    trivia.iter().map(|t| t.text).collect()
+}
+
+const CODE: &str = ";;; Squares things.
+;; Doc for sq.
+(define (sq x) ; trailing
+  (* x x))  #| after |#
+
+#;(old version)
+(sq 3)
+";
+
+#[test]
+fn test_trivia_and_forms_cover_the_source() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let syntax = parse(&arena, CODE).unwrap();
+    assert_eq!(syntax.root.len(), 2);
+
+    // Top-level trivia and forms tile the source; trivia inside a form is
+    // part of its text.
+    let root = &syntax.root[..syntax.root.len()];
+    let mut pieces: Vec<(u32, &str)> = syntax
+        .trivia()
+        .iter()
+        .filter(|t| {
+            !root
+                .iter()
+                .any(|e| e.span.start <= t.span.start && t.span.end <= e.span.end)
+        })
+        .map(|t| (t.span.start, t.text))
+        .collect();
+    for expr in root {
+        pieces.push((expr.span.start, syntax.text(expr)));
+    }
+    pieces.sort();
+
+    let rebuilt: String = pieces.into_iter().map(|(_, text)| text).collect();
+    assert_eq!(rebuilt, CODE);
+}
+
+#[test]
+fn test_trivia_kinds_and_positions() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let syntax = parse(&arena, CODE).unwrap();
+    let comments: Vec<_> = syntax
+        .trivia()
+        .iter()
+        .filter(|t| t.kind != TriviaKind::Whitespace)
+        .map(|t| (t.kind, t.text, t.span.line, t.span.column))
+        .collect();
+
+    assert_eq!(
+        comments,
+        vec![
+            (TriviaKind::LineComment, ";;; Squares things.", 1, 1),
+            (TriviaKind::LineComment, ";; Doc for sq.", 2, 1),
+            (TriviaKind::LineComment, "; trailing", 3, 16),
+            (TriviaKind::BlockComment, "#| after |#", 4, 13),
+            (TriviaKind::DatumComment, "#;(old version)", 6, 1),
+        ]
+    );
+
+    // Whitespace never runs past a line break.
+    for trivia in syntax.trivia() {
+        if trivia.kind == TriviaKind::Whitespace {
+            assert!(!trivia.text.trim_end_matches('\n').contains('\n'));
+        }
+    }
+}
+
+#[test]
+fn test_comments_attach_to_neighbouring_forms() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let syntax = parse(&arena, CODE).unwrap();
+    let (define, call) = (&syntax.root[0], &syntax.root[1]);
+
+    assert_eq!(
+        texts(syntax.leading(define)),
+        vec![";;; Squares things.", "\n", ";; Doc for sq.", "\n"]
+    );
+    assert_eq!(
+        texts(syntax.trailing(define)),
+        vec!["  ", "#| after |#", "\n"]
+    );
+    assert_eq!(
+        texts(syntax.leading(call)),
+        vec!["\n", "#;(old version)", "\n"]
+    );
+    assert_eq!(texts(syntax.trailing(call)), vec!["\n"]);
+
+    // Inside the definition, the comment after `(sq x)` stays on its line.
+    let Atom::List { body } = define.payload else {
+        panic!("expected a list");
+    };
+    assert_eq!(
+        texts(syntax.trailing(&body[1])),
+        vec![" ", "; trailing", "\n"]
+    );
+    assert_eq!(texts(syntax.leading(&body[2])), vec!["  "]);
+    assert!(syntax.trailing(&body[2]).is_empty());
+}
+
+#[test]
+fn test_lossless_parse_matches_plain_parse() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let syntax = parse(&arena, CODE).unwrap();
+    assert_eq!(syntax.root, tyson::read::parse(&arena, CODE).unwrap());
+    assert_eq!(syntax.source, CODE);
+
+    assert!(parse(&arena, "(unclosed ; comment").is_err());
+    assert!(parse(&arena, "").unwrap().trivia().is_empty());
+}
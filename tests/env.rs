@@ -0,0 +1,233 @@
+use tyson::MemoryBlock as Block;
+use tyson::env::{Address, Env};
+use tyson::eval::Interpreter;
+use tyson::read::{Atom, parse_with};
+use tyson::sandbox::Preset;
+use tyson::symbol::SymbolTable;
+
+#[test]
+fn test_global_bindings() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut env = Env::new(&arena).unwrap();
+
+    env.define("x", Atom::Int { inner: 1 });
+    env.define("y", Atom::Int { inner: 2 });
+    env.define("x", Atom::Int { inner: 3 });
+
+    assert_eq!(env.get("x"), Some(Atom::Int { inner: 3 }));
+    assert_eq!(env.get("y"), Some(Atom::Int { inner: 2 }));
+    assert_eq!(env.get("z"), None);
+}
+
+#[test]
+fn test_local_frames_shadow_parents() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let mut global = Env::new(&arena).unwrap();
+    global.define("x", Atom::Int { inner: 1 });
+    global.define("y", Atom::Int { inner: 2 });
+
+    let mut local = Env::extend(&global, 2).unwrap();
+    local.define("y", Atom::Int { inner: 3 });
+
+    assert_eq!(local.get("x"), Some(Atom::Int { inner: 1 }));
+    assert_eq!(local.get("y"), Some(Atom::Int { inner: 3 }));
+    assert_eq!(global.get("y"), Some(Atom::Int { inner: 2 }));
+    assert!(local.parent().is_some());
+    assert!(global.parent().is_none());
+}
+
+#[test]
+fn test_local_frames_grow_in_order() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let global = Env::new(&arena).unwrap();
+    let mut local = Env::extend(&global, 1).unwrap();
+
+    local.define("a", Atom::Int { inner: 1 });
+    let a = local.symbols().get("a").unwrap();
+    let address = local.resolve(a).unwrap();
+
+    for (i, name) in ["b", "c", "d", "e"].iter().enumerate() {
+        assert!(local.define(name, Atom::Int { inner: i as i64 }).is_some());
+    }
+
+    assert_eq!(local.resolve(a), Some(address));
+    assert_eq!(local.get_at(address), Some(Atom::Int { inner: 1 }));
+    assert_eq!(local.get("e"), Some(Atom::Int { inner: 3 }));
+}
+
+#[test]
+fn test_resolve_addresses() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let mut global = Env::new(&arena).unwrap();
+    global.define("g", Atom::Int { inner: 0 });
+
+    let mut outer = Env::extend(&global, 2).unwrap();
+    outer.define("a", Atom::Int { inner: 1 });
+    outer.define("b", Atom::Int { inner: 2 });
+
+    let mut inner = Env::extend(&outer, 1).unwrap();
+    inner.define("c", Atom::Int { inner: 3 });
+
+    let mut symbols = inner.symbols();
+    let (a, b, c, g) = (
+        symbols.get("a").unwrap(),
+        symbols.get("b").unwrap(),
+        symbols.get("c").unwrap(),
+        symbols.get("g").unwrap(),
+    );
+    let missing = symbols.intern("missing").unwrap();
+    drop(symbols);
+
+    let address = inner.resolve(b).unwrap();
+    assert_eq!(address, Address::Local { depth: 1, index: 1 });
+    assert_eq!(inner.get_at(address), Some(Atom::Int { inner: 2 }));
+
+    assert_eq!(
+        inner.resolve(c),
+        Some(Address::Local { depth: 0, index: 0 })
+    );
+    assert_eq!(inner.resolve(g), Some(Address::Global(g)));
+    assert_eq!(inner.lookup(a), Some(Atom::Int { inner: 1 }));
+    assert_eq!(inner.resolve(missing), None);
+    assert_eq!(outer.resolve(c), None);
+}
+
+#[test]
+fn test_reader_shares_the_env_symbols() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let mut env = Env::new(&arena).unwrap();
+    env.define("answer", Atom::Int { inner: 42 });
+
+    let root = parse_with(&arena, "answer\n", &mut env.symbols()).unwrap();
+    let symbol = env.symbols().symbol(&root[0].payload).unwrap();
+    assert_eq!(env.lookup(symbol), Some(Atom::Int { inner: 42 }));
+
+    // A symbol from another table never matches, even with the same name.
+    let mut other = SymbolTable::new(&arena);
+    let stranger = other.intern("answer").unwrap();
+    assert_eq!(env.lookup(stranger), None);
+}
+
+#[test]
+fn test_copies_share_a_frame() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let env = Env::new(&arena).unwrap();
+    let mut copy = env;
+    copy.define("x", Atom::Int { inner: 1 });
+
+    assert_eq!(env.get("x"), Some(Atom::Int { inner: 1 }));
+    assert_eq!(env.id(), copy.id());
+}
+
+#[test]
+fn test_evaluate_in_arena_frames() {
+    let block = Block::with_capacity(4 * 1024 * 1024);
+    let arena = block.arena(1024 * 1024).unwrap();
+
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+    assert_eq!(
+        interpreter.run(
+            "(define (f a)
+               (define b (* a 2))
+               (define c (+ b 1))
+               (define d (+ c 1))
+               (let ((e 1)) (+ a b c d e)))
+             (f 1)"
+        ),
+        Ok(Atom::Int { inner: 11 })
+    );
+}
+
+#[test]
+fn test_assign_updates_the_nearest_binding() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let mut global = Env::new(&arena).unwrap();
+    global.define("count", Atom::Int { inner: 0 });
+
+    let mut outer = Env::extend(&global, 1).unwrap();
+    outer.define("x", Atom::Int { inner: 1 });
+
+    let mut inner = Env::extend(&outer, 1).unwrap();
+    inner.assign("count", Atom::Int { inner: 5 }).unwrap();
+    inner.assign("x", Atom::Int { inner: 2 }).unwrap();
+
+    assert_eq!(global.get("count"), Some(Atom::Int { inner: 5 }));
+    assert_eq!(outer.get("x"), Some(Atom::Int { inner: 2 }));
+    assert_eq!(inner.bindings().count(), 2);
+
+    assert_eq!(
+        inner.assign("missing", Atom::Int { inner: 0 }),
+        Err("Unbound variable")
+    );
+    assert_eq!(outer.assign("x", Atom::Nil), Ok(()));
+    assert!(!global.contains("x"));
+}
+
+#[test]
+fn test_remove_and_bindings() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let mut global = Env::new(&arena).unwrap();
+    global.define("x", Atom::Int { inner: 1 });
+    global.define("y", Atom::Int { inner: 2 });
+
+    let mut local = Env::extend(&global, 2).unwrap();
+    local.define("y", Atom::Int { inner: 3 });
+    local.define("z", Atom::Int { inner: 4 });
+
+    let mut visible: Vec<_> = local
+        .bindings()
+        .map(|(symbol, value)| (symbol.name(), value))
+        .collect();
+    visible.sort_by_key(|(name, _)| *name);
+    assert_eq!(
+        visible,
+        vec![
+            ("x", Atom::Int { inner: 1 }),
+            ("y", Atom::Int { inner: 3 }),
+            ("z", Atom::Int { inner: 4 }),
+        ]
+    );
+
+    assert_eq!(local.remove("y"), Some(Atom::Int { inner: 3 }));
+    assert_eq!(local.remove("x"), None);
+    assert!(local.contains("x"));
+    assert_eq!(local.get("y"), Some(Atom::Int { inner: 2 }));
+    assert_eq!(local.get("z"), Some(Atom::Int { inner: 4 }));
+}
+
+#[test]
+fn test_evaluate_set_from_a_closure() {
+    let block = Block::with_capacity(4 * 1024 * 1024);
+    let arena = block.arena(1024 * 1024).unwrap();
+
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+    assert_eq!(
+        interpreter.run(
+            "(define count 0)
+             (define (make-counter)
+               (let ((n 0))
+                 (lambda () (set! n (+ n 1)) (set! count (+ count 1)) n)))
+             (define tick (make-counter))
+             (tick)
+             (tick)
+             (and (= (tick) 3) (= count 3))"
+        ),
+        Ok(Atom::True)
+    );
+    assert_eq!(interpreter.run("(set! missing 1)"), Err("Unbound variable"));
+}
@@ -0,0 +1,82 @@
+use tyson::MemoryBlock as Block;
+use tyson::print::print;
+use tyson::read::{Atom, ParseErrorKind, parse};
+
+fn string(code: &str) -> String {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    match parse(&arena, code).unwrap()[0].payload {
+        Atom::String { inner } => inner.to_string(),
+        other => panic!("expected a string, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_string_escapes() {
+    assert_eq!(string(r#""say \"hi\"""#), "say \"hi\"");
+    assert_eq!(string(r#""a\tb\nc\\d""#), "a\tb\nc\\d");
+    assert_eq!(string(r#""\a\b\r\|""#), "\u{7}\u{8}\r|");
+    assert_eq!(string(r#""\x41;\x3bb;!""#), "Aλ!");
+    assert_eq!(string(r#""\u{1F600} \u{e9}""#), "😀 é");
+}
+
+#[test]
+fn test_line_continuation() {
+    assert_eq!(string("\"one \\\n    two\""), "one two");
+    assert_eq!(string("\"one \\  \t\r\n  two\""), "one two");
+    assert_eq!(string("\"keeps\n  newlines\""), "keeps\n  newlines");
+}
+
+#[test]
+fn test_strings_without_escapes_borrow_the_source() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let code = "(f \"plain text\")";
+    let root = parse(&arena, code).unwrap();
+    let Atom::List { body } = root[0].payload else {
+        panic!("expected a list");
+    };
+    let Atom::String { inner } = body[1].payload else {
+        panic!("expected a string");
+    };
+    assert_eq!(inner.as_ptr(), code[4..].as_ptr());
+}
+
+#[test]
+fn test_invalid_escapes() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    for code in [
+        r#""\q""#,
+        r#""\x41""#,
+        r#""\x;""#,
+        r#""\xd800;""#,
+        r#""\u{110000}""#,
+        r#""\u41""#,
+        "\"\\  x\"",
+    ] {
+        let error = parse(&arena, code).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::InvalidEscape, "{code}");
+        assert_eq!(error.span.start, 0);
+    }
+
+    let error = parse(&arena, r#""ends in \""#).unwrap_err();
+    assert_eq!(error.kind, ParseErrorKind::UnterminatedString);
+}
+
+#[test]
+fn test_printer_escapes_strings() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let code = r#""say \"hi\"\n\ttab \\ \x1b; λ""#;
+    let root = parse(&arena, code).unwrap();
+    let mut printed = String::new();
+    print(&mut printed, &root[..root.len()], false).unwrap();
+    assert_eq!(printed, code);
+
+    assert_eq!(parse(&arena, &printed).unwrap(), root);
+}
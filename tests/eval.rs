@@ -0,0 +1,171 @@
+use tyson::MemoryBlock as Block;
+use tyson::eval::Interpreter;
+use tyson::print::print_value;
+use tyson::read::Atom;
+use tyson::sandbox::Preset;
+
+// Evaluates `code` in a fresh environment and prints its value.
+fn run(code: &'static str) -> Result<String, &'static str> {
+    let block = Block::with_capacity(64 * 1024 * 1024);
+    let arena = block.arena(32 * 1024 * 1024).unwrap();
+
+    let value = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap()).run(code)?;
+
+    let mut text = String::new();
+    print_value(&mut text, &value).unwrap();
+    Ok(text)
+}
+
+fn assert_evals(code: &'static str, expected: &str) {
+    assert_eq!(run(code).as_deref(), Ok(expected), "{code}");
+}
+
+#[test]
+fn test_arithmetic_and_comparison() {
+    assert_evals("(+ 1 2 3)", "6");
+    assert_evals("(- 10)", "-10");
+    assert_evals("(- 10 1 2)", "7");
+    assert_evals("(/ 6 3)", "2");
+    assert_evals("(/ 1 4)", "1/4");
+    assert_evals("(/ 1.0 4)", "0.25");
+    assert_evals("(+ 1 0.5)", "1.5");
+    assert_evals("(^ 2 10)", "1024");
+    assert_evals("(% -7 2)", "1");
+    assert_evals("(// -7 2)", "-1");
+    assert_evals("(< 1 2 3)", "#t");
+    assert_evals("(>= 3 3 4)", "#f");
+    assert_evals("(= 1 1.0)", "#t");
+    assert_evals("(!= 'a 'b)", "#t");
+    assert_evals("(! 5)", "-5");
+    assert_evals("(! #f)", "#t");
+}
+
+#[test]
+fn test_definitions_and_closures() {
+    assert_evals("(define x 2) (define (f y) (* x y)) (f 21)", "42");
+    assert_evals(
+        "(define (adder n) (lambda (x) (+ x n)))
+         (define add2 (adder 2))
+         (add2 40)",
+        "42",
+    );
+    assert_evals("((lambda args args) 1 2 3)", "(1 2 3)");
+    assert_evals("((lambda (a . rest) rest) 1 2 3)", "(2 3)");
+    assert_evals(
+        "(define (f)
+           (define a 1) (define b 2) (define c 3)
+           (+ a b c))
+         (f)",
+        "6",
+    );
+}
+
+#[test]
+fn test_binding_forms() {
+    assert_evals("(let ((x 1) (y 2)) (+ x y))", "3");
+    assert_evals("(let* ((x 1) (y (+ x 1))) (* x y))", "2");
+    assert_evals(
+        "(letrec ((even? (lambda (n) (if (= n 0) #t (odd? (- n 1)))))
+                  (odd? (lambda (n) (if (= n 0) #f (even? (- n 1))))))
+           (even? 100))",
+        "#t",
+    );
+    assert_evals("(let1 x 5 (* x x))", "25");
+    assert_evals(
+        "(let loop ((i 0) (acc '())) (if (= i 3) acc (loop (+ i 1) (cons i acc))))",
+        "(2 1 0)",
+    );
+}
+
+#[test]
+fn test_conditionals() {
+    assert_evals("(if #f 1)", "#nil");
+    assert_evals("(if '() 'yes 'no)", "yes");
+    assert_evals("(and 1 2 3)", "3");
+    assert_evals("(and)", "#t");
+    assert_evals("(or #f nil 7)", "7");
+    assert_evals("(-> 5 (- 1))", "4");
+    assert_evals("(some-> nil car)", "#nil");
+    assert_evals("(some-> '() null?)", "#t");
+    assert_evals("(quote (when a b))", "(when a b)");
+}
+
+#[test]
+fn test_lists_and_pairs() {
+    assert_evals("(cons 1 (cons 2 '()))", "(1 2)");
+    assert_evals("(cons 1 2)", "(1 . 2)");
+    assert_evals("(car (cdr '(1 2 3)))", "2");
+    assert_evals("(list 1 (list 2 3) 4)", "(1 (2 3) 4)");
+    assert_evals("(equal? (cons 1 (cons 2 '())) '(1 2))", "#t");
+    assert_evals("(eq? 'a 'a)", "#t");
+    assert_evals("(string-append \"ab\" \"cd\")", "\"abcd\"");
+}
+
+#[test]
+fn test_primitives_can_be_shadowed() {
+    assert_evals("(define (list . items) 'mine) (list 1 2)", "mine");
+    assert_evals("(if #t list)", "#<primitive list>");
+}
+
+#[test]
+fn test_tail_calls_run_in_constant_stack() {
+    assert_evals(
+        "(define (count n) (if (= n 0) 'done (count (- n 1)))) (count 50000)",
+        "done",
+    );
+}
+
+#[test]
+fn test_tail_calls_are_bounded_by_the_arena() {
+    // Tail calls leave the stack alone, but each one binds its arguments in
+    // a new frame of the arena, so a long enough loop uses the arena up.
+    let block = Block::with_capacity(8 * 1024 * 1024);
+    let arena = block.arena(4 * 1024 * 1024).unwrap();
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+
+    assert_eq!(
+        interpreter.run("(let loop ((i 0)) (if (= i 10000) i (loop (+ i 1))))"),
+        Ok(Atom::Int { inner: 10000 })
+    );
+    assert_eq!(
+        interpreter.run("(let loop ((i 0)) (if (= i 1000000) i (loop (+ i 1))))"),
+        Err("Failed to allocate frame")
+    );
+}
+
+#[test]
+fn test_deep_recursion_is_an_error() {
+    let block = Block::with_capacity(64 * 1024 * 1024);
+    let arena = block.arena(48 * 1024 * 1024).unwrap();
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+
+    assert_eq!(
+        interpreter.run("(define (sum n) (if (= n 0) 0 (+ n (sum (- n 1))))) (sum 1000000)"),
+        Err("Stack overflow")
+    );
+    assert_eq!(interpreter.run("(sum 10)"), Ok(Atom::Int { inner: 55 }));
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(run("(+ undefined-name)"), Err("Unbound variable"));
+    assert_eq!(run("(set! undefined-name 1)"), Err("Unbound variable"));
+    assert_eq!(run("(1 2)"), Err("Not a procedure"));
+    assert_eq!(run("((lambda (x) x))"), Err("Wrong number of arguments"));
+    assert_eq!(run("(car '())"), Err("Not a pair"));
+    assert_eq!(run("(+ 1 'a)"), Err("Not a number"));
+    assert_eq!(run("(/ 1 0)"), Err("Division by zero"));
+}
+
+#[test]
+fn test_apply_from_rust() {
+    let block = Block::with_capacity(64 * 1024 * 1024);
+    let arena = block.arena(16 * 1024 * 1024).unwrap();
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+
+    let add = interpreter.run("(lambda (a b) (+ a b))").unwrap();
+    assert_eq!(
+        interpreter.apply(add, &[Atom::Int { inner: 1 }, Atom::Int { inner: 2 }]),
+        Ok(Atom::Int { inner: 3 })
+    );
+}
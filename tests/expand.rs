@@ -0,0 +1,96 @@
+use tyson::MemoryBlock as Block;
+use tyson::expand::expand;
+use tyson::print::print;
+use tyson::read::parse;
+
+fn assert_expands(code: &'static str, expected: &'static str) {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, code).unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+    let expected = parse(&arena, expected).unwrap();
+
+    assert_eq!(expanded, expected);
+}
+
+#[test]
+fn test_thread_first() {
+    assert_expands("(-> x (f a) g (h b c))", "(h (g (f x a)) b c)");
+}
+
+#[test]
+fn test_thread_last() {
+    assert_expands("(<- x (f a) g (h b c))", "(h b c (g (f a x)))");
+}
+
+#[test]
+fn test_thread_single_value() {
+    assert_expands("(define y (-> x))", "(define y x)");
+}
+
+#[test]
+fn test_thread_nested() {
+    assert_expands(
+        "(define (f x) (-> x (+ 1) (<- (list 2))))",
+        "(define (f x) (list 2 (+ x 1)))",
+    );
+}
+
+#[test]
+fn test_thread_as() {
+    assert_expands(
+        "(as-> 5 v (+ v 1) (list 0 v))",
+        "(let* ((v 5) (v (+ v 1)) (v (list 0 v))) v)",
+    );
+}
+
+#[test]
+fn test_thread_some() {
+    assert_expands(
+        "(some-> x (f a) g)",
+        "(let ((%some-0 x))
+           (if (= %some-0 nil) nil
+               (let ((%some-1 (f %some-0 a)))
+                 (if (= %some-1 nil) nil (g %some-1)))))",
+    );
+    assert_expands(
+        "(some<- x (f a))",
+        "(let ((%some-0 x)) (if (= %some-0 nil) nil (f a %some-0)))",
+    );
+}
+
+#[test]
+fn test_thread_quoted_data_untouched() {
+    assert_expands("'(-> x f)", "'(-> x f)");
+    assert_expands("(quote (-> a b))", "(quote (-> a b))");
+    assert_expands("(quote (when a b))", "(quote (when a b))");
+    assert_expands(
+        "(quasiquote ((when a b) (unquote (-> a b)) [(unquote-splicing (when c d))]))",
+        "(quasiquote ((when a b) (unquote (b a)) [(unquote-splicing (if c d nil))]))",
+    );
+}
+
+#[test]
+fn test_thread_errors() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(->)").unwrap();
+    assert!(expand(&arena, &root[..root.len()]).is_err());
+
+    let root = parse(&arena, "(as-> 1 (x))").unwrap();
+    assert!(expand(&arena, &root[..root.len()]).is_err());
+}
+
+#[test]
+fn test_arrows_print_as_read() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(-> (<- x))").unwrap();
+    let mut output = String::new();
+    print(&mut output, &root, false).unwrap();
+
+    assert_eq!(output, "\n(-> \n  (<- x))");
+}
@@ -149,3 +149,17 @@ fn test_hash_map_iter_mut_updates_values() {
         assert_eq!(map.find(&i), Some(&(i * 2)));
     }
 }
+
+#[test]
+fn test_hash_map_find_borrowed_key() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(1024).unwrap();
+    let mut map: HashMap<&str, i32> = HashMap::new(&arena, 16);
+
+    map.insert(&"alpha", &1);
+
+    let name = String::from("alpha");
+    assert_eq!(map.find(name.as_str()), Some(&1));
+    assert!(map.contains("alpha"));
+    assert!(!map.contains("beta"));
+}
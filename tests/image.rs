@@ -0,0 +1,201 @@
+use tyson::MemoryBlock as Block;
+use tyson::env::Env;
+use tyson::eval::Interpreter;
+use tyson::image::{load, restore, save, snapshot};
+use tyson::read::{Atom, parse, parse_copy};
+use tyson::sandbox::Preset;
+
+#[test]
+fn test_image_round_trip() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let code = parse(&arena, "(define (square x) (* x x)) '(1 2.5 \"three\")").unwrap();
+    let mut env = Env::new(&arena).unwrap();
+    env.define("answer", Atom::Int { inner: 42 });
+    env.define("ratio", Atom::Number { inner: 0.125 });
+    env.define("greeting", Atom::String { inner: "hello" });
+    env.define(
+        "bytes",
+        Atom::Buffer {
+            data: b"\x00\x01\x02",
+        },
+    );
+    env.define(
+        "prelude",
+        Atom::File {
+            path: "prelude.tyson",
+            lazy: true,
+        },
+    );
+    env.define("square", code[0].payload);
+    env.define("data", code[1].payload);
+    env.define("kind", Atom::Quoted { name: "equal?" });
+
+    let mut image = Vec::new();
+    snapshot(&env, &mut image).unwrap();
+
+    let other = Block::with_capacity(1024 * 1024);
+    let restored_arena = other.arena(64 * 1024).unwrap();
+    let restored = restore(&restored_arena, &image).unwrap();
+
+    for name in [
+        "answer", "ratio", "greeting", "bytes", "prelude", "square", "data", "kind",
+    ] {
+        assert_eq!(restored.get(name), env.get(name), "{name}");
+    }
+    assert_eq!(restored.get("missing"), None);
+}
+
+#[test]
+fn test_image_flattens_parents() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let mut global = Env::new(&arena).unwrap();
+    global.define("x", Atom::Int { inner: 1 });
+    global.define("y", Atom::Int { inner: 2 });
+
+    let mut local = Env::extend(&global, 1).unwrap();
+    local.define("y", Atom::Int { inner: 3 });
+
+    let mut image = Vec::new();
+    snapshot(&local, &mut image).unwrap();
+    let restored = restore(&arena, &image).unwrap();
+
+    assert_eq!(restored.get("x"), Some(Atom::Int { inner: 1 }));
+    assert_eq!(restored.get("y"), Some(Atom::Int { inner: 3 }));
+}
+
+#[test]
+fn test_image_file() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let path = std::env::temp_dir().join(format!("tyson-image-{}.img", std::process::id()));
+
+    let code = parse(&arena, "(lambda (a b) (+ a b))").unwrap();
+    let mut env = Env::new(&arena).unwrap();
+    env.define("add", code[0].payload);
+    save(&env, &path).unwrap();
+
+    let restored = load(&arena, &path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(restored.get("add"), Some(code[0].payload));
+}
+
+#[test]
+fn test_image_rejects_malformed_input() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    assert!(restore(&arena, b"").is_err());
+    assert!(restore(&arena, b"NOPE\x01\x00\x00\x00").is_err());
+
+    let mut env = Env::new(&arena).unwrap();
+    env.define("list", parse(&arena, "(1 2 3)").unwrap()[0].payload);
+    let mut image = Vec::new();
+    snapshot(&env, &mut image).unwrap();
+
+    for len in 0..image.len() {
+        assert!(restore(&arena, &image[..len]).is_err());
+    }
+
+    image.push(0);
+    assert!(restore(&arena, &image).is_err());
+}
+
+#[test]
+fn test_image_keeps_pairs_and_closures() {
+    let block = Block::with_capacity(8 * 1024 * 1024);
+    let arena = block.arena(4 * 1024 * 1024).unwrap();
+
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+    interpreter
+        .run(
+            "(define pairs (cons 1 (cons 2 3)))
+             (define (square x) (* x x))
+             (define also square)",
+        )
+        .unwrap();
+    let env = interpreter.env();
+
+    let mut image = Vec::new();
+    snapshot(&env, &mut image).unwrap();
+    let restored = restore(&arena, &image).unwrap();
+
+    assert_eq!(restored.get("pairs"), env.get("pairs"));
+    let (Some(Atom::Closure { closure: square }), Some(Atom::Closure { closure: also })) =
+        (restored.get("square"), restored.get("also"))
+    else {
+        panic!("expected closures");
+    };
+    assert!(core::ptr::eq(square, also));
+    assert_eq!(square.env().id(), restored.id());
+
+    let mut interpreter = Interpreter::new(&arena, restored);
+    assert_eq!(interpreter.run("(square 7)"), Ok(Atom::Int { inner: 49 }));
+}
+
+#[test]
+fn test_image_rejects_closures_over_local_frames() {
+    let block = Block::with_capacity(8 * 1024 * 1024);
+    let arena = block.arena(4 * 1024 * 1024).unwrap();
+
+    // A closure over a `let` frame has nowhere to go in a flat image.
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+    interpreter
+        .run("(define local (let ((n 1)) (lambda () n)))")
+        .unwrap();
+    let mut image = Vec::new();
+    let error = snapshot(&interpreter.env(), &mut image).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_image_rejects_deep_nesting() {
+    let block = Block::with_capacity(8 * 1024 * 1024);
+    let arena = block.arena(4 * 1024 * 1024).unwrap();
+
+    // One binding holding a list nested `levels` deep, written by hand so
+    // the encoder's own recursion is not involved.
+    let image = |levels: usize| {
+        let mut image = b"TYSN\x02\x00\x00\x00".to_vec();
+        image.extend(1u64.to_le_bytes());
+        image.extend(1u64.to_le_bytes());
+        image.push(b'x');
+        for depth in 0..levels {
+            image.push(15);
+            image.extend(1u64.to_le_bytes());
+            image.extend((depth as u64 + 1).to_le_bytes());
+        }
+        image.push(0);
+        image
+    };
+
+    assert!(restore(&arena, &image(200)).is_ok());
+    assert_eq!(
+        restore(&arena, &image(100_000)).err(),
+        Some("Image nested too deeply")
+    );
+}
+
+#[test]
+fn test_snapshot_rejects_deep_nesting() {
+    let block = Block::with_capacity(8 * 1024 * 1024);
+    let arena = block.arena(4 * 1024 * 1024).unwrap();
+
+    let nested = |levels: usize| {
+        let code = format!("{}1{}", "[".repeat(levels), "]".repeat(levels));
+        let root = parse_copy(&arena, &code).unwrap();
+        let mut env = Env::new(&arena).unwrap();
+        env.define("x", root[0].payload).unwrap();
+        let mut image = Vec::new();
+        snapshot(&env, &mut image).map(|_| image)
+    };
+
+    let image = nested(200).unwrap();
+    assert!(restore(&arena, &image).is_ok());
+    let error = nested(600).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
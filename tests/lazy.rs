@@ -0,0 +1,218 @@
+use tyson::MemoryBlock as Block;
+use tyson::check::{Arity, Checker};
+use tyson::env::Env;
+use tyson::eval::Interpreter;
+use tyson::expand::expand;
+use tyson::image::{restore, snapshot};
+use tyson::lazy::{STREAMS, State, delay, force, make_promise};
+use tyson::print::print_value;
+use tyson::read::{Atom, parse};
+use tyson::sandbox::Preset;
+
+#[test]
+fn test_force_memoises() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(4096).unwrap();
+
+    let promise = delay(&arena, Atom::Symbol { name: "thunk" }).unwrap();
+    let mut calls = 0;
+
+    for _ in 0..3 {
+        let value = force(promise, |thunk| {
+            assert_eq!(thunk, Atom::Symbol { name: "thunk" });
+            calls += 1;
+            Ok::<_, ()>(Atom::Int { inner: 42 })
+        });
+        assert_eq!(value, Ok(Atom::Int { inner: 42 }));
+    }
+
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn test_force_error_leaves_promise_pending() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(4096).unwrap();
+
+    let promise = delay(&arena, Atom::Nil).unwrap();
+    assert_eq!(force(promise, |_| Err("boom")), Err("boom"));
+
+    let Atom::Promise { promise: cell } = promise else {
+        panic!("delay should build a promise");
+    };
+    assert!(!cell.is_forced());
+    assert_eq!(force(promise, |_| Ok::<_, ()>(Atom::True)), Ok(Atom::True));
+    assert!(cell.is_forced());
+}
+
+#[test]
+fn test_force_reentrant_keeps_first_value() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(4096).unwrap();
+
+    let promise = delay(&arena, Atom::Nil).unwrap();
+    let value = force(promise, |_| {
+        let inner = force(promise, |_| Ok::<_, ()>(Atom::Int { inner: 1 }));
+        assert_eq!(inner, Ok(Atom::Int { inner: 1 }));
+        Ok::<_, ()>(Atom::Int { inner: 2 })
+    });
+
+    assert_eq!(value, Ok(Atom::Int { inner: 1 }));
+}
+
+#[test]
+fn test_make_promise_and_force_values() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(4096).unwrap();
+
+    let ready = make_promise(&arena, Atom::Int { inner: 7 }).unwrap();
+    assert_eq!(
+        force(ready, |_| Err("should not run")),
+        Ok(Atom::Int { inner: 7 })
+    );
+    assert_eq!(make_promise(&arena, ready), Some(ready));
+    assert_eq!(force(Atom::True, |_| Err("should not run")), Ok(Atom::True));
+}
+
+#[test]
+fn test_expand_delay_and_stream_cons() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(stream-cons 1 (f x))").unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+    let expected = parse(&arena, "(cons 1 (%delay (lambda () (f x))))").unwrap();
+    assert_eq!(expanded, expected);
+
+    let root = parse(&arena, "(delay 1 2)").unwrap();
+    assert!(expand(&arena, &root[..root.len()]).is_err());
+}
+
+#[test]
+fn test_stream_library_checks() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(256 * 1024).unwrap();
+
+    let root = parse(&arena, STREAMS).unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+
+    let mut checker = Checker::new();
+    for name in ["null?", "pair?", "promise?", "force", "%delay"] {
+        checker.declare(name, Some(Arity::Exact(1)));
+    }
+
+    let diagnostics = checker.check(&arena, &expanded[..expanded.len()]);
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+}
+
+#[test]
+fn test_promise_image_round_trip() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let pending = delay(&arena, Atom::Symbol { name: "later" }).unwrap();
+    let forced = make_promise(&arena, Atom::Int { inner: 3 }).unwrap();
+
+    let mut env = Env::new(&arena).unwrap();
+    env.define("pending", pending);
+    env.define("forced", forced);
+
+    let mut image = Vec::new();
+    snapshot(&env, &mut image).unwrap();
+    let restored = restore(&arena, &image).unwrap();
+
+    let Some(Atom::Promise { promise }) = restored.get("pending") else {
+        panic!("expected a promise");
+    };
+    assert_eq!(
+        promise.state(),
+        State::Pending {
+            thunk: Atom::Symbol { name: "later" }
+        }
+    );
+
+    let Some(Atom::Promise { promise }) = restored.get("forced") else {
+        panic!("expected a promise");
+    };
+    assert_eq!(
+        promise.state(),
+        State::Forced {
+            value: Atom::Int { inner: 3 }
+        }
+    );
+}
+
+#[test]
+fn test_evaluate_promises_and_streams() {
+    let block = Block::with_capacity(16 * 1024 * 1024);
+    let arena = block.arena(8 * 1024 * 1024).unwrap();
+
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+    interpreter.run(STREAMS).unwrap();
+
+    assert_eq!(
+        interpreter.run("(force (delay (+ 1 2)))"),
+        Ok(Atom::Int { inner: 3 })
+    );
+    assert_eq!(interpreter.run("(force 5)"), Ok(Atom::Int { inner: 5 }));
+    assert_eq!(
+        interpreter.run("(define p (delay (cons 1 2))) (eq? (force p) (force p))"),
+        Ok(Atom::True)
+    );
+    assert_eq!(
+        interpreter.run("(force (make-promise 'ready))"),
+        Ok(Atom::Symbol { name: "ready" })
+    );
+
+    let value = interpreter
+        .run(
+            "(define (from n) (stream-cons n (from (+ n 1))))
+             (stream->list
+               (stream-take (stream-filter (lambda (x) (= (% x 2) 1)) (stream-map (lambda (x) (* x x)) (from 1))) 3))",
+        )
+        .unwrap();
+
+    let mut text = String::new();
+    print_value(&mut text, &value).unwrap();
+    assert_eq!(text, "(1 9 25)");
+}
+
+#[test]
+fn test_promise_image_keeps_sharing_and_cycles() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    // A promise whose value is a list holding the promise itself.
+    let promise = delay(&arena, Atom::Nil).unwrap();
+    let Atom::List { mut body } = parse(&arena, "(self)").unwrap()[0].payload else {
+        panic!("expected a list");
+    };
+    body[0].payload = promise;
+    force(promise, |_| Ok::<_, ()>(Atom::List { body })).unwrap();
+
+    let mut env = Env::new(&arena).unwrap();
+    env.define("a", promise);
+    env.define("b", promise);
+
+    let mut image = Vec::new();
+    snapshot(&env, &mut image).unwrap();
+    let restored = restore(&arena, &image).unwrap();
+
+    let (Some(Atom::Promise { promise: a }), Some(Atom::Promise { promise: b })) =
+        (restored.get("a"), restored.get("b"))
+    else {
+        panic!("expected promises");
+    };
+    assert!(core::ptr::eq(a, b));
+
+    let State::Forced {
+        value: Atom::List { body },
+    } = a.state()
+    else {
+        panic!("expected a forced list");
+    };
+    let Atom::Promise { promise: inner } = body[0].payload else {
+        panic!("expected a promise");
+    };
+    assert!(core::ptr::eq(a, inner));
+}
@@ -35,7 +35,7 @@ fn test_list_operations() {
     assert_eq!(list.len(), 0);
 
     assert!(list.push_back(&3).is_some());
-    assert_eq!(list.head().is_some(), true);
+    assert!(list.head().is_some());
     assert_eq!(list.len(), 1);
 }
 
@@ -0,0 +1,194 @@
+use tyson::MemoryBlock as Block;
+use tyson::check::Checker;
+use tyson::env::Env;
+use tyson::eval::Interpreter;
+use tyson::expand::expand;
+use tyson::image::{restore, snapshot};
+use tyson::print::{print, print_value};
+use tyson::read::{Atom, parse};
+use tyson::sandbox::Preset;
+
+#[test]
+fn test_read_vector_and_map() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "([1 2 3] {a 1 b [2]} [] {})").unwrap();
+    let Atom::List { body } = root[0].payload else {
+        panic!("expected a list");
+    };
+
+    let Atom::Vector { body: vector } = body[0].payload else {
+        panic!("expected a vector");
+    };
+    assert_eq!(vector.len(), 3);
+    assert_eq!(vector[2].payload, Atom::Int { inner: 3 });
+    assert_eq!(vector[0].depth, 2);
+
+    let Atom::Map { map } = body[1].payload else {
+        panic!("expected a map");
+    };
+    assert_eq!(map.len(), 2);
+    assert_eq!(
+        map.get(Atom::Symbol { name: "a" })
+            .map(|value| value.payload),
+        Some(Atom::Int { inner: 1 })
+    );
+    assert!(matches!(
+        map.get(Atom::Symbol { name: "b" })
+            .map(|value| value.payload),
+        Some(Atom::Vector { .. })
+    ));
+    assert_eq!(map.get(Atom::Symbol { name: "c" }), None);
+    assert_eq!(map.get(Atom::Number { inner: 1.0 }), None);
+
+    assert!(matches!(body[2].payload, Atom::Vector { body } if body.is_empty()));
+    assert!(matches!(body[3].payload, Atom::Map { map } if map.is_empty()));
+}
+
+#[test]
+fn test_print_keeps_delimiters() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "[1 {a 2} []]").unwrap();
+    let mut output = String::new();
+    print(&mut output, &root, false).unwrap();
+    assert_eq!(output, "\n[1 \n  {a 2} []]");
+}
+
+#[test]
+fn test_mismatched_delimiters() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    assert!(parse(&arena, "(a b]").is_err());
+    assert!(parse(&arena, "[a b)").is_err());
+    assert!(parse(&arena, "{a b)").is_err());
+    assert!(parse(&arena, "(a [b)]").is_err());
+    assert!(parse(&arena, "(a b").is_err());
+    assert!(parse(&arena, "a b)").is_err());
+}
+
+#[test]
+fn test_map_needs_pairs() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    assert!(parse(&arena, "{a 1 b}").is_err());
+    assert!(parse(&arena, "{a 1 b 2}").is_ok());
+}
+
+#[test]
+fn test_map_keys() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "{1 one \"1\" string #\\1 char #t yes nil none}").unwrap();
+    let Atom::Map { map } = root[0].payload else {
+        panic!("expected a map");
+    };
+    let keys: Vec<_> = map.iter().map(|(key, _)| key).collect();
+    assert_eq!(
+        keys,
+        vec![
+            Atom::Int { inner: 1 },
+            Atom::String { inner: "1" },
+            Atom::Char { inner: '1' },
+            Atom::True,
+            Atom::Nil,
+        ]
+    );
+
+    assert!(parse(&arena, "{(a) 1}").is_err());
+    assert!(parse(&arena, "{1.5 x}").is_err());
+    assert!(parse(&arena, "{a 1 b 2 a 3}").is_err());
+    assert!(parse(&arena, "{a 1 \"a\" 2}").is_ok());
+}
+
+#[test]
+fn test_maps_compare_by_entries() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "{a 1 b 2} {b 2 a 1} {a 1 b 3}").unwrap();
+    assert_eq!(root[0].payload, root[1].payload);
+    assert_ne!(root[0].payload, root[2].payload);
+}
+
+#[test]
+fn test_expand_and_check_inside_literals() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "[(-> x f) {k (<- y g)}]").unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+    assert_eq!(expanded, parse(&arena, "[(f x) {k (g y)}]").unwrap());
+
+    // Map keys are data, so only `f`, `x`, `g` and `y` are unbound.
+    let diagnostics = Checker::new().check(&arena, &expanded[..expanded.len()]);
+    assert_eq!(diagnostics.len(), 4);
+}
+
+#[test]
+fn test_literals_image_round_trip() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "([1 2] {a \"b\"})").unwrap();
+    let Atom::List { body } = root[0].payload else {
+        panic!("expected a list");
+    };
+
+    let mut env = Env::new(&arena).unwrap();
+    env.define("vector", body[0].payload);
+    env.define("map", body[1].payload);
+
+    let mut image = Vec::new();
+    snapshot(&env, &mut image).unwrap();
+    let restored = restore(&arena, &image).unwrap();
+
+    assert_eq!(restored.get("vector"), Some(body[0].payload));
+    assert_eq!(restored.get("map"), Some(body[1].payload));
+}
+
+#[test]
+fn test_evaluate_literals() {
+    let block = Block::with_capacity(16 * 1024 * 1024);
+    let arena = block.arena(4 * 1024 * 1024).unwrap();
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+
+    let mut show = |code: &'static str| {
+        let mut text = String::new();
+        print_value(&mut text, &interpreter.run(code).unwrap()).unwrap();
+        text
+    };
+    assert_eq!(show("[1 (+ 1 2) \"x\"]"), "[1 3 \"x\"]");
+    assert_eq!(show("(define x 5) {a x b (* x 2)}"), "{a 5 b 10}");
+    assert_eq!(show("(vector-ref [1 2 3] 2)"), "3");
+    assert_eq!(show("(vector-length [])"), "0");
+    assert_eq!(show("(equal? [1 [2]] [1 [2]])"), "#t");
+
+    assert_eq!(
+        interpreter.run("(vector-ref [1] 1)"),
+        Err("Vector index out of range")
+    );
+}
+
+#[test]
+fn test_quoted_literals_are_data() {
+    let block = Block::with_capacity(16 * 1024 * 1024);
+    let arena = block.arena(4 * 1024 * 1024).unwrap();
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+
+    let mut show = |code: &'static str| {
+        let mut text = String::new();
+        print_value(&mut text, &interpreter.run(code).unwrap()).unwrap();
+        text
+    };
+    assert_eq!(show("'[1 (+ 1 2)]"), "[1 (+ 1 2)]");
+    assert_eq!(show("'{a (+ 1 1)}"), "{a (+ 1 1)}");
+    assert_eq!(show("'[x [y {k z}]]"), "[x [y {k z}]]");
+    assert_eq!(show("(vector-ref '[a b] 1)"), "b");
+    assert_eq!(show("(equal? '[1 (2)] [1 '(2)])"), "#t");
+}
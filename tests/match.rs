@@ -0,0 +1,173 @@
+use tyson::MemoryBlock as Block;
+use tyson::check::{Arity, Checker};
+use tyson::eval::Interpreter;
+use tyson::expand::expand;
+use tyson::print::print_value;
+use tyson::read::{Atom, parse};
+use tyson::sandbox::Preset;
+
+#[test]
+fn test_match_literals_and_wildcard() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(match x (1 'one) (_ 'other))").unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+    let expected = parse(
+        &arena,
+        "(let ((%match-0 x))
+           (let ((%fail-2 (lambda ()
+                            (let ((%fail-1 (lambda () (%match-error %match-0))))
+                              'other))))
+             (if (equal? %match-0 1) 'one (%fail-2))))",
+    )
+    .unwrap();
+    assert_eq!(expanded, expected);
+}
+
+#[test]
+fn test_match_list_with_ellipsis() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(match xs ((a b rest ...) (f a b rest)))").unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+    let expected = parse(
+        &arena,
+        "(let ((%match-0 xs))
+           (let ((%fail-1 (lambda () (%match-error %match-0))))
+             (if (pair? %match-0)
+               (let ((a (car %match-0)))
+                 (let ((%tail-2 (cdr %match-0)))
+                   (if (pair? %tail-2)
+                     (let ((b (car %tail-2)))
+                       (let ((%tail-3 (cdr %tail-2)))
+                         (let ((rest %tail-3))
+                           (f a b rest))))
+                     (%fail-1))))
+               (%fail-1))))",
+    )
+    .unwrap();
+    assert_eq!(expanded, expected);
+}
+
+#[test]
+fn test_match_nested_vector_guard_and_record() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(match v ([(? number? n) ($ point x _)] (g n x)))").unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+    let expected = parse(
+        &arena,
+        "(let ((%match-0 v))
+           (let ((%fail-1 (lambda () (%match-error %match-0))))
+             (if (vector? %match-0)
+               (if (= (vector-length %match-0) 2)
+                 (let ((%part-2 (vector-ref %match-0 0)))
+                   (if (number? %part-2)
+                     (let ((n %part-2))
+                       (let ((%part-3 (vector-ref %match-0 1)))
+                         (if (%record? point %part-3)
+                           (let ((x (%record-ref point %part-3 0)))
+                             (g n x))
+                           (%fail-1))))
+                     (%fail-1)))
+                 (%fail-1))
+               (%fail-1))))",
+    )
+    .unwrap();
+    assert_eq!(expanded, expected);
+}
+
+#[test]
+fn test_match_malformed() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    for code in [
+        "(match)",
+        "(match x y)",
+        "(match x ((a) ))",
+        "(match x ((... a) 1))",
+        "(match x ((a (b) ...) 1))",
+        "(match x ({a 1} 1))",
+    ] {
+        let root = parse(&arena, code).unwrap();
+        assert!(expand(&arena, &root[..root.len()]).is_err(), "{code}");
+    }
+}
+
+#[test]
+fn test_match_bindings_check() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(128 * 1024).unwrap();
+
+    let root = parse(
+        &arena,
+        "(define (sum xs)
+           (match xs
+             (() 0)
+             ((x rest ...) (+ x (sum rest)))
+             ([a b] (+ a b c))))",
+    )
+    .unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+
+    let mut checker = Checker::new();
+    for name in ["null?", "pair?", "vector?", "vector-length", "%match-error"] {
+        checker.declare(name, Some(Arity::Exact(1)));
+    }
+    checker.declare("vector-ref", Some(Arity::Exact(2)));
+
+    let diagnostics = checker.check(&arena, &expanded[..expanded.len()]);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+}
+
+#[test]
+fn test_evaluate_match() {
+    let block = Block::with_capacity(4 * 1024 * 1024);
+    let arena = block.arena(1024 * 1024).unwrap();
+
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+    interpreter
+        .run(
+            "(define-record-type point (make-point x y) point? (x point-x) (y point-y))
+             (define (seven? x) (eqv? x 7))
+             (define (describe v)
+               (match v
+                 (0 'zero)
+                 (() 'empty)
+                 ((? seven? n) n)
+                 (($ point x y) (+ x y))
+                 ([a rest ...] rest)
+                 ((a b ...) b)))",
+        )
+        .unwrap();
+
+    assert_eq!(
+        interpreter.run("(describe 0)"),
+        Ok(Atom::Symbol { name: "zero" })
+    );
+    assert_eq!(
+        interpreter.run("(describe '())"),
+        Ok(Atom::Symbol { name: "empty" })
+    );
+    assert_eq!(interpreter.run("(describe 7)"), Ok(Atom::Int { inner: 7 }));
+    assert_eq!(
+        interpreter.run("(describe (make-point 3 4))"),
+        Ok(Atom::Int { inner: 7 })
+    );
+
+    for (code, expected) in [
+        ("(describe [1 2 3])", "[2 3]"),
+        ("(describe '(1 2 3))", "(2 3)"),
+    ] {
+        let value = interpreter.run(code).unwrap();
+        let mut text = String::new();
+        print_value(&mut text, &value).unwrap();
+        assert_eq!(text, expected, "{code}");
+    }
+
+    assert!(interpreter.run("(describe \"text\")").is_err());
+}
@@ -0,0 +1,326 @@
+use tyson::MemoryBlock as Block;
+use tyson::env::Env;
+use tyson::eval::Interpreter;
+use tyson::image::{restore, snapshot};
+use tyson::numeric::{add, denominator, div, exact, inexact, is_exact, mul, numerator, sub};
+use tyson::print::{print, print_value};
+use tyson::read::{Atom, parse};
+use tyson::sandbox::Preset;
+
+fn render(atom: &Atom) -> String {
+    match atom {
+        Atom::BigInt { inner } => inner.to_string(),
+        Atom::Rational { inner } => inner.to_string(),
+        Atom::Int { inner } => inner.to_string(),
+        Atom::Number { inner } => inner.to_string(),
+        _ => panic!("not a number: {atom:?}"),
+    }
+}
+
+#[test]
+fn test_read_big_integer() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(
+        &arena,
+        "(123456789012345678901234 -98765432109876543210 42)",
+    )
+    .unwrap();
+    let Atom::List { body } = root[0].payload else {
+        panic!("expected a list");
+    };
+
+    assert!(matches!(body[0].payload, Atom::BigInt { .. }));
+    assert_eq!(render(&body[0].payload), "123456789012345678901234");
+    assert_eq!(render(&body[1].payload), "-98765432109876543210");
+    assert_eq!(body[2].payload, Atom::Int { inner: 42 });
+}
+
+#[test]
+fn test_read_i64_bounds() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(
+        &arena,
+        "(9223372036854775807 -9223372036854775808 9223372036854775808)",
+    )
+    .unwrap();
+    let Atom::List { body } = root[0].payload else {
+        panic!("expected a list");
+    };
+
+    assert_eq!(body[0].payload, Atom::Int { inner: i64::MAX });
+    assert_eq!(body[1].payload, Atom::Int { inner: i64::MIN });
+    assert!(matches!(body[2].payload, Atom::BigInt { .. }));
+    assert_eq!(render(&body[2].payload), "9223372036854775808");
+}
+
+#[test]
+fn test_read_rationals() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(3/4 -6/8 4/2 0/5)").unwrap();
+    let Atom::List { body } = root[0].payload else {
+        panic!("expected a list");
+    };
+
+    assert_eq!(render(&body[0].payload), "3/4");
+    assert_eq!(render(&body[1].payload), "-3/4");
+    assert_eq!(body[2].payload, Atom::Int { inner: 2 });
+    assert_eq!(body[3].payload, Atom::Int { inner: 0 });
+
+    let mut output = String::new();
+    print(&mut output, &root, false).unwrap();
+    assert_eq!(output, "\n(3/4 -3/4 2 0)");
+}
+
+#[test]
+fn test_exact_division() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let one = Atom::Int { inner: 1 };
+    let three = Atom::Int { inner: 3 };
+    let third = div(&arena, &one, &three).unwrap();
+
+    assert!(is_exact(&third));
+    assert_eq!(render(&third), "1/3");
+    assert_eq!(mul(&arena, &third, &three).unwrap(), one);
+    assert_eq!(render(&add(&arena, &third, &third).unwrap()), "2/3");
+    assert_eq!(render(&sub(&arena, &third, &one).unwrap()), "-2/3");
+    assert_eq!(
+        div(&arena, &one, &Atom::Int { inner: 0 }),
+        Err("Division by zero")
+    );
+}
+
+#[test]
+fn test_overflow_promotes_to_big_integer() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let max = Atom::Int { inner: i64::MAX };
+    let sum = add(&arena, &max, &max).unwrap();
+    assert_eq!(render(&sum), "18446744073709551614");
+
+    let square = mul(&arena, &sum, &sum).unwrap();
+    assert_eq!(render(&square), "340282366920938463389587631136930004996");
+
+    let back = div(&arena, &square, &sum).unwrap();
+    assert_eq!(back, sum);
+    assert_eq!(sub(&arena, &sum, &max).unwrap(), max);
+}
+
+#[test]
+fn test_inexact_contagion() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let half = div(&arena, &Atom::Int { inner: 1 }, &Atom::Int { inner: 2 }).unwrap();
+    let sum = add(&arena, &half, &Atom::Number { inner: 0.25 }).unwrap();
+
+    assert_eq!(sum, Atom::Number { inner: 0.75 });
+    assert!(add(&arena, &half, &Atom::Nil).is_err());
+}
+
+#[test]
+fn test_exact_inexact_conversion() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let exact_value = exact(&arena, &Atom::Number { inner: 0.375 }).unwrap();
+    assert_eq!(render(&exact_value), "3/8");
+    assert_eq!(
+        inexact(&exact_value).unwrap(),
+        Atom::Number { inner: 0.375 }
+    );
+
+    let whole = exact(&arena, &Atom::Number { inner: -1e20 }).unwrap();
+    assert_eq!(render(&whole), "-100000000000000000000");
+    assert!(exact(&arena, &Atom::Number { inner: f64::NAN }).is_err());
+}
+
+#[test]
+fn test_numerator_denominator() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let value = div(&arena, &Atom::Int { inner: -6 }, &Atom::Int { inner: 4 }).unwrap();
+    assert_eq!(numerator(&arena, &value).unwrap(), Atom::Int { inner: -3 });
+    assert_eq!(denominator(&arena, &value).unwrap(), Atom::Int { inner: 2 });
+
+    let seven = Atom::Int { inner: 7 };
+    assert_eq!(numerator(&arena, &seven).unwrap(), seven);
+    assert_eq!(denominator(&arena, &seven).unwrap(), Atom::Int { inner: 1 });
+
+    let inexact_value = Atom::Number { inner: 0.75 };
+    assert_eq!(
+        numerator(&arena, &inexact_value).unwrap(),
+        Atom::Number { inner: 3.0 }
+    );
+    assert_eq!(
+        denominator(&arena, &inexact_value).unwrap(),
+        Atom::Number { inner: 4.0 }
+    );
+}
+
+#[test]
+fn test_numbers_image_round_trip() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(123456789012345678901234 -22/7)").unwrap();
+    let Atom::List { body } = root[0].payload else {
+        panic!("expected a list");
+    };
+
+    let mut env = Env::new(&arena).unwrap();
+    env.define("big", body[0].payload);
+    env.define("ratio", body[1].payload);
+
+    let mut image = Vec::new();
+    snapshot(&env, &mut image).unwrap();
+    let restored = restore(&arena, &image).unwrap();
+
+    assert_eq!(restored.get("big"), Some(body[0].payload));
+    assert_eq!(restored.get("ratio"), Some(body[1].payload));
+}
+
+#[test]
+fn test_evaluate_numeric_tower() {
+    let block = Block::with_capacity(16 * 1024 * 1024);
+    let arena = block.arena(4 * 1024 * 1024).unwrap();
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+
+    let mut eval = |code: &'static str| render(&interpreter.run(code).unwrap());
+    assert_eq!(eval("(^ 2 100)"), "1267650600228229401496703205376");
+    assert_eq!(eval("(^ 2 -2)"), "1/4");
+    assert_eq!(eval("(+ 1/3 1/6)"), "1/2");
+    assert_eq!(eval("(* 9223372036854775807 2)"), "18446744073709551614");
+    assert_eq!(eval("(numerator 6/4)"), "3");
+    assert_eq!(eval("(exact 0.5)"), "1/2");
+    assert_eq!(eval("(inexact 1/4)"), "0.25");
+    assert_eq!(eval("(% -7 2)"), "1");
+
+    assert_eq!(interpreter.run("(< 1/3 0.5 2/3)"), Ok(Atom::True));
+    assert_eq!(interpreter.run("(= 1/2 0.5)"), Ok(Atom::True));
+}
+
+fn read<'a>(arena: &'a tyson::Arena<'a>, code: &'a str) -> Atom<'a> {
+    parse(arena, code).unwrap()[0].payload
+}
+
+#[test]
+fn test_read_radix_prefixes() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    assert_eq!(read(&arena, "#xff"), Atom::Int { inner: 255 });
+    assert_eq!(read(&arena, "#XFF"), Atom::Int { inner: 255 });
+    assert_eq!(read(&arena, "#b-101"), Atom::Int { inner: -5 });
+    assert_eq!(read(&arena, "#o777"), Atom::Int { inner: 511 });
+    assert_eq!(read(&arena, "#d42"), Atom::Int { inner: 42 });
+    assert_eq!(render(&read(&arena, "#x1/a")), "1/10");
+    assert_eq!(
+        render(&read(&arena, "#xffffffffffffffffffff")),
+        "1208925819614629174706175"
+    );
+}
+
+#[test]
+fn test_read_decimal_forms() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    assert_eq!(read(&arena, "1e-9"), Atom::Number { inner: 1e-9 });
+    assert_eq!(read(&arena, "2.5E3"), Atom::Number { inner: 2500.0 });
+    assert_eq!(read(&arena, ".5"), Atom::Number { inner: 0.5 });
+    assert_eq!(read(&arena, "-.25"), Atom::Number { inner: -0.25 });
+    assert_eq!(read(&arena, "+7"), Atom::Int { inner: 7 });
+    assert_eq!(read(&arena, "+1.5"), Atom::Number { inner: 1.5 });
+    assert_eq!(read(&arena, "1_000_000"), Atom::Int { inner: 1_000_000 });
+    assert_eq!(read(&arena, "#xff_ff"), Atom::Int { inner: 0xffff });
+    assert_eq!(read(&arena, "3_000.5"), Atom::Number { inner: 3000.5 });
+
+    // Signs and dots on their own are still symbols.
+    assert_eq!(read(&arena, "+"), Atom::Add);
+    assert_eq!(read(&arena, "..."), Atom::Symbol { name: "..." });
+    assert_eq!(read(&arena, "-x"), Atom::Symbol { name: "-x" });
+}
+
+#[test]
+fn test_read_special_floats() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(+inf.0 -inf.0 +nan.0 -nan.0)").unwrap();
+    let Atom::List { body } = root[0].payload else {
+        panic!("expected a list");
+    };
+
+    assert_eq!(
+        body[0].payload,
+        Atom::Number {
+            inner: f64::INFINITY
+        }
+    );
+    assert_eq!(
+        body[1].payload,
+        Atom::Number {
+            inner: f64::NEG_INFINITY
+        }
+    );
+    assert!(matches!(body[2].payload, Atom::Number { inner } if inner.is_nan()));
+    assert!(matches!(body[3].payload, Atom::Number { inner } if inner.is_nan()));
+
+    let root = parse(&arena, "+inf.0 -inf.0 +nan.0").unwrap();
+    let mut printed = String::new();
+    print(&mut printed, &root[..root.len()], false).unwrap();
+    assert_eq!(printed, "+inf.0 -inf.0 +nan.0");
+}
+
+#[test]
+fn test_malformed_numbers_are_errors() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    for code in [
+        "1e", "1.2.3", "12abc", "#xfg", "#b102", "1__0", "1_", "_1e5", "#x_ff", "1/0", "1/-2",
+        "1e5/2", "#x1.5", "+.e1",
+    ] {
+        match parse(&arena, code) {
+            Err(error) => assert_eq!(
+                error.kind,
+                tyson::read::ParseErrorKind::InvalidNumber,
+                "{code}"
+            ),
+            Ok(root) if code.starts_with(['_', '+']) => {
+                assert!(matches!(root[0].payload, Atom::Symbol { .. }), "{code}")
+            }
+            Ok(root) => panic!("{code} read as {:?}", root[0].payload),
+        }
+    }
+}
+
+#[test]
+fn test_inexact_parts_stay_inexact() {
+    let block = Block::with_capacity(8 * 1024 * 1024);
+    let arena = block.arena(4 * 1024 * 1024).unwrap();
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+
+    assert_eq!(
+        interpreter.run("(numerator 0.5)"),
+        Ok(Atom::Number { inner: 1.0 })
+    );
+    assert_eq!(
+        interpreter.run("(denominator 0.5)"),
+        Ok(Atom::Number { inner: 2.0 })
+    );
+
+    let mut text = String::new();
+    print_value(&mut text, &interpreter.run("(numerator 0.5)").unwrap()).unwrap();
+    assert_eq!(text, "1.0");
+}
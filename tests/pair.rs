@@ -0,0 +1,39 @@
+use tyson::MemoryBlock as Block;
+use tyson::pair::{car, cdr, cons, is_null, is_pair, items, list};
+use tyson::read::{Atom, parse};
+
+#[test]
+fn test_pairs_and_arrays_are_both_lists() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let one = Atom::Int { inner: 1 };
+    let two = Atom::Int { inner: 2 };
+    let pairs = cons(&arena, one, cons(&arena, two, Atom::Void).unwrap()).unwrap();
+    let array = list(&arena, &[one, two]).unwrap();
+    let read = parse(&arena, "'(1 2)").unwrap()[0].payload;
+
+    for list in [pairs, array, read] {
+        assert!(is_pair(&list));
+        assert_eq!(car(&list), Ok(one));
+        assert_eq!(car(&cdr(&list).unwrap()), Ok(two));
+        assert!(is_null(&cdr(&cdr(&list).unwrap()).unwrap()));
+        assert_eq!(items(list).collect::<Vec<_>>(), [one, two]);
+    }
+
+    assert_eq!(list(&arena, &[]), Ok(Atom::Void));
+    assert_eq!(car(&Atom::Void), Err("Not a pair"));
+    assert_eq!(cdr(&one), Err("Not a pair"));
+}
+
+#[test]
+fn test_improper_lists_keep_their_tail() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let dotted = cons(&arena, Atom::Int { inner: 1 }, Atom::Int { inner: 2 }).unwrap();
+    let mut iter = items(dotted);
+    assert_eq!(iter.by_ref().count(), 1);
+    assert_eq!(iter.rest(), Atom::Int { inner: 2 });
+    assert!(!is_null(&iter.rest()));
+}
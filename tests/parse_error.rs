@@ -0,0 +1,118 @@
+use tyson::MemoryBlock as Block;
+use tyson::read::{ParseErrorKind, Span, parse};
+
+fn error(code: &str) -> (ParseErrorKind, Span) {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let error = parse(&arena, code).unwrap_err();
+    (error.kind, error.span)
+}
+
+#[test]
+fn test_delimiter_errors() {
+    let (kind, span) = error("(a b]");
+    assert_eq!(kind, ParseErrorKind::UnbalancedDelimiter);
+    assert_eq!(span.start, 4);
+
+    let (kind, span) = error("x)");
+    assert_eq!(kind, ParseErrorKind::UnbalancedDelimiter);
+    assert_eq!(span.column, 2);
+
+    // An unclosed form points at its opening delimiter.
+    let (kind, span) = error("(ok)\n  (a [b c]");
+    assert_eq!(kind, ParseErrorKind::UnexpectedEof);
+    assert_eq!((span.line, span.column), (2, 3));
+
+    assert_eq!(error("{a 1 b}").0, ParseErrorKind::OddMapLiteral);
+
+    // Map key errors point at the key.
+    let (kind, span) = error("{a 1 (b) 2}");
+    assert_eq!(kind, ParseErrorKind::InvalidMapKey);
+    assert_eq!(span.start, 5);
+
+    let (kind, span) = error("{a 1 b 2 a 3}");
+    assert_eq!(kind, ParseErrorKind::DuplicateMapKey);
+    assert_eq!(span.start, 9);
+}
+
+#[test]
+fn test_token_errors() {
+    let (kind, span) = error("(print \"never closed)");
+    assert_eq!(kind, ParseErrorKind::UnterminatedString);
+    assert_eq!(span.start, 7);
+
+    assert_eq!(error("(a #;)").0, ParseErrorKind::MalformedComment);
+    assert_eq!(
+        error("#| open #| nested |#").0,
+        ParseErrorKind::UnterminatedComment
+    );
+    assert_eq!(error("(+ 1.2.3 4)").0, ParseErrorKind::InvalidNumber);
+    assert_eq!(error("1/0").0, ParseErrorKind::InvalidNumber);
+    assert_eq!(error("-5-").0, ParseErrorKind::InvalidNumber);
+    assert_eq!(error("#\\bogus").0, ParseErrorKind::InvalidCharacter);
+    assert_eq!(error("#\\").0, ParseErrorKind::InvalidCharacter);
+}
+
+#[test]
+fn test_quote_errors() {
+    assert_eq!(error("'").0, ParseErrorKind::UnexpectedEof);
+    assert_eq!(error("(a ')").0, ParseErrorKind::InvalidQuote);
+    assert_eq!(error("''a").0, ParseErrorKind::InvalidQuote);
+}
+
+#[test]
+fn test_quoted_literals_read_as_themselves() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    assert_eq!(
+        parse(&arena, "'1 '\"s\" '#t").unwrap(),
+        parse(&arena, "1 \"s\" #t").unwrap()
+    );
+}
+
+#[test]
+fn test_error_display() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let error = parse(&arena, "(a\n  \"b").unwrap_err();
+    assert_eq!(error.to_string(), "2:3: unterminated string");
+}
+
+#[test]
+fn test_empty_and_trailing_input() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    assert_eq!(parse(&arena, "").unwrap().len(), 0);
+    assert_eq!(parse(&arena, "  \n ").unwrap().len(), 0);
+    assert_eq!(parse(&arena, "foo").unwrap().len(), 1);
+    assert_eq!(parse(&arena, "42").unwrap().len(), 1);
+}
+
+#[test]
+fn test_reader_never_panics() {
+    const PIECES: &[&str] = &[
+        "(", ")", "[", "]", "{", "}", "'", "`", ";", ";;", "\"", "#", "#\\", "\\", "-", ".", "/",
+        "1", "2.5", "a", " ", "\n", "#t", "nil", "+", "ñ", "#|", "|#", "#;",
+    ];
+
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(256 * 1024).unwrap();
+
+    let mut seed: u64 = 0x9e3779b97f4a7c15;
+    for _ in 0..5000 {
+        let mut code = String::new();
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        for i in 0..(seed % 12) {
+            code.push_str(PIECES[((seed >> (i * 5)) % PIECES.len() as u64) as usize]);
+        }
+
+        let _ = parse(&arena, &code);
+        arena.clear();
+    }
+}
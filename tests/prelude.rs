@@ -0,0 +1,145 @@
+use tyson::MemoryBlock as Block;
+use tyson::check::{Arity, Checker};
+use tyson::env::Env;
+use tyson::eval::Interpreter;
+use tyson::expand::expand;
+use tyson::prelude::{PRELUDE, root};
+use tyson::print::print_value;
+use tyson::read::{Atom, parse};
+
+fn assert_expands(code: &'static str, expected: &'static str) {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, code).unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+    let expected = parse(&arena, expected).unwrap();
+
+    assert_eq!(expanded, expected);
+}
+
+#[test]
+fn test_when_and_unless() {
+    assert_expands("(when (> x 0) (f x) x)", "(if (> x 0) (begin (f x) x) nil)");
+    assert_expands("(unless (null? xs) (f xs))", "(if (null? xs) nil (f xs))");
+}
+
+#[test]
+fn test_cond() {
+    assert_expands(
+        "(cond ((< x 0) 'neg) ((= x 0)) (else 'pos))",
+        "(if (< x 0) 'neg (or (= x 0) 'pos))",
+    );
+    assert_expands(
+        "(cond ((assv x xs) => cdr) (else #f))",
+        "(let ((%cond-0 (assv x xs))) (if %cond-0 (cdr %cond-0) #f))",
+    );
+    assert_expands(
+        "(cond ((f x) (g x) (h x)))",
+        "(if (f x) (begin (g x) (h x)) nil)",
+    );
+}
+
+#[test]
+fn test_case() {
+    assert_expands(
+        "(case (f x) ((a b) 1) ((2) 'two) (else 3))",
+        "(let ((%case-0 (f x)))
+           (if (or (eqv? %case-0 'a) (eqv? %case-0 'b))
+               1
+               (if (or (eqv? %case-0 2)) 'two 3)))",
+    );
+}
+
+#[test]
+fn test_conditional_errors() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    for code in [
+        "(cond (else 1) (x 2))",
+        "(cond x)",
+        "(case)",
+        "(case x (a 1))",
+        "(when)",
+        "(unless x)",
+    ] {
+        let root = parse(&arena, code).unwrap();
+        assert!(expand(&arena, &root[..root.len()]).is_err(), "{code}");
+    }
+}
+
+#[test]
+fn test_prelude_checks() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(256 * 1024).unwrap();
+
+    let root = parse(&arena, PRELUDE).unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+
+    let mut checker = Checker::new();
+    for name in [
+        "null?",
+        "pair?",
+        "car",
+        "cdr",
+        "string-length",
+        "coroutine?",
+    ] {
+        checker.declare(name, Some(Arity::Exact(1)));
+    }
+    for name in ["equal?", "string=?", "generator-for-each"] {
+        checker.declare(name, Some(Arity::Exact(2)));
+    }
+    checker.declare("substring", Some(Arity::Exact(3)));
+    checker.declare("string-append", Some(Arity::AtLeast(0)));
+
+    let diagnostics = checker.check(&arena, &expanded[..expanded.len()]);
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+}
+
+#[test]
+fn test_user_env_extends_prelude() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(256 * 1024).unwrap();
+
+    let prelude = root(&arena).unwrap();
+    let mut user = Env::extend(&prelude, 4).unwrap();
+    user.define("map", Atom::Int { inner: 1 });
+
+    for name in ["fold-left", "reverse", "filter", "assoc", "string-join"] {
+        assert!(user.contains(name), "{name}");
+    }
+    assert_eq!(user.get("map"), Some(Atom::Int { inner: 1 }));
+    assert!(matches!(prelude.get("map"), Some(Atom::Closure { .. })));
+}
+
+#[test]
+fn test_prelude_procedures_run() {
+    let block = Block::with_capacity(16 * 1024 * 1024);
+    let arena = block.arena(8 * 1024 * 1024).unwrap();
+
+    let prelude = root(&arena).unwrap();
+    let mut interpreter = Interpreter::new(&arena, Env::extend(&prelude, 4).unwrap());
+
+    for (code, expected) in [
+        ("(length '(a b c))", "3"),
+        ("(reverse (iota 4))", "(3 2 1 0)"),
+        ("(map (lambda (x) (* x x)) '(1 2 3))", "(1 4 9)"),
+        (
+            "(filter (lambda (x) (> x 1)) (append '(1 2) '(3)))",
+            "(2 3)",
+        ),
+        ("(fold-left + 0 (iota 5))", "10"),
+        ("(list-ref '(a b c) 2)", "c"),
+        ("(member 2 '(1 2 3))", "(2 3)"),
+        ("(every pair? '((1) (2)))", "#t"),
+        ("(string-join '(\"a\" \"b\" \"c\") \", \")", "\"a, b, c\""),
+        ("(string-suffix? \"lo\" \"hello\")", "#t"),
+    ] {
+        let value = interpreter.run(code).unwrap();
+        let mut text = String::new();
+        print_value(&mut text, &value).unwrap();
+        assert_eq!(text, expected, "{code}");
+    }
+}
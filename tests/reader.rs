@@ -0,0 +1,111 @@
+use tyson::MemoryBlock as Block;
+use tyson::read::{Atom, ParseErrorKind, Reader, parse};
+
+#[test]
+fn test_forms_are_returned_once_closed() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut reader = Reader::new(&arena);
+
+    reader.feed("(define (sq x)\n");
+    assert_eq!(reader.next_form(), Ok(None));
+    assert!(reader.needs_more());
+
+    reader.feed("  (* x x)) (sq");
+    let form = reader.next_form().unwrap().unwrap();
+    assert_eq!(form, parse(&arena, "(define (sq x) (* x x))").unwrap()[0]);
+    assert_eq!(reader.next_form(), Ok(None));
+    assert!(reader.needs_more());
+
+    reader.feed(" 3)\n");
+    let form = reader.next_form().unwrap().unwrap();
+    assert_eq!(form, parse(&arena, "(sq 3)").unwrap()[0]);
+    assert!(!reader.needs_more());
+}
+
+#[test]
+fn test_several_forms_in_one_chunk() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut reader = Reader::new(&arena);
+
+    reader.feed("1 \"two\" 'three ;; trailing comment\n");
+    let mut forms = Vec::new();
+    while let Some(form) = reader.next_form().unwrap() {
+        forms.push(form.payload);
+    }
+
+    assert_eq!(
+        forms,
+        vec![
+            Atom::Int { inner: 1 },
+            Atom::String { inner: "two" },
+            Atom::Quoted { name: "three" }
+        ]
+    );
+    assert!(!reader.needs_more());
+}
+
+#[test]
+fn test_incomplete_input_is_not_an_error() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    for partial in ["(a [b", "\"open string", "'", "(quote x) '", "sym"] {
+        let mut reader = Reader::new(&arena);
+        reader.feed(partial);
+        while reader.next_form().unwrap().is_some() {}
+        assert!(reader.needs_more(), "{partial}");
+    }
+
+    assert!(parse(&arena, "(a [b").unwrap_err().is_incomplete());
+    assert!(parse(&arena, "\"open").unwrap_err().is_incomplete());
+    assert!(!parse(&arena, "(a]").unwrap_err().is_incomplete());
+}
+
+#[test]
+fn test_symbols_can_span_chunks() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut reader = Reader::new(&arena);
+
+    reader.feed("hel");
+    assert_eq!(reader.next_form(), Ok(None));
+    reader.feed("lo 12");
+    assert_eq!(
+        reader.next_form().unwrap().unwrap().payload,
+        Atom::Symbol { name: "hello" }
+    );
+    assert_eq!(reader.next_form(), Ok(None));
+
+    reader.feed("3");
+    assert_eq!(
+        reader.finish().unwrap().unwrap().payload,
+        Atom::Int { inner: 123 }
+    );
+    assert_eq!(reader.finish(), Ok(None));
+}
+
+#[test]
+fn test_syntax_errors_discard_the_bad_form() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut reader = Reader::new(&arena);
+
+    reader.feed("(a b]\n(ok)");
+    assert_eq!(
+        reader.next_form().unwrap_err().kind,
+        ParseErrorKind::UnbalancedDelimiter
+    );
+    assert_eq!(
+        reader.next_form().unwrap().unwrap(),
+        parse(&arena, "(ok)").unwrap()[0]
+    );
+
+    reader.feed("(never closed");
+    assert_eq!(
+        reader.finish().unwrap_err().kind,
+        ParseErrorKind::UnexpectedEof
+    );
+    assert!(!reader.needs_more());
+}
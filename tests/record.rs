@@ -0,0 +1,233 @@
+use tyson::MemoryBlock as Block;
+use tyson::check::{Arity, Checker};
+use tyson::env::Env;
+use tyson::eval::Interpreter;
+use tyson::expand::expand;
+use tyson::image::{restore, snapshot};
+use tyson::print::print;
+use tyson::read::{Atom, Expression, Span, parse};
+use tyson::record::{is_record, make_record, record_ref, record_set, record_type};
+use tyson::sandbox::Preset;
+
+#[test]
+fn test_record_accessors_and_modifiers() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let point = record_type(&arena, "point", &["x", "y"]).unwrap();
+    let p = make_record(
+        &arena,
+        &point,
+        &[Atom::Int { inner: 1 }, Atom::Int { inner: 2 }],
+    )
+    .unwrap();
+
+    assert_eq!(is_record(&point, &p), Ok(Atom::True));
+    assert_eq!(record_ref(&point, &p, 1), Ok(Atom::Int { inner: 2 }));
+    assert_eq!(
+        record_set(&point, &p, 0, Atom::Int { inner: 5 }),
+        Ok(Atom::Void)
+    );
+    assert_eq!(record_ref(&point, &p, 0), Ok(Atom::Int { inner: 5 }));
+    assert!(record_ref(&point, &p, 2).is_err());
+    assert!(make_record(&arena, &point, &[Atom::Nil]).is_err());
+}
+
+#[test]
+fn test_record_types_are_distinct() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let point = record_type(&arena, "point", &["x", "y"]).unwrap();
+    let other = record_type(&arena, "point", &["x", "y"]).unwrap();
+    let p = make_record(&arena, &point, &[Atom::Nil, Atom::Nil]).unwrap();
+
+    assert_eq!(is_record(&other, &p), Ok(Atom::False));
+    assert_eq!(is_record(&point, &Atom::Nil), Ok(Atom::False));
+    assert!(record_ref(&other, &p, 0).is_err());
+    assert!(is_record(&Atom::Nil, &p).is_err());
+}
+
+#[test]
+fn test_print_record() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let order = record_type(&arena, "order", &["id", "item"]).unwrap();
+    let payload = make_record(
+        &arena,
+        &order,
+        &[Atom::Int { inner: 7 }, Atom::Symbol { name: "tea" }],
+    )
+    .unwrap();
+
+    let mut output = String::new();
+    let record = Expression {
+        depth: 0,
+        payload,
+        span: Span::default(),
+    };
+    print(&mut output, &[record], false).unwrap();
+    assert_eq!(output, "#<record order 7 tea>");
+
+    let mut output = String::new();
+    print(
+        &mut output,
+        &[Expression {
+            depth: 0,
+            payload: order,
+            span: Span::default(),
+        }],
+        false,
+    )
+    .unwrap();
+    assert_eq!(output, "#<record-type order>");
+}
+
+#[test]
+fn test_expand_define_record_type() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(
+        &arena,
+        "(define-record-type <point> (make-point y) point? (x point-x set-point-x!) (y point-y))",
+    )
+    .unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+    let expected = parse(
+        &arena,
+        "(begin
+           (define <point> (%record-type 'point 'x 'y))
+           (define (make-point y) (%record <point> nil y))
+           (define (point? %object) (%record? <point> %object))
+           (define (point-x %object) (%record-ref <point> %object 0))
+           (define (set-point-x! %object %value) (%record-set! <point> %object 0 %value))
+           (define (point-y %object) (%record-ref <point> %object 1)))",
+    )
+    .unwrap();
+    assert_eq!(expanded, expected);
+
+    let root = parse(
+        &arena,
+        "(define-record-type point (make-point z) point? (x point-x))",
+    )
+    .unwrap();
+    assert!(expand(&arena, &root[..root.len()]).is_err());
+}
+
+#[test]
+fn test_record_definitions_check() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(
+        &arena,
+        "(define-record-type point (make-point x y) point? (x point-x) (y point-y))
+         (point-x (make-point 1 2))
+         (make-point 1)",
+    )
+    .unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+
+    let mut checker = Checker::new();
+    checker.declare("%record-type", Some(Arity::AtLeast(1)));
+    checker.declare("%record", Some(Arity::AtLeast(1)));
+    checker.declare("%record?", Some(Arity::Exact(2)));
+    checker.declare("%record-ref", Some(Arity::Exact(3)));
+
+    let diagnostics = checker.check(&arena, &expanded[..expanded.len()]);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+}
+
+#[test]
+fn test_record_image_round_trip() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let point = record_type(&arena, "point", &["x", "y"]).unwrap();
+    let a = make_record(&arena, &point, &[Atom::Int { inner: 1 }, Atom::Nil]).unwrap();
+    let b = make_record(&arena, &point, &[Atom::True, Atom::False]).unwrap();
+
+    let mut env = Env::new(&arena).unwrap();
+    env.define("a", a);
+    env.define("b", b);
+
+    let mut image = Vec::new();
+    snapshot(&env, &mut image).unwrap();
+    let restored = restore(&arena, &image).unwrap();
+
+    let a = restored.get("a").unwrap();
+    let b = restored.get("b").unwrap();
+    let Atom::Record { record } = a else {
+        panic!("expected a record");
+    };
+    assert_eq!(record.get(0), Some(Atom::Int { inner: 1 }));
+
+    let Atom::Record { record: other } = b else {
+        panic!("expected a record");
+    };
+    assert_eq!(is_record(&other.type_of(), &a), Ok(Atom::True));
+}
+
+#[test]
+fn test_evaluate_define_record_type() {
+    let block = Block::with_capacity(4 * 1024 * 1024);
+    let arena = block.arena(1024 * 1024).unwrap();
+
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+    interpreter
+        .run(
+            "(define-record-type point (make-point x y) point? (x point-x set-point-x!) (y point-y))
+             (define p (make-point 1 2))",
+        )
+        .unwrap();
+
+    assert_eq!(interpreter.run("(point? p)"), Ok(Atom::True));
+    assert_eq!(interpreter.run("(point? 5)"), Ok(Atom::False));
+    assert_eq!(interpreter.run("(point-y p)"), Ok(Atom::Int { inner: 2 }));
+    assert_eq!(
+        interpreter.run("(set-point-x! p 10) (point-x p)"),
+        Ok(Atom::Int { inner: 10 })
+    );
+    assert_eq!(interpreter.run("(eqv? p p)"), Ok(Atom::True));
+    assert_eq!(
+        interpreter.run("(eqv? p (make-point 10 2))"),
+        Ok(Atom::False)
+    );
+    assert!(interpreter.run("(point-x 5)").is_err());
+}
+
+#[test]
+fn test_image_keeps_same_shaped_types_apart() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let point = record_type(&arena, "point", &["x", "y"]).unwrap();
+    let other = record_type(&arena, "point", &["x", "y"]).unwrap();
+
+    let mut env = Env::new(&arena).unwrap();
+    env.define("point", point);
+    env.define(
+        "a",
+        make_record(&arena, &point, &[Atom::Nil, Atom::Nil]).unwrap(),
+    );
+    env.define(
+        "b",
+        make_record(&arena, &other, &[Atom::Nil, Atom::Nil]).unwrap(),
+    );
+
+    let mut image = Vec::new();
+    snapshot(&env, &mut image).unwrap();
+    let restored = restore(&arena, &image).unwrap();
+
+    let point = restored.get("point").unwrap();
+    assert_eq!(
+        is_record(&point, &restored.get("a").unwrap()),
+        Ok(Atom::True)
+    );
+    assert_eq!(
+        is_record(&point, &restored.get("b").unwrap()),
+        Ok(Atom::False)
+    );
+}
@@ -0,0 +1,178 @@
+use tyson::MemoryBlock as Block;
+use tyson::check::DiagnosticKind;
+use tyson::eval::Interpreter;
+use tyson::expand::expand;
+use tyson::image::{restore, snapshot};
+use tyson::read::{Atom, Expression, Span, parse};
+use tyson::sandbox::{Capability, Preset, capability};
+
+#[test]
+fn test_presets_by_name() {
+    assert_eq!("pure".parse(), Ok(Preset::Pure));
+    assert_eq!("io".parse(), Ok(Preset::Io));
+    assert_eq!("full".parse(), Ok(Preset::Full));
+    assert_eq!("root".parse::<Preset>(), Err("Unknown sandbox preset"));
+
+    assert!(Preset::Pure.allows(Capability::Pure));
+    assert!(!Preset::Pure.allows(Capability::Io));
+    assert!(Preset::Io.allows(Capability::Io));
+    assert!(!Preset::Io.allows(Capability::Clock));
+    assert!(Preset::Full.allows(Capability::Process));
+
+    assert_eq!(capability("car"), Some(Capability::Pure));
+    assert_eq!(capability("system"), Some(Capability::Process));
+    assert_eq!(capability("my-function"), None);
+}
+
+#[test]
+fn test_sandboxed_env_only_binds_allowed_primitives() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let pure = Preset::Pure.env(&arena).unwrap();
+    assert_eq!(pure.get("car"), Some(Atom::Primitive { name: "car" }));
+    assert_eq!(pure.get("display"), None);
+    assert_eq!(
+        Preset::Pure.lookup(&pure, "display"),
+        Err("Forbidden primitive")
+    );
+    assert_eq!(
+        Preset::Pure.lookup(&pure, "sleep"),
+        Err("Forbidden primitive")
+    );
+    assert_eq!(Preset::Pure.lookup(&pure, "nope"), Err("Unbound variable"));
+
+    let io = Preset::Io.env(&arena).unwrap();
+    assert!(io.contains("load"));
+    assert!(!io.contains("getenv"));
+
+    let full = Preset::Full.env(&arena).unwrap();
+    assert_eq!(
+        Preset::Full.lookup(&full, "current-time"),
+        Ok(Atom::Primitive {
+            name: "current-time"
+        })
+    );
+}
+
+#[test]
+fn test_checker_reports_forbidden_primitives() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(
+        &arena,
+        "(define (f x) (display (car x)) (system \"ls\") (unknown x))",
+    )
+    .unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+
+    let diagnostics = Preset::Pure
+        .checker()
+        .check(&arena, &expanded[..expanded.len()]);
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DiagnosticKind::Forbidden { name: "display" },
+            DiagnosticKind::Forbidden { name: "system" },
+            DiagnosticKind::Unbound { name: "unknown" },
+        ]
+    );
+    assert_eq!(
+        diagnostics.iter().next().unwrap().to_string(),
+        "`display` is not available in this sandbox"
+    );
+
+    let diagnostics = Preset::Io
+        .checker()
+        .check(&arena, &expanded[..expanded.len()]);
+    assert_eq!(diagnostics.len(), 2);
+
+    let diagnostics = Preset::Full
+        .checker()
+        .check(&arena, &expanded[..expanded.len()]);
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn test_user_definitions_shadow_forbidden_names() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(define (display x) x) (display 1)").unwrap();
+    let diagnostics = Preset::Pure.checker().check(&arena, &root[..root.len()]);
+    assert!(diagnostics.is_empty());
+
+    let mut env = Preset::Pure.env(&arena).unwrap();
+    env.define("display", Atom::Int { inner: 1 });
+    assert_eq!(
+        Preset::Pure.lookup(&env, "display"),
+        Ok(Atom::Int { inner: 1 })
+    );
+}
+
+#[test]
+fn test_primitive_image_round_trip() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let env = Preset::Pure.env(&arena).unwrap();
+    let mut image = Vec::new();
+    snapshot(&env, &mut image).unwrap();
+    let restored = restore(&arena, &image).unwrap();
+
+    assert_eq!(restored.get("cdr"), Some(Atom::Primitive { name: "cdr" }));
+    assert_eq!(restored.get("load"), None);
+}
+
+#[test]
+fn test_file_literals_need_io() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let file = [Expression {
+        depth: 0,
+        payload: Atom::File {
+            path: "secrets.tyson",
+            lazy: false,
+        },
+        span: Span::default(),
+    }];
+
+    let diagnostics = Preset::Pure.checker().check(&arena, &file);
+    assert_eq!(
+        diagnostics.iter().map(|d| d.kind).collect::<Vec<_>>(),
+        vec![DiagnosticKind::Forbidden { name: "load" }]
+    );
+    assert!(Preset::Io.checker().check(&arena, &file).is_empty());
+}
+
+#[test]
+fn test_evaluator_enforces_the_preset() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(256 * 1024).unwrap();
+
+    let pure = Preset::Pure.env(&arena).unwrap();
+    let mut interpreter = Interpreter::new(&arena, pure);
+    assert_eq!(interpreter.run("(car '(1 2))"), Ok(Atom::Int { inner: 1 }));
+    assert_eq!(interpreter.run("(display 1)"), Err("Forbidden primitive"));
+    assert_eq!(interpreter.run("(nope 1)"), Err("Unbound variable"));
+
+    let file = Expression {
+        depth: 0,
+        payload: Atom::File {
+            path: "secrets.tyson",
+            lazy: false,
+        },
+        span: Span::default(),
+    };
+    assert_eq!(interpreter.eval(&file), Err("Forbidden primitive"));
+    // Shadowing `load` does not let a file literal through.
+    interpreter.run("(define (load path) path)").unwrap();
+    assert_eq!(interpreter.eval(&file), Err("Forbidden primitive"));
+
+    let io = Preset::Io.env(&arena).unwrap();
+    let mut interpreter = Interpreter::new(&arena, io);
+    assert_eq!(interpreter.eval(&file), Err("Unable to load file"));
+}
@@ -0,0 +1,83 @@
+use std::io::Cursor;
+use tyson::MemoryBlock as Block;
+use tyson::eval::Interpreter;
+use tyson::print::print_value;
+use tyson::read::{Atom, ParseErrorKind, parse, parse_copy, parse_file, parse_reader};
+use tyson::sandbox::Preset;
+
+#[test]
+fn test_parse_borrowed_input() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let code = String::from("(define x \"hi\")");
+    let root = parse(&arena, &code).unwrap();
+    assert_eq!(root, parse(&arena, "(define x \"hi\")").unwrap());
+}
+
+#[test]
+fn test_parse_copy_outlives_source() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = {
+        let code = format!("(f {} \"{}\")", 42, "str");
+        parse_copy(&arena, &code).unwrap()
+    };
+    assert_eq!(root, parse(&arena, "(f 42 \"str\")").unwrap());
+}
+
+#[test]
+fn test_parse_reader() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse_reader(&arena, Cursor::new(b"(a b) [1 2]".to_vec())).unwrap();
+    assert_eq!(root.len(), 2);
+
+    let error = parse_reader(&arena, Cursor::new(b"(ok)\n  (\"\xff\")".to_vec())).unwrap_err();
+    assert_eq!(error.kind, ParseErrorKind::InvalidUtf8);
+    assert_eq!((error.span.line, error.span.column), (2, 5));
+}
+
+#[test]
+fn test_parse_file() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let path = std::env::temp_dir().join(format!("tyson-source-{}.tyson", std::process::id()));
+    std::fs::write(&path, "(define (sq x) (* x x))\n(sq 3)\n").unwrap();
+    let root = parse_file(&arena, &path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(root.unwrap().len(), 2);
+
+    let error = parse_file(&arena, "/nonexistent/file.tyson").unwrap_err();
+    assert_eq!(error.kind, ParseErrorKind::Io);
+}
+
+#[test]
+fn test_load_evaluates_a_file() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(256 * 1024).unwrap();
+
+    let path = std::env::temp_dir().join(format!("tyson-load-{}.tyson", std::process::id()));
+    std::fs::write(&path, "(define (sq x) (* x x))\n(sq 3)\n").unwrap();
+
+    let mut interpreter = Interpreter::new(&arena, Preset::Io.env(&arena).unwrap());
+    let code = format!(
+        "(define p (lazyload {:?})) (list (load {:?}) (sq 4))",
+        path, path
+    );
+    let loaded = interpreter.run(&code);
+    let forced = interpreter.run("(force p)");
+    std::fs::remove_file(&path).unwrap();
+
+    let mut text = String::new();
+    print_value(&mut text, &loaded.unwrap()).unwrap();
+    assert_eq!(text, "(9 16)");
+    assert_eq!(forced, Ok(Atom::Int { inner: 9 }));
+    assert_eq!(
+        interpreter.run("(load \"/nonexistent/file.tyson\")"),
+        Err("Unable to load file")
+    );
+}
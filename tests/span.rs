@@ -0,0 +1,85 @@
+use tyson::MemoryBlock as Block;
+use tyson::check::{Checker, DiagnosticKind};
+use tyson::expand::expand;
+use tyson::read::{Atom, Span, parse};
+
+fn span(start: u32, end: u32, line: u32, column: u32) -> Span {
+    Span {
+        start,
+        end,
+        line,
+        column,
+    }
+}
+
+#[test]
+fn test_atoms_and_lists_have_spans() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let code = "(define x 42)\n  (f \"hi\" [1 2])\n";
+    let root = parse(&arena, code).unwrap();
+
+    assert_eq!(root[0].span, span(0, 13, 1, 1));
+    assert_eq!(root[1].span, span(16, 30, 2, 3));
+    assert_eq!(&code[16..30], "(f \"hi\" [1 2])");
+
+    let Atom::List { body } = root[1].payload else {
+        panic!("expected a list");
+    };
+    assert_eq!(body[0].span, span(17, 18, 2, 4));
+    assert_eq!(body[1].span, span(19, 23, 2, 6));
+    assert_eq!(body[2].span, span(24, 29, 2, 11));
+
+    let Atom::Vector { body } = body[2].payload else {
+        panic!("expected a vector");
+    };
+    assert_eq!(body[1].span, span(27, 28, 2, 14));
+}
+
+#[test]
+fn test_quoted_forms_cover_the_quote() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "'(a b) 'sym ()").unwrap();
+    assert_eq!(root[0].span, span(0, 6, 1, 1));
+    assert_eq!(root[1].span, span(7, 11, 1, 8));
+    assert_eq!(root[2].span, span(12, 14, 1, 13));
+}
+
+#[test]
+fn test_spans_do_not_affect_equality() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let a = parse(&arena, "(+ 1 2)").unwrap();
+    let b = parse(&arena, "\n\n   (+   1 2)").unwrap();
+    assert_ne!(a[0].span, b[0].span);
+    assert_eq!(a, b);
+}
+
+#[test]
+fn test_expansion_keeps_the_original_span() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(f 1)\n(when (ok? x) (g x))").unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+
+    assert_eq!(expanded[1].span, root[1].span);
+    assert_eq!(expanded[1].span.line, 2);
+}
+
+#[test]
+fn test_diagnostics_point_at_the_source() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(define (f x)\n  (+ x y))").unwrap();
+    let diagnostics = Checker::new().check(&arena, &root[..root.len()]);
+
+    let diagnostic = diagnostics.iter().next().unwrap();
+    assert_eq!(diagnostic.kind, DiagnosticKind::Unbound { name: "y" });
+    assert_eq!(diagnostic.expr.span, span(21, 22, 2, 8));
+}
@@ -92,7 +92,7 @@ fn test_string_display_and_eq() {
     let s = builder.build();
 
     // Display uses as_str internally
-    let rendered = format!("{}", s);
+    let rendered = s.to_string();
     assert_eq!(rendered, "abcdef");
 
     // Equality between two Strings
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use tyson::MemoryBlock as Block;
+use tyson::eval::Interpreter;
+use tyson::image::{restore, snapshot};
+use tyson::read::{Atom, parse, parse_with};
+use tyson::sandbox::Preset;
+use tyson::symbol::{SymbolTable, eq, string_to_symbol, symbol_to_string};
+
+#[test]
+fn test_intern_returns_one_symbol_per_name() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut symbols = SymbolTable::new(&arena);
+
+    let a = symbols.intern("alpha").unwrap();
+    let b = symbols.intern(&String::from("alpha")).unwrap();
+    let c = symbols.intern("beta").unwrap();
+
+    assert_eq!(a, b);
+    assert_eq!(a.id(), b.id());
+    assert_ne!(a, c);
+    assert_eq!(a.name(), "alpha");
+    assert_eq!(symbols.len(), 2);
+    assert_eq!(symbols.get("gamma"), None);
+}
+
+#[test]
+fn test_reader_interns_across_sources() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut symbols = SymbolTable::new(&arena);
+
+    let first = parse_with(&arena, "(foo bar 'foo)", &mut symbols).unwrap();
+    let second = parse_with(&arena, "foo\n", &mut symbols).unwrap();
+
+    let Atom::List { body } = first[0].payload else {
+        panic!("expected a list");
+    };
+    assert_eq!(eq(&body[0].payload, &second[0].payload), Atom::True);
+    assert_eq!(eq(&body[0].payload, &body[2].payload), Atom::True);
+    assert_eq!(eq(&body[0].payload, &body[1].payload), Atom::False);
+    assert_eq!(eq(&body[0].payload, &Atom::Int { inner: 1 }), Atom::False);
+}
+
+#[test]
+fn test_parse_interns_within_a_program() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(x y x)").unwrap();
+    let Atom::List { body } = root[0].payload else {
+        panic!("expected a list");
+    };
+    assert_eq!(eq(&body[0].payload, &body[2].payload), Atom::True);
+}
+
+#[test]
+fn test_string_symbol_conversion() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut symbols = SymbolTable::new(&arena);
+
+    let read = parse_with(&arena, "hello\n", &mut symbols).unwrap();
+    let made = string_to_symbol(&mut symbols, &Atom::String { inner: "hello" }).unwrap();
+    assert_eq!(eq(&read[0].payload, &made), Atom::True);
+
+    assert_eq!(
+        symbol_to_string(&symbols, &made),
+        Ok(Atom::String { inner: "hello" })
+    );
+    assert!(symbol_to_string(&symbols, &Atom::Symbol { name: "unknown" }).is_err());
+    assert!(string_to_symbol(&mut symbols, &Atom::Nil).is_err());
+}
+
+#[test]
+fn test_symbols_as_keys() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut symbols = SymbolTable::new(&arena);
+
+    let mut values = HashMap::new();
+    values.insert(symbols.intern("x").unwrap(), 1);
+    values.insert(symbols.intern("y").unwrap(), 2);
+
+    assert_eq!(values.get(&symbols.intern("x").unwrap()), Some(&1));
+    assert_eq!(values.get(&symbols.get("y").unwrap()), Some(&2));
+}
+
+#[test]
+fn test_evaluate_symbol_conversion() {
+    let block = Block::with_capacity(4 * 1024 * 1024);
+    let arena = block.arena(1024 * 1024).unwrap();
+
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+    assert_eq!(
+        interpreter.run("(eq? (string->symbol \"apple\") 'apple)"),
+        Ok(Atom::True)
+    );
+    assert_eq!(
+        interpreter.run("(symbol->string 'apple)"),
+        Ok(Atom::String { inner: "apple" })
+    );
+    assert!(interpreter.run("(symbol->string 5)").is_err());
+}
+
+#[test]
+fn test_get_finds_interned_names_by_address() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut symbols = SymbolTable::new(&arena);
+
+    let symbol = symbols.intern("alpha").unwrap();
+    assert_eq!(symbols.get(symbol.name()), Some(symbol));
+    assert_eq!(symbols.get(&symbol.name()[..3]), None);
+    assert_eq!(symbols.get(&String::from("alpha")), Some(symbol));
+}
+
+#[test]
+fn test_resolve_interns_made_up_names() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+    let mut symbols = SymbolTable::new(&arena);
+
+    let mut root = parse(&arena, "(f 'f)").unwrap();
+    let read = parse_with(&arena, "f\n", &mut symbols).unwrap();
+    symbols.resolve(&mut root).unwrap();
+
+    let Atom::List { body } = root[0].payload else {
+        panic!("expected a list");
+    };
+    assert_eq!(eq(&body[0].payload, &read[0].payload), Atom::True);
+    assert_eq!(eq(&body[1].payload, &read[0].payload), Atom::True);
+}
+
+#[test]
+fn test_loaded_symbols_share_the_table() {
+    let block = Block::with_capacity(4 * 1024 * 1024);
+    let arena = block.arena(1024 * 1024).unwrap();
+
+    let path = std::env::temp_dir().join(format!("tyson-symbol-{}.tyson", std::process::id()));
+    std::fs::write(&path, "(define loaded 'pear)\n").unwrap();
+
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+    let code = format!("(load {:?}) (eq? loaded (string->symbol \"pear\"))", path);
+    let result = interpreter.run(&code);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(result, Ok(Atom::True));
+}
+
+#[test]
+fn test_restored_symbols_share_the_table() {
+    let block = Block::with_capacity(4 * 1024 * 1024);
+    let arena = block.arena(1024 * 1024).unwrap();
+
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+    interpreter.run("(define fruit 'plum)").unwrap();
+    let mut image = Vec::new();
+    snapshot(&interpreter.env(), &mut image).unwrap();
+
+    let env = restore(&arena, &image).unwrap();
+    let mut restored = Interpreter::new(&arena, env);
+    assert_eq!(
+        restored.run("(eq? fruit (string->symbol \"plum\"))"),
+        Ok(Atom::True)
+    );
+}
@@ -0,0 +1,127 @@
+use tyson::MemoryBlock as Block;
+use tyson::eval::Interpreter;
+use tyson::expand::expand;
+use tyson::print::print_value;
+use tyson::read::{Atom, parse};
+use tyson::sandbox::Preset;
+use tyson::thread::{Parcel, spawn};
+
+#[test]
+fn test_block_arenas() {
+    let block = Block::with_capacity(4096);
+    let arenas = block.arenas(4, 1024).unwrap();
+
+    assert_eq!(arenas.len(), 4);
+    assert_eq!(block.len(), 4096);
+    assert!(arenas.iter().all(|arena| arena.capacity() == 1024));
+    assert!(block.arenas(1, 1).is_none());
+}
+
+#[test]
+fn test_block_arenas_too_large() {
+    let block = Block::with_capacity(4096);
+
+    assert!(block.arenas(5, 1024).is_none());
+    assert!(block.arenas(2, usize::MAX).is_none());
+    assert_eq!(block.len(), 0);
+}
+
+#[test]
+fn test_parcel_round_trip() {
+    let block = Block::with_capacity(1024 * 1024);
+    let source = block.arena(64 * 1024).unwrap();
+    let target = block.arena(64 * 1024).unwrap();
+
+    let exprs = parse(&source, "(define (f x) (list x \"x\" 'x 1.5))").unwrap();
+    let parcel = Parcel::pack(&exprs[..exprs.len()]);
+    let copy = parcel.unpack(&target).unwrap();
+
+    assert_eq!(copy, exprs);
+    assert!(!parcel.is_empty());
+}
+
+#[test]
+fn test_spawn_workers() {
+    let block = Block::with_capacity(1024 * 1024);
+    let main = block.arena(64 * 1024).unwrap();
+    let workers = block.arenas(4, 64 * 1024).unwrap();
+
+    let exprs = parse(&main, "(-> x (f 1) (g 2))").unwrap();
+    let parcel = Parcel::pack(&exprs[..exprs.len()]);
+
+    let results: Vec<Parcel> = std::thread::scope(|scope| {
+        let handles: Vec<_> = workers
+            .into_iter()
+            .map(|arena| {
+                spawn(scope, arena, parcel.clone(), |arena, exprs| {
+                    expand(arena, &exprs[..exprs.len()])
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap().unwrap())
+            .collect()
+    });
+
+    let expected = parse(&main, "(g (f x 1) 2)").unwrap();
+    for result in results {
+        assert_eq!(result.unpack(&main).unwrap(), expected);
+    }
+}
+
+#[test]
+fn test_spawn_worker_error() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let result = std::thread::scope(|scope| {
+        spawn(scope, arena, Parcel::pack(&[]), |_, _| Err("failed"))
+            .join()
+            .unwrap()
+    });
+
+    assert_eq!(result, Err("failed"));
+}
+
+#[test]
+fn test_spawn_and_join_tasks() {
+    let block = Block::with_capacity(64 * 1024 * 1024);
+    let arena = block.arena(32 * 1024 * 1024).unwrap();
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+
+    let value = interpreter
+        .run(
+            "(define (fib n) (if (< n 2) n (+ (fib (- n 1)) (fib (- n 2)))))
+             (define t1 (spawn fib 10))
+             (define t2 (spawn fib 15))
+             (define t3 (spawn fib 20))
+             (list (join t1) (join t2) (join t3))",
+        )
+        .unwrap();
+    let mut text = String::new();
+    print_value(&mut text, &value).unwrap();
+    assert_eq!(text, "(55 610 6765)");
+
+    let task = interpreter.run("(spawn list 1 \"two\" 'three)").unwrap();
+    assert!(matches!(task, Atom::Task { .. }));
+    let value = interpreter.apply(Atom::Primitive { name: "join" }, &[task]);
+    let mut text = String::new();
+    print_value(&mut text, &value.unwrap()).unwrap();
+    assert_eq!(text, "(1 \"two\" three)");
+
+    assert_eq!(
+        interpreter.apply(Atom::Primitive { name: "join" }, &[task]),
+        Err("Task already joined")
+    );
+    assert_eq!(interpreter.run("(join (spawn car '()))"), Err("Not a pair"));
+    assert_eq!(
+        interpreter.run("(let ((n 1)) (spawn (lambda () n)))"),
+        Err("Unable to send procedure")
+    );
+    assert_eq!(
+        interpreter.run("(join (spawn (lambda () fib)))"),
+        Err("Unable to send value")
+    );
+}
@@ -0,0 +1,175 @@
+use tyson::MemoryBlock as Block;
+use tyson::check::{Checker, DiagnosticKind};
+use tyson::eval::Interpreter;
+use tyson::expand::expand;
+use tyson::read::{Atom, parse};
+use tyson::sandbox::Preset;
+use tyson::types::{Type, TypeChecker};
+
+fn kinds(code: &'static str) -> Vec<String> {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(256 * 1024).unwrap();
+
+    let root = parse(&arena, code).unwrap();
+    let diagnostics = TypeChecker::new().check(&arena, &root[..root.len()]);
+    diagnostics.iter().map(|d| d.to_string()).collect()
+}
+
+#[test]
+fn test_annotated_function_checks() {
+    assert!(kinds("(define (f (x : Int)) : Int (+ x 1)) (f 2)").is_empty());
+    assert_eq!(
+        kinds("(define (f (x : Int)) : Int (+ x 1)) (f \"two\")"),
+        ["expected a value of type Int but found String"]
+    );
+    assert_eq!(
+        kinds("(define (f (x : Int)) : String (* x 2))"),
+        ["expected a value of type String but found Int"]
+    );
+}
+
+#[test]
+fn test_calls_before_definition() {
+    assert_eq!(
+        kinds("(define (g) (f #t)) (define (f (b : Bool) (n : Number)) : Bool b) (f #t 1.5)"),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        kinds("(define (g) (f 1 1)) (define (f (b : Bool) (n : Number)) : Bool b)"),
+        ["expected a value of type Bool but found Int"]
+    );
+}
+
+#[test]
+fn test_unannotated_code_is_unchecked() {
+    assert!(kinds("(define (f x) (+ x 1)) (f \"anything\") (define y (f #t))").is_empty());
+    assert!(kinds("(lambda (a b) (if a b 1))").is_empty());
+}
+
+#[test]
+fn test_local_inference() {
+    assert_eq!(
+        kinds("(define (f (s : String)) : Int (let ((n 1) (m 2.5)) (+ n m)))"),
+        ["expected a value of type Int but found Float"]
+    );
+    assert_eq!(
+        kinds("(define x 1) (define (f (s : String)) s) (f x)"),
+        ["expected a value of type String but found Int"]
+    );
+    assert_eq!(
+        kinds("(define x : Int 1) (set! x \"one\") (< x #\\a)"),
+        [
+            "expected a value of type Int but found String",
+            "expected a value of type Number but found Char"
+        ]
+    );
+}
+
+#[test]
+fn test_set_only_checks_declared_types() {
+    assert!(kinds("(define x 1) (set! x 2.5)").is_empty());
+    assert!(kinds("(let ((x 1)) (set! x 0.5) x)").is_empty());
+    assert!(kinds("(define (f) 1) (set! f (lambda (a) a))").is_empty());
+    assert_eq!(
+        kinds("(define (f (n : Int)) (set! n 0.5) n)"),
+        ["expected a value of type Int but found Float"]
+    );
+}
+
+#[test]
+fn test_function_types() {
+    assert!(
+        kinds(
+            "(define (apply (f : (-> Int Int)) (x : Int)) : Int (f x))
+             (apply (lambda ((n : Int)) : Int n) 1)"
+        )
+        .is_empty()
+    );
+    assert_eq!(
+        kinds(
+            "(define (apply (f : (-> Int Int)) (x : Int)) : Int (f x))
+             (apply (lambda ((s : String)) : Int 1) 1)"
+        ),
+        ["expected a value of type (-> Int Int) but found (-> String Int)"]
+    );
+}
+
+#[test]
+fn test_unknown_types() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(&arena, "(define (f (x : Integer)) : Int x)").unwrap();
+    let diagnostics = TypeChecker::new().check(&arena, &root[..root.len()]);
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind).collect();
+    assert_eq!(kinds, [DiagnosticKind::UnknownType { name: "Integer" }]);
+}
+
+#[test]
+fn test_declared_globals() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let mut checker = TypeChecker::new();
+    checker.declare("limit", Type::Int);
+
+    let root = parse(&arena, "(define (f (s : String)) s) (f limit)").unwrap();
+    let diagnostics = checker.check(&arena, &root[..root.len()]);
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn test_expand_erases_annotations() {
+    let block = Block::with_capacity(1024 * 1024);
+    let arena = block.arena(64 * 1024).unwrap();
+
+    let root = parse(
+        &arena,
+        "(define (f (x : Int) y) : Int (-> x (g y)))
+         (define n : Int 1)
+         (lambda ((s : String)) : String s)
+         (define (h x) x)",
+    )
+    .unwrap();
+    let expanded = expand(&arena, &root[..root.len()]).unwrap();
+    let expected = parse(
+        &arena,
+        "(define (f x y) (g x y))
+         (define n 1)
+         (lambda (s) s)
+         (define (h x) x)",
+    )
+    .unwrap();
+    assert_eq!(expanded, expected);
+
+    let mut checker = Checker::new();
+    checker.declare("g", None);
+    let diagnostics = checker.check(&arena, &expanded[..expanded.len()]);
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+}
+
+#[test]
+fn test_evaluate_annotated_code() {
+    let block = Block::with_capacity(4 * 1024 * 1024);
+    let arena = block.arena(1024 * 1024).unwrap();
+
+    let mut interpreter = Interpreter::new(&arena, Preset::Full.env(&arena).unwrap());
+    assert_eq!(
+        interpreter.run(
+            "(define (square (x : Int)) : Int (* x x))
+             (define n : Int 4)
+             ((lambda ((y : Int)) : Int (+ y (square n))) 1)"
+        ),
+        Ok(Atom::Int { inner: 17 })
+    );
+}
+
+#[test]
+fn test_annotated_definition_binds_its_type() {
+    // The later plain definition hoists `x` as Any; the annotated one must
+    // still give it Number while `y` is checked.
+    assert_eq!(
+        kinds("(define x : Number 1) (define y : Int x) (define x 2)"),
+        ["expected a value of type Int but found Number"]
+    );
+}